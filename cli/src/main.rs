@@ -1,5 +1,9 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
 use clap::Parser as _;
-use food::api;
+use dioxus::fullstack::{HeaderMap, HeaderValue};
+use food::{api, models};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -13,6 +17,22 @@ async fn main() -> anyhow::Result<()> {
         dioxus::fullstack::set_server_url(url);
     }
 
+    let token = match args.token {
+        Some(token) => Some(token),
+        None => match args.config.or_else(default_config_path) {
+            Some(path) => config_token(&path)?,
+            None => None,
+        },
+    };
+    if let Some(token) = token {
+        let mut authorization = HeaderValue::from_str(&format!("Bearer {token}"))
+            .context("the API token contains invalid characters")?;
+        authorization.set_sensitive(true);
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", authorization);
+        dioxus::fullstack::set_request_headers(headers);
+    }
+
     args.command.run().await?;
 
     Ok(())
@@ -23,15 +43,48 @@ struct Args {
     #[arg(long, default_value_t = 8080)]
     port: u16,
 
+    /// A personal API token to act as its user, created on the user page.
+    #[arg(long, env = "FOOD_API_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// A config file with a `token = "..."` line, used when no token is given otherwise. Defaults
+    /// to `food/config` in `$XDG_CONFIG_HOME` or `~/.config`.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Command,
 }
 
+fn default_config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config_home.join("food").join("config"))
+}
+
+/// The token in a config file, a missing file has no token.
+fn config_token(path: &Path) -> anyhow::Result<Option<String>> {
+    let config = match std::fs::read_to_string(path) {
+        Ok(config) => config,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+    let token = config.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "token").then(|| value.trim().trim_matches('"').to_string())
+    });
+    Ok(token)
+}
+
 #[derive(clap::Subcommand)]
 enum Command {
     AddUser(AddUser),
     DeleteUser(DeleteUser),
     ChangePassword(ChangePassword),
+    SetRoles(SetRoles),
 }
 
 impl Command {
@@ -54,6 +107,11 @@ impl Command {
                 .await?;
                 println!("Password changed successfully");
             }
+            Self::SetRoles(set_roles) => {
+                let roles = set_roles.roles.into_iter().map(Into::into).collect();
+                api::user::set_roles(set_roles.email, roles).await?;
+                println!("Roles changed successfully");
+            }
         }
         Ok(())
     }
@@ -77,3 +135,28 @@ struct ChangePassword {
     current_password: String,
     new_password: String,
 }
+
+/// Replace the roles of a user, new users only view recipes until they get more.
+#[derive(clap::Args)]
+struct SetRoles {
+    email: String,
+    #[arg(required = true)]
+    roles: Vec<Role>,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Role {
+    Admin,
+    Editor,
+    Viewer,
+}
+
+impl From<Role> for models::Role {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Admin => Self::Admin,
+            Role::Editor => Self::Editor,
+            Role::Viewer => Self::Viewer,
+        }
+    }
+}
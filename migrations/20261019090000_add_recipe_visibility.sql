@@ -0,0 +1,80 @@
+-- Households are joined with a code that their members pass on.
+CREATE TABLE household (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    invite_code TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE household_member (
+    household_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    PRIMARY KEY (household_id, user_id),
+    FOREIGN KEY (household_id) REFERENCES household (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
+
+-- Recipes that existed before visibility was introduced have no owner and stay public. Titles
+-- only have to be unique among the recipes of an owner, a title that anyone has taken would give
+-- away the private recipes of others. SQLite can't drop the UNIQUE constraint of a column, so the
+-- table is created anew. Instructions and ingredients lose their recipes in between, which
+-- their foreign keys only check once the recipes are back.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE old_recipe AS
+SELECT
+    id,
+    title,
+    description,
+    meal_type_id,
+    source_id,
+    creation_date
+FROM recipe;
+
+DROP TABLE recipe;
+
+CREATE TABLE recipe (
+    id INTEGER PRIMARY KEY NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    meal_type_id INTEGER NOT NULL,
+    source_id INTEGER NOT NULL,
+    creation_date TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%d', 'NOW')),
+    owner_id INTEGER REFERENCES user (id) ON DELETE SET NULL,
+    household_id INTEGER REFERENCES household (id) ON DELETE SET NULL,
+    visibility TEXT NOT NULL DEFAULT 'public'
+    CHECK (visibility IN ('private', 'household', 'public')),
+    FOREIGN KEY (meal_type_id) REFERENCES meal_type (id),
+    FOREIGN KEY (source_id) REFERENCES source (id)
+);
+
+INSERT INTO recipe
+(id, title, description, meal_type_id, source_id, creation_date)
+SELECT
+    id,
+    title,
+    description,
+    meal_type_id,
+    source_id,
+    creation_date
+FROM old_recipe;
+
+DROP TABLE old_recipe;
+
+CREATE UNIQUE INDEX recipe_owner_title ON recipe (owner_id, title);
+
+-- Who may see which recipes that aren't public: owners their own, and members of a household what
+-- is shared with it. Everyone may see public recipes, so queries filter on
+-- `r.visibility = 'public' OR EXISTS (... WHERE viewer_id = ?)` rather than listing every public
+-- recipe for every user here. Someone who isn't logged in has a NULL id and only sees public ones.
+CREATE VIEW recipe_viewer AS
+SELECT r.id AS recipe_id, r.owner_id AS viewer_id
+FROM recipe AS r
+WHERE r.visibility != 'public' AND r.owner_id IS NOT NULL
+UNION ALL
+SELECT r.id, hm.user_id
+FROM
+    recipe AS r
+JOIN
+    household_member AS hm
+    ON r.household_id = hm.household_id
+WHERE r.visibility = 'household' AND hm.user_id IS NOT r.owner_id;
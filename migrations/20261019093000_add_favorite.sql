@@ -0,0 +1,7 @@
+CREATE TABLE favorite (
+    user_id INTEGER NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    PRIMARY KEY (user_id, recipe_id),
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE,
+    FOREIGN KEY (recipe_id) REFERENCES recipe (id) ON DELETE CASCADE
+);
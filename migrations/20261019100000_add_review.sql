@@ -0,0 +1,11 @@
+CREATE TABLE review (
+    id INTEGER PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    rating INTEGER NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    text TEXT,
+    creation_date TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%d', 'NOW')),
+    UNIQUE (recipe_id, user_id),
+    FOREIGN KEY (recipe_id) REFERENCES recipe (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
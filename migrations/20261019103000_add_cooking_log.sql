@@ -0,0 +1,13 @@
+CREATE TABLE cooking_log (
+    id INTEGER PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    cooked_on TEXT NOT NULL,
+    servings INTEGER NOT NULL CHECK (servings > 0),
+    notes TEXT,
+    FOREIGN KEY (recipe_id) REFERENCES recipe (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX cooking_log_recipe_id ON cooking_log (recipe_id);
+CREATE INDEX cooking_log_user_id ON cooking_log (user_id);
@@ -0,0 +1,34 @@
+CREATE TABLE collection (
+    id INTEGER PRIMARY KEY NOT NULL,
+    owner_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    household_id INTEGER,
+    visibility TEXT NOT NULL DEFAULT 'private'
+    CHECK (visibility IN ('private', 'household', 'public')),
+    FOREIGN KEY (owner_id) REFERENCES user (id) ON DELETE CASCADE,
+    FOREIGN KEY (household_id) REFERENCES household (id) ON DELETE SET NULL
+);
+
+CREATE TABLE collection_recipe (
+    collection_id INTEGER NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (collection_id, recipe_id),
+    FOREIGN KEY (collection_id) REFERENCES collection (id) ON DELETE CASCADE,
+    FOREIGN KEY (recipe_id) REFERENCES recipe (id) ON DELETE CASCADE
+);
+
+-- The same rules as for recipes in `recipe_viewer`.
+CREATE VIEW collection_viewer AS
+SELECT c.id AS collection_id, c.owner_id AS viewer_id
+FROM collection AS c
+WHERE c.visibility != 'public'
+UNION ALL
+SELECT c.id, hm.user_id
+FROM
+    collection AS c
+JOIN
+    household_member AS hm
+    ON c.household_id = hm.household_id
+WHERE c.visibility = 'household' AND hm.user_id != c.owner_id;
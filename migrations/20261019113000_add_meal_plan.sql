@@ -0,0 +1,12 @@
+CREATE TABLE meal_plan_entry (
+    id INTEGER PRIMARY KEY NOT NULL,
+    user_id INTEGER NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    planned_on TEXT NOT NULL,
+    meal_slot TEXT NOT NULL CHECK (meal_slot IN ('breakfast', 'lunch', 'dinner')),
+    servings INTEGER NOT NULL CHECK (servings > 0),
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE,
+    FOREIGN KEY (recipe_id) REFERENCES recipe (id) ON DELETE CASCADE
+);
+
+CREATE INDEX meal_plan_entry_user_id_planned_on ON meal_plan_entry (user_id, planned_on);
@@ -0,0 +1,21 @@
+-- Recipes that existed before servings were introduced are assumed to serve four.
+ALTER TABLE recipe ADD COLUMN servings INTEGER NOT NULL DEFAULT 4 CHECK (servings > 0);
+
+CREATE TABLE shopping_list (
+    id INTEGER PRIMARY KEY NOT NULL,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    creation_date TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%d', 'NOW')),
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
+
+CREATE TABLE shopping_list_item (
+    id INTEGER PRIMARY KEY NOT NULL,
+    shopping_list_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    quantity REAL NOT NULL,
+    unit TEXT NOT NULL,
+    checked BOOLEAN NOT NULL DEFAULT FALSE,
+    position INTEGER NOT NULL,
+    FOREIGN KEY (shopping_list_id) REFERENCES shopping_list (id) ON DELETE CASCADE
+);
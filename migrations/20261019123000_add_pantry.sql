@@ -0,0 +1,13 @@
+CREATE TABLE pantry_item (
+    id INTEGER PRIMARY KEY NOT NULL,
+    user_id INTEGER NOT NULL,
+    ingredient_id INTEGER NOT NULL,
+    -- How much is left is optional, but when it's known so is the unit it's measured in.
+    quantity REAL CHECK (quantity > 0),
+    unit TEXT,
+    expires_on TEXT,
+    CHECK ((quantity IS NULL) = (unit IS NULL)),
+    UNIQUE (user_id, ingredient_id),
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE,
+    FOREIGN KEY (ingredient_id) REFERENCES ingredient (id) ON DELETE CASCADE
+);
@@ -0,0 +1,72 @@
+-- Nutrition facts per 100 g, keyed by the name of the ingredient so that they also apply to
+-- ingredients that are added later.
+CREATE TABLE nutrition (
+    ingredient TEXT PRIMARY KEY NOT NULL COLLATE NOCASE,
+    kcal REAL NOT NULL CHECK (kcal >= 0),
+    protein REAL NOT NULL CHECK (protein >= 0),
+    fat REAL NOT NULL CHECK (fat >= 0),
+    carbohydrates REAL NOT NULL CHECK (carbohydrates >= 0)
+);
+
+-- What a millilitre or a piece of an ingredient weighs, to turn volumes and counts into grams.
+CREATE TABLE ingredient_weight (
+    ingredient TEXT PRIMARY KEY NOT NULL COLLATE NOCASE,
+    grams_per_ml REAL CHECK (grams_per_ml > 0),
+    grams_per_piece REAL CHECK (grams_per_piece > 0)
+);
+
+INSERT INTO nutrition
+(ingredient, kcal, protein, fat, carbohydrates)
+VALUES
+('apple', 52, 0.3, 0.2, 13.8),
+('banana', 89, 1.1, 0.3, 22.8),
+('butter', 717, 0.9, 81.1, 0.1),
+('carrot', 41, 0.9, 0.2, 9.6),
+('cheese', 403, 24.9, 33.1, 1.3),
+('chicken breast', 165, 31.0, 3.6, 0),
+('cream', 340, 2.8, 36.1, 2.7),
+('egg', 143, 12.6, 9.5, 0.7),
+('flour', 364, 10.3, 1.0, 76.3),
+('garlic', 149, 6.4, 0.5, 33.1),
+('ground beef', 254, 17.2, 20.0, 0),
+('honey', 304, 0.3, 0, 82.4),
+('lemon', 29, 1.1, 0.3, 9.3),
+('lettuce', 15, 1.4, 0.2, 2.9),
+('milk', 61, 3.2, 3.3, 4.8),
+('oats', 389, 16.9, 6.9, 66.3),
+('olive oil', 884, 0, 100, 0),
+('onion', 40, 1.1, 0.1, 9.3),
+('pasta', 371, 13.0, 1.5, 74.7),
+('potato', 77, 2.0, 0.1, 17.5),
+('rice', 365, 7.1, 0.7, 80.0),
+('salt', 0, 0, 0, 0),
+('sugar', 387, 0, 0, 100),
+('tomato', 18, 0.9, 0.2, 3.9),
+('water', 0, 0, 0, 0),
+('yogurt', 61, 3.5, 3.3, 4.7);
+
+INSERT INTO ingredient_weight
+(ingredient, grams_per_ml, grams_per_piece)
+VALUES
+('apple', NULL, 180),
+('banana', NULL, 120),
+('butter', 0.91, NULL),
+('carrot', NULL, 60),
+('cream', 1.0, NULL),
+('egg', NULL, 50),
+('flour', 0.53, NULL),
+('garlic', NULL, 5),
+('honey', 1.42, NULL),
+('lemon', NULL, 100),
+('lettuce', NULL, 300),
+('milk', 1.03, NULL),
+('oats', 0.41, NULL),
+('olive oil', 0.91, NULL),
+('onion', NULL, 110),
+('potato', NULL, 170),
+('rice', 0.85, NULL),
+('salt', 1.2, NULL),
+('sugar', 0.85, NULL),
+('tomato', NULL, 120),
+('water', 1.0, NULL),
+('yogurt', 1.03, NULL);
@@ -0,0 +1,18 @@
+CREATE TABLE store (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT UNIQUE NOT NULL COLLATE NOCASE
+);
+
+-- Prices are keyed by the name of the ingredient, like nutrition facts. A price applies from its
+-- effective date until the next price of the same ingredient in the same store.
+CREATE TABLE ingredient_price (
+    id INTEGER PRIMARY KEY NOT NULL,
+    ingredient TEXT NOT NULL COLLATE NOCASE,
+    store_id INTEGER NOT NULL,
+    -- The price of one `unit` of the ingredient.
+    price REAL NOT NULL CHECK (price >= 0),
+    unit TEXT NOT NULL,
+    effective_from TEXT NOT NULL,
+    UNIQUE (ingredient, store_id, effective_from),
+    FOREIGN KEY (store_id) REFERENCES store (id) ON DELETE CASCADE
+);
@@ -0,0 +1,15 @@
+-- An immutable snapshot of the editable parts of a recipe, taken on every edit. The snapshot is
+-- the JSON of the title, description, servings, ingredients and instructions, so that later
+-- changes to the recipe tables don't rewrite history.
+CREATE TABLE recipe_revision (
+    id INTEGER PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    -- Counts up from 1 per recipe.
+    number INTEGER NOT NULL CHECK (number > 0),
+    author_id INTEGER,
+    created_at TEXT NOT NULL DEFAULT (STRFTIME('%Y-%m-%d %H:%M:%S', 'NOW')),
+    snapshot TEXT NOT NULL,
+    UNIQUE (recipe_id, number),
+    FOREIGN KEY (recipe_id) REFERENCES recipe (id) ON DELETE CASCADE,
+    FOREIGN KEY (author_id) REFERENCES user (id) ON DELETE SET NULL
+);
@@ -0,0 +1,4 @@
+-- The recipe a recipe was forked from, variations outlive the recipe they started from.
+ALTER TABLE recipe ADD COLUMN forked_from INTEGER REFERENCES recipe (id) ON DELETE SET NULL;
+
+CREATE INDEX recipe_forked_from ON recipe (forked_from);
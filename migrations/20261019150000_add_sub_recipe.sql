@@ -0,0 +1,30 @@
+-- A recipe ingredient is either an ingredient in a unit, or another recipe, a sub-recipe, with
+-- its quantity in servings of that recipe. SQLite can't drop a NOT NULL constraint, so the table
+-- is rebuilt. Nothing references recipe_ingredient, so the ids are simply carried over.
+CREATE TABLE recipe_ingredient_new (
+    id INTEGER PRIMARY KEY NOT NULL,
+    quantity REAL NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    ingredient_id INTEGER,
+    measurement_id INTEGER,
+    sub_recipe_id INTEGER,
+    FOREIGN KEY (recipe_id) REFERENCES recipe (id),
+    FOREIGN KEY (ingredient_id) REFERENCES ingredient (id),
+    FOREIGN KEY (measurement_id) REFERENCES measurement (id),
+    FOREIGN KEY (sub_recipe_id) REFERENCES recipe (id),
+    CHECK (
+        (ingredient_id IS NOT NULL AND measurement_id IS NOT NULL AND sub_recipe_id IS NULL)
+        OR (ingredient_id IS NULL AND measurement_id IS NULL AND sub_recipe_id IS NOT NULL)
+    ),
+    CHECK (sub_recipe_id != recipe_id)
+);
+
+INSERT INTO recipe_ingredient_new (id, quantity, recipe_id, ingredient_id, measurement_id)
+SELECT id, quantity, recipe_id, ingredient_id, measurement_id
+FROM recipe_ingredient;
+
+DROP TABLE recipe_ingredient;
+
+ALTER TABLE recipe_ingredient_new RENAME TO recipe_ingredient;
+
+CREATE INDEX recipe_ingredient_sub_recipe_id ON recipe_ingredient (sub_recipe_id);
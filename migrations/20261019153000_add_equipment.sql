@@ -0,0 +1,23 @@
+CREATE TABLE equipment (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
+CREATE TABLE recipe_equipment (
+    recipe_id INTEGER NOT NULL,
+    equipment_id INTEGER NOT NULL,
+    PRIMARY KEY (recipe_id, equipment_id),
+    FOREIGN KEY (recipe_id) REFERENCES recipe (id) ON DELETE CASCADE,
+    FOREIGN KEY (equipment_id) REFERENCES equipment (id) ON DELETE CASCADE
+);
+
+-- Users are assumed to own all equipment, except what they have marked as not owned.
+CREATE TABLE unowned_equipment (
+    user_id INTEGER NOT NULL,
+    equipment_id INTEGER NOT NULL,
+    PRIMARY KEY (user_id, equipment_id),
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE,
+    FOREIGN KEY (equipment_id) REFERENCES equipment (id) ON DELETE CASCADE
+);
+
+CREATE INDEX recipe_equipment_equipment_id ON recipe_equipment (equipment_id);
@@ -0,0 +1,12 @@
+-- `ratio` is how much of the replacement to use for every one of the ingredient, in the same unit.
+CREATE TABLE substitution (
+    id INTEGER PRIMARY KEY NOT NULL,
+    ingredient_id INTEGER NOT NULL,
+    replacement_id INTEGER NOT NULL,
+    ratio REAL NOT NULL CHECK (ratio > 0),
+    note TEXT NOT NULL DEFAULT '',
+    CHECK (ingredient_id != replacement_id),
+    UNIQUE (ingredient_id, replacement_id),
+    FOREIGN KEY (ingredient_id) REFERENCES ingredient (id) ON DELETE CASCADE,
+    FOREIGN KEY (replacement_id) REFERENCES ingredient (id) ON DELETE CASCADE
+);
@@ -0,0 +1,46 @@
+CREATE TABLE role (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL UNIQUE
+);
+
+-- Roles don't inherit from each other, every role lists all of its permissions.
+CREATE TABLE role_permission (
+    role_id INTEGER NOT NULL,
+    permission TEXT NOT NULL,
+    PRIMARY KEY (role_id, permission),
+    FOREIGN KEY (role_id) REFERENCES role (id) ON DELETE CASCADE
+);
+
+CREATE TABLE user_role (
+    user_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+    PRIMARY KEY (user_id, role_id),
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES role (id) ON DELETE CASCADE
+);
+
+INSERT INTO role
+(id, name)
+VALUES
+(1, 'admin'),
+(2, 'editor'),
+(3, 'viewer');
+
+INSERT INTO role_permission
+(role_id, permission)
+VALUES
+(1, 'users:manage'),
+(1, 'recipes:edit'),
+(1, 'recipes:view'),
+(2, 'recipes:edit'),
+(2, 'recipes:view'),
+(3, 'recipes:view');
+
+-- Someone has to manage the existing accounts, the first user to register does. Everyone could
+-- edit their recipes before.
+INSERT INTO user_role
+(user_id, role_id)
+SELECT
+    id,
+    CASE WHEN id = (SELECT MIN(id) FROM user) THEN 1 ELSE 2 END
+FROM user;
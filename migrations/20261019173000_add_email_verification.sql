@@ -0,0 +1,13 @@
+-- Users who were added before registration existed were added by an admin, they count as
+-- verified.
+ALTER TABLE user ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE user SET email_verified = TRUE;
+
+-- Single use tokens that are sent to users, only their hash is stored.
+CREATE TABLE user_token (
+    token_hash TEXT PRIMARY KEY NOT NULL,
+    user_id INTEGER NOT NULL,
+    purpose TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
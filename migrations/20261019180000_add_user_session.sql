@@ -0,0 +1,9 @@
+-- The sessions users are logged in with, a session that isn't listed here is logged out. This
+-- allows ending every session of a user at once, like when their password changes. Sessions from
+-- before this table existed aren't listed, so everyone logs in again once.
+CREATE TABLE user_session (
+    session_id TEXT PRIMARY KEY NOT NULL,
+    user_id INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
@@ -0,0 +1,20 @@
+-- Failed logins per account and per client address, to slow down guessing passwords. Times are
+-- unix timestamps in seconds.
+CREATE TABLE login_failure (
+    kind TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    failures INTEGER NOT NULL,
+    last_failed_at INTEGER NOT NULL,
+    locked_until INTEGER,
+    PRIMARY KEY (kind, subject)
+);
+
+-- Security relevant events, like accounts getting locked.
+CREATE TABLE security_log (
+    id INTEGER PRIMARY KEY NOT NULL,
+    event TEXT NOT NULL,
+    email TEXT,
+    client TEXT,
+    details TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,17 @@
+-- Two-factor authentication with time-based one-time passwords. The secret has to be stored as is
+-- to compute codes, it only counts once the user confirmed it with a first code.
+CREATE TABLE user_totp (
+    user_id INTEGER PRIMARY KEY NOT NULL,
+    secret TEXT NOT NULL,
+    confirmed BOOLEAN NOT NULL DEFAULT FALSE,
+    -- The time step of the last accepted code, so that a code can't be used twice.
+    last_used_step INTEGER,
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
+
+-- Single use codes to log in without the authenticator, only their hash is stored.
+CREATE TABLE user_recovery_code (
+    code_hash TEXT PRIMARY KEY NOT NULL,
+    user_id INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
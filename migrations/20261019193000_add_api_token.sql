@@ -0,0 +1,20 @@
+-- Personal tokens for scripts and the CLI, which authenticate as their user with at most the
+-- permissions listed for them. Only the hash of a token is stored.
+CREATE TABLE api_token (
+    id INTEGER PRIMARY KEY NOT NULL,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    last_used_at TEXT,
+    -- Tokens without expiry work until they are revoked.
+    expires_at TEXT,
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
+
+CREATE TABLE api_token_permission (
+    token_id INTEGER NOT NULL,
+    permission TEXT NOT NULL,
+    PRIMARY KEY (token_id, permission),
+    FOREIGN KEY (token_id) REFERENCES api_token (id) ON DELETE CASCADE
+);
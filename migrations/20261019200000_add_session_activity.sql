@@ -0,0 +1,23 @@
+-- Users can see and end their sessions, so a session gets an id that can be shown unlike the
+-- session id in its cookie, and remembers where it was used from. Adding a primary key takes
+-- rebuilding the table, nothing references it.
+CREATE TABLE user_session_new (
+    id INTEGER PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL UNIQUE,
+    user_id INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    last_seen_at TEXT NOT NULL DEFAULT (datetime('now')),
+    user_agent TEXT,
+    ip TEXT,
+    FOREIGN KEY (user_id) REFERENCES user (id) ON DELETE CASCADE
+);
+
+INSERT INTO user_session_new (session_id, user_id, created_at, last_seen_at)
+SELECT session_id, user_id, created_at, created_at
+FROM user_session;
+
+DROP TABLE user_session;
+
+ALTER TABLE user_session_new RENAME TO user_session;
+
+CREATE INDEX user_session_user_id ON user_session (user_id);
@@ -0,0 +1,3 @@
+-- A new email address a user asked for, which only replaces their address once they follow the
+-- link mailed to it. Until then they keep logging in with the old one.
+ALTER TABLE user ADD COLUMN pending_email TEXT;
@@ -0,0 +1,8 @@
+-- When users were last mailed something by the forms anyone can fill in, per address and kind of
+-- mail, so that those forms can't be used to flood an inbox.
+CREATE TABLE mail_sent (
+    email TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    sent_at TEXT NOT NULL,
+    PRIMARY KEY (email, kind)
+);
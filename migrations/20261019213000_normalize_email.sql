@@ -0,0 +1,14 @@
+-- Email addresses are stored trimmed and lowercased from now on, as they are looked up that way.
+-- Addresses that only differ in case from another account's are left alone, an admin has to sort
+-- those out.
+UPDATE user
+SET email = lower(trim(email))
+WHERE NOT EXISTS (
+    SELECT 1
+    FROM user AS other
+    WHERE other.id != user.id AND lower(trim(other.email)) = lower(trim(user.email))
+);
+
+UPDATE user
+SET pending_email = lower(trim(pending_email))
+WHERE pending_email IS NOT NULL;
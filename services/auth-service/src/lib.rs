@@ -1,32 +1,261 @@
+mod repository;
+mod verifier;
+
+pub use repository::SecurityEvent;
+pub use verifier::{Argon2Verifier, PasswordVerifier};
+
+use std::net::IpAddr;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use sqlx::SqlitePool;
-use user_service::{User, UserError, UserService};
+use user_service::{TotpError, User, UserError, UserService};
+
+use crate::repository::{AuthRepository, Throttled};
+
+/// Failed logins for an account until it gets locked.
+const MAX_ACCOUNT_FAILURES: i64 = 5;
+/// Failed logins from a client until it gets locked, more than for an account since people can
+/// share an address.
+const MAX_CLIENT_FAILURES: i64 = 20;
+/// How long the first lock lasts, every failure after it doubles that.
+const BASE_LOCK_SECONDS: i64 = 30;
+const MAX_LOCK_SECONDS: i64 = 60 * 60;
+/// Failures are forgotten once there weren't any for this long.
+const FAILURE_WINDOW_SECONDS: i64 = 24 * 60 * 60;
+
+/// Checked against when there is no user for an email address, so that logging in costs the same
+/// whether the address has an account or not.
+static DUMMY_HASH: LazyLock<String> =
+    LazyLock::new(|| password_auth::generate_hash("not the password of anyone"));
 
 #[must_use]
 #[derive(Clone)]
 pub struct AuthService {
     user_service: UserService,
+    repo: AuthRepository,
+    verifier: Arc<dyn PasswordVerifier>,
 }
 
 impl AuthService {
     pub fn new(pool: SqlitePool) -> Self {
         Self {
-            user_service: UserService::new(pool),
+            user_service: UserService::new(pool.clone()),
+            repo: AuthRepository::new(pool),
+            verifier: Arc::new(Argon2Verifier),
         }
     }
 
-    pub async fn login(&self, email: &str, password: &str) -> Result<User, LoginError> {
-        let user = self.user_service.user_by_email(email).await?;
+    /// Check passwords with `verifier` instead of Argon2 directly.
+    pub fn with_verifier(self, verifier: Arc<dyn PasswordVerifier>) -> Self {
+        Self { verifier, ..self }
+    }
 
-        password_auth::verify_password(password, &user.password_hash)?;
+    /// Log in with an email address and password. Failed logins are counted per account, and per
+    /// client when the client is known. After too many of them logins are refused for a while
+    /// without checking the password. Users with two-factor authentication finish with `login_second_factor`.
+    pub async fn login(
+        &self,
+        email: &str,
+        password: &str,
+        client: Option<IpAddr>,
+    ) -> Result<LoginStep, LoginError> {
+        let attempt = Attempt::new(email, client);
+        self.check_locks(&attempt).await?;
+
+        let user = match self.verify(email, password).await {
+            Ok(user) => user,
+            Err(LoginError::InvalidCredentials) => {
+                self.count_failures(&attempt).await?;
+                return Err(LoginError::InvalidCredentials);
+            }
+            Err(LoginError::EmailNotVerified) => {
+                // The password was right, so whoever is trying knows it.
+                self.clear_failures(&attempt).await?;
+                return Err(LoginError::EmailNotVerified);
+            }
+            Err(err) => return Err(err),
+        };
+
+        // Failures are only forgotten after the second factor, otherwise knowing the password
+        // would allow guessing codes forever.
+        if self.user_service.has_totp(user.id).await? {
+            return Ok(LoginStep::SecondFactorRequired(user));
+        }
+        self.clear_failures(&attempt).await?;
+        Ok(LoginStep::LoggedIn(user))
+    }
+
+    /// Finish logging in a user who has two-factor authentication with a code from their
+    /// authenticator or a recovery code. Wrong codes count as failed logins.
+    pub async fn login_second_factor(
+        &self,
+        user_id: i64,
+        code: &str,
+        client: Option<IpAddr>,
+    ) -> Result<User, LoginError> {
+        let user = self.user_service.user_by_id(user_id).await?;
+        let attempt = Attempt::new(&user.email, client);
+        self.check_locks(&attempt).await?;
+
+        match self.user_service.verify_second_factor(user_id, code).await {
+            Ok(()) => {
+                self.clear_failures(&attempt).await?;
+                Ok(user)
+            }
+            Err(TotpError::InvalidCode | TotpError::NotEnrolled) => {
+                self.count_failures(&attempt).await?;
+                Err(LoginError::InvalidSecondFactor)
+            }
+            Err(TotpError::AlreadyEnabled | TotpError::Internal) => Err(LoginError::Internal),
+        }
+    }
+
+    /// The most recent security events, newest first.
+    pub async fn security_log(&self, limit: i64) -> Result<Vec<SecurityEvent>, sqlx::Error> {
+        self.repo.security_log(limit).await
+    }
+
+    async fn verify(&self, email: &str, password: &str) -> Result<User, LoginError> {
+        let user = match self.user_service.user_by_email(email).await {
+            Ok(user) => user,
+            Err(UserError::NotFound) => {
+                // The same work as for a wrong password, otherwise the time it takes reveals
+                // which addresses have an account.
+                let _ = self.verifier.verify(password, &DUMMY_HASH);
+                return Err(LoginError::InvalidCredentials);
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        self.verifier.verify(password, &user.password_hash)?;
+
+        // Only after the password check, so that it can't be used to find out which addresses
+        // have an account.
+        if !user.email_verified {
+            return Err(LoginError::EmailNotVerified);
+        }
 
         Ok(user)
     }
+
+    async fn check_locks(&self, attempt: &Attempt<'_>) -> Result<(), LoginError> {
+        for (kind, subject) in attempt.throttled() {
+            if let Some(locked_until) = self.repo.locked_until(kind, &subject).await?
+                && locked_until > attempt.now
+            {
+                let retry_after = u64::try_from(locked_until - attempt.now).unwrap_or_default();
+                return Err(LoginError::TooManyAttempts {
+                    retry_after: Duration::from_secs(retry_after),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn count_failures(&self, attempt: &Attempt<'_>) -> Result<(), LoginError> {
+        for (kind, subject) in attempt.throttled() {
+            let failures = self
+                .repo
+                .add_failure(kind, &subject, attempt.now, FAILURE_WINDOW_SECONDS)
+                .await?;
+            let max_failures = match kind {
+                Throttled::Account => MAX_ACCOUNT_FAILURES,
+                Throttled::Client => MAX_CLIENT_FAILURES,
+            };
+            if failures < max_failures {
+                continue;
+            }
+            let lock_seconds = lock_seconds(failures - max_failures);
+            self.repo
+                .lock(kind, &subject, attempt.now + lock_seconds)
+                .await?;
+            let details = format!(
+                "{} {subject} locked for {lock_seconds}s after {failures} failed logins",
+                kind.as_str()
+            );
+            self.repo
+                .log_event(
+                    "lockout",
+                    Some(attempt.email),
+                    attempt.client.as_deref(),
+                    &details,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn clear_failures(&self, attempt: &Attempt<'_>) -> Result<(), LoginError> {
+        self.repo
+            .clear_failures(Throttled::Account, &attempt.account())
+            .await?;
+        Ok(())
+    }
+}
+
+/// The result of logging in with a password.
+pub enum LoginStep {
+    LoggedIn(User),
+    /// The password was right, but the user has to enter a second factor too.
+    SecondFactorRequired(User),
+}
+
+/// Someone trying to log in, failed logins are counted for the account and the client.
+struct Attempt<'a> {
+    email: &'a str,
+    client: Option<String>,
+    now: i64,
+}
+
+impl<'a> Attempt<'a> {
+    fn new(email: &'a str, client: Option<IpAddr>) -> Self {
+        Self {
+            email,
+            client: client.map(|client| client.to_string()),
+            now: now(),
+        }
+    }
+
+    fn account(&self) -> String {
+        user_service::normalize_email(self.email)
+    }
+
+    fn throttled(&self) -> Vec<(Throttled, String)> {
+        let mut throttled = vec![(Throttled::Account, self.account())];
+        if let Some(client) = &self.client {
+            throttled.push((Throttled::Client, client.clone()));
+        }
+        throttled
+    }
+}
+
+/// Doubles with every failure past the limit, up to a maximum.
+fn lock_seconds(failures_past_limit: i64) -> i64 {
+    let doublings = u32::try_from(failures_past_limit).unwrap_or(u32::MAX);
+    BASE_LOCK_SECONDS
+        .saturating_mul(2_i64.saturating_pow(doublings))
+        .min(MAX_LOCK_SECONDS)
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| {
+            i64::try_from(duration.as_secs()).unwrap_or(i64::MAX)
+        })
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum LoginError {
     #[error("invalid credentials")]
     InvalidCredentials,
+    #[error("email address not verified")]
+    EmailNotVerified,
+    #[error("invalid second factor")]
+    InvalidSecondFactor,
+    #[error("too many failed logins, retry after {retry_after:?}")]
+    TooManyAttempts { retry_after: Duration },
     #[error("internal error")]
     Internal,
 }
@@ -48,3 +277,9 @@ impl From<password_auth::VerifyError> for LoginError {
         }
     }
 }
+
+impl From<sqlx::Error> for LoginError {
+    fn from(_err: sqlx::Error) -> Self {
+        Self::Internal
+    }
+}
@@ -0,0 +1,165 @@
+use sqlx::SqlitePool;
+
+/// What failed logins are counted for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Throttled {
+    /// An account, by email address, whether it exists or not.
+    Account,
+    /// A client, by IP address.
+    Client,
+}
+
+impl Throttled {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Account => "account",
+            Self::Client => "client",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecurityEvent {
+    pub id: i64,
+    pub event: String,
+    pub email: Option<String>,
+    pub client: Option<String>,
+    pub details: String,
+    pub created_at: String,
+}
+
+#[derive(Clone)]
+pub(crate) struct AuthRepository {
+    pool: SqlitePool,
+}
+
+impl AuthRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn locked_until(
+        &self,
+        kind: Throttled,
+        subject: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let kind = kind.as_str();
+        let record = sqlx::query!(
+            r#"
+            SELECT locked_until
+            FROM login_failure
+            WHERE kind = ? AND subject = ?;
+            "#,
+            kind,
+            subject
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(record.and_then(|record| record.locked_until))
+    }
+
+    /// Count a failed login, starting over when the last one is older than `window` seconds.
+    /// Returns the number of failures.
+    pub async fn add_failure(
+        &self,
+        kind: Throttled,
+        subject: &str,
+        now: i64,
+        window: i64,
+    ) -> Result<i64, sqlx::Error> {
+        let kind = kind.as_str();
+        let record = sqlx::query!(
+            r#"
+            INSERT INTO login_failure (kind, subject, failures, last_failed_at)
+            VALUES (?1, ?2, 1, ?3)
+            ON CONFLICT (kind, subject) DO UPDATE SET
+                failures = CASE
+                    WHEN last_failed_at < ?3 - ?4 THEN 1
+                    ELSE failures + 1
+                END,
+                last_failed_at = ?3
+            RETURNING failures;
+            "#,
+            kind,
+            subject,
+            now,
+            window
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(record.failures)
+    }
+
+    pub async fn lock(
+        &self,
+        kind: Throttled,
+        subject: &str,
+        locked_until: i64,
+    ) -> Result<(), sqlx::Error> {
+        let kind = kind.as_str();
+        sqlx::query!(
+            r#"
+            UPDATE login_failure SET locked_until = ?
+            WHERE kind = ? AND subject = ?;
+            "#,
+            locked_until,
+            kind,
+            subject
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn clear_failures(&self, kind: Throttled, subject: &str) -> Result<(), sqlx::Error> {
+        let kind = kind.as_str();
+        sqlx::query!(
+            r#"
+            DELETE FROM login_failure
+            WHERE kind = ? AND subject = ?;
+            "#,
+            kind,
+            subject
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn log_event(
+        &self,
+        event: &str,
+        email: Option<&str>,
+        client: Option<&str>,
+        details: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO security_log (event, email, client, details)
+            VALUES (?, ?, ?, ?);
+            "#,
+            event,
+            email,
+            client,
+            details
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn security_log(&self, limit: i64) -> Result<Vec<SecurityEvent>, sqlx::Error> {
+        sqlx::query_as!(
+            SecurityEvent,
+            r#"
+            SELECT *
+            FROM security_log
+            ORDER BY id DESC
+            LIMIT ?;
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+}
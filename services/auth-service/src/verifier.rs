@@ -0,0 +1,13 @@
+/// Checks a password against a stored hash.
+pub trait PasswordVerifier: Send + Sync {
+    fn verify(&self, password: &str, hash: &str) -> Result<(), password_auth::VerifyError>;
+}
+
+/// Verifies the Argon2 hashes that users are stored with.
+pub struct Argon2Verifier;
+
+impl PasswordVerifier for Argon2Verifier {
+    fn verify(&self, password: &str, hash: &str) -> Result<(), password_auth::VerifyError> {
+        password_auth::verify_password(password, hash)
+    }
+}
@@ -0,0 +1,184 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use auth_service::{Argon2Verifier, AuthService, LoginError, LoginStep, PasswordVerifier};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use user_service::{UserService, totp_code};
+
+static MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
+
+const PASSWORD: &str = "ValidPassword123!";
+
+/// An auth service with a verified user `cook@example.com`.
+async fn setup_service(
+    pool_options: SqlitePoolOptions,
+    options: SqliteConnectOptions,
+) -> AuthService {
+    let pool = pool_options
+        .connect_with(options.foreign_keys(true))
+        .await
+        .unwrap();
+    let users = UserService::new(pool.clone());
+    let new_user = users
+        .add_user("cook", "cook@example.com", PASSWORD)
+        .await
+        .unwrap();
+    users
+        .verify_email(&new_user.verification_token)
+        .await
+        .unwrap();
+    AuthService::new(pool)
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_account_lockout(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a user
+    let service = setup_service(pool_options, options).await;
+    let client = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+
+    // WHEN logging in with the wrong password a few times, THEN the logins fail normally
+    for _ in 0..4 {
+        let result = service.login("cook@example.com", "wrong", client).await;
+        assert!(matches!(result, Err(LoginError::InvalidCredentials)));
+    }
+    assert!(service.security_log(10).await.unwrap().is_empty());
+
+    // WHEN a correct login follows, THEN it works and the failures are forgotten
+    service
+        .login("cook@example.com", PASSWORD, client)
+        .await
+        .unwrap();
+    for _ in 0..4 {
+        let result = service.login("cook@example.com", "wrong", client).await;
+        assert!(matches!(result, Err(LoginError::InvalidCredentials)));
+    }
+
+    // WHEN failing once more
+    let result = service.login("Cook@example.com", "wrong", client).await;
+    assert!(matches!(result, Err(LoginError::InvalidCredentials)));
+
+    // THEN the account is locked, even for the right password, and the lockout is logged
+    let result = service.login("cook@example.com", PASSWORD, client).await;
+    let Err(LoginError::TooManyAttempts { retry_after }) = result else {
+        panic!("expected the account to be locked");
+    };
+    assert!(retry_after.as_secs() > 0 && retry_after.as_secs() <= 30);
+    let log = service.security_log(10).await.unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].event, "lockout");
+    assert_eq!(log[0].email.as_deref(), Some("Cook@example.com"));
+    assert_eq!(log[0].client.as_deref(), Some("192.0.2.1"));
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_client_lockout(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a user and a client guessing the passwords of many accounts
+    let service = setup_service(pool_options, options).await;
+    let client = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2)));
+    for i in 0..20 {
+        let email = format!("user{i}@example.com");
+        let result = service.login(&email, "wrong", client).await;
+        assert!(matches!(result, Err(LoginError::InvalidCredentials)));
+    }
+
+    // WHEN that client logs in, THEN it is locked out
+    let result = service.login("cook@example.com", PASSWORD, client).await;
+    assert!(matches!(result, Err(LoginError::TooManyAttempts { .. })));
+
+    // WHEN another client logs in, THEN it isn't
+    let other = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 3)));
+    service
+        .login("cook@example.com", PASSWORD, other)
+        .await
+        .unwrap();
+}
+
+/// Counts the passwords it verifies.
+#[derive(Default)]
+struct CountingVerifier {
+    verified: AtomicUsize,
+}
+
+impl PasswordVerifier for CountingVerifier {
+    fn verify(&self, password: &str, hash: &str) -> Result<(), password_auth::VerifyError> {
+        self.verified.fetch_add(1, Ordering::SeqCst);
+        Argon2Verifier.verify(password, hash)
+    }
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_unknown_email_verifies_a_hash(
+    pool_options: SqlitePoolOptions,
+    options: SqliteConnectOptions,
+) {
+    // GIVEN a user and a verifier that counts the work done
+    let verifier = Arc::new(CountingVerifier::default());
+    let service = setup_service(pool_options, options)
+        .await
+        .with_verifier(verifier.clone());
+
+    // WHEN logging in with the wrong password
+    let result = service.login("cook@example.com", "wrong", None).await;
+
+    // THEN a password was verified
+    assert!(matches!(result, Err(LoginError::InvalidCredentials)));
+    assert_eq!(verifier.verified.load(Ordering::SeqCst), 1);
+
+    // WHEN logging in with an address without account
+    let result = service.login("nobody@example.com", "wrong", None).await;
+
+    // THEN the login fails the same way, after verifying a password all the same
+    assert!(matches!(result, Err(LoginError::InvalidCredentials)));
+    assert_eq!(verifier.verified.load(Ordering::SeqCst), 2);
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_second_factor(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a user with two-factor authentication
+    let pool = pool_options
+        .clone()
+        .connect_with(options.clone().foreign_keys(true))
+        .await
+        .unwrap();
+    let service = setup_service(pool_options, options).await;
+    let users = UserService::new(pool);
+    let user = users.user_by_email("cook@example.com").await.unwrap();
+    let enrolment = users.begin_totp(user.id).await.unwrap();
+    let code = totp_code(&enrolment.secret, unix_time()).unwrap();
+    let recovery_codes = users.confirm_totp(user.id, &code).await.unwrap();
+
+    // WHEN logging in with the password, THEN the second factor is still needed
+    let step = service
+        .login("cook@example.com", PASSWORD, None)
+        .await
+        .unwrap();
+    assert!(matches!(step, LoginStep::SecondFactorRequired(_)));
+
+    // WHEN entering a fresh code, THEN they are logged in
+    let next_code = totp_code(&enrolment.secret, unix_time() + 30).unwrap();
+    let logged_in = service
+        .login_second_factor(user.id, &next_code, None)
+        .await
+        .unwrap();
+    assert_eq!(logged_in.id, user.id);
+
+    // WHEN entering wrong codes, THEN the account gets locked like for wrong passwords
+    for _ in 0..5 {
+        let result = service.login_second_factor(user.id, "000000", None).await;
+        assert!(matches!(result, Err(LoginError::InvalidSecondFactor)));
+    }
+    let result = service
+        .login_second_factor(user.id, &recovery_codes[0], None)
+        .await;
+    assert!(matches!(result, Err(LoginError::TooManyAttempts { .. })));
+}
@@ -0,0 +1,28 @@
+//! Sending mail to users through a pluggable transport.
+mod outbox;
+
+pub use outbox::OutboxTransport;
+
+use std::future::Future;
+use std::pin::Pin;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub type SendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), MailError>> + Send + 'a>>;
+
+/// Delivers mail. Boxed futures keep the trait usable as `dyn MailTransport`, so the transport
+/// can be picked at startup.
+pub trait MailTransport: Send + Sync {
+    fn send<'a>(&'a self, mail: &'a Mail) -> SendFuture<'a>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MailError {
+    #[error("failed to deliver mail: {0}")]
+    Delivery(#[from] std::io::Error),
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Mail, MailError, MailTransport, SendFuture};
+
+/// Writes mail to files in a directory instead of sending it, for local development and
+/// tests. Every mail is a file with the recipient and subject as headers, followed by an empty
+/// line and the body.
+pub struct OutboxTransport {
+    dir: PathBuf,
+    sent: AtomicU64,
+}
+
+impl OutboxTransport {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            sent: AtomicU64::new(0),
+        }
+    }
+
+    /// The mail in the outbox, oldest first.
+    pub fn mails(&self) -> Result<Vec<Mail>, MailError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut paths = fs::read_dir(&self.dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.sort();
+        paths
+            .into_iter()
+            .filter_map(|path| parse(&fs::read_to_string(path).ok()?))
+            .map(Ok)
+            .collect()
+    }
+
+    fn write(&self, mail: &Mail) -> Result<(), MailError> {
+        fs::create_dir_all(&self.dir)?;
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis());
+        // The counter keeps mails sent in the same millisecond apart and in order.
+        let count = self.sent.fetch_add(1, Ordering::Relaxed);
+        let path = self.dir.join(format!("{millis:016}-{count:06}.eml"));
+        let contents = format!(
+            "To: {}\nSubject: {}\n\n{}",
+            mail.to, mail.subject, mail.body
+        );
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+impl MailTransport for OutboxTransport {
+    fn send<'a>(&'a self, mail: &'a Mail) -> SendFuture<'a> {
+        Box::pin(async move { self.write(mail) })
+    }
+}
+
+fn parse(contents: &str) -> Option<Mail> {
+    let (headers, body) = contents.split_once("\n\n")?;
+    let mut to = None;
+    let mut subject = None;
+    for line in headers.lines() {
+        match line.split_once(": ")? {
+            ("To", value) => to = Some(value.to_string()),
+            ("Subject", value) => subject = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    Some(Mail {
+        to: to?,
+        subject: subject?,
+        body: body.to_string(),
+    })
+}
@@ -0,0 +1,35 @@
+use mail_service::{Mail, MailTransport, OutboxTransport};
+
+fn outbox_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("mail-service-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn test_outbox() {
+    // GIVEN an empty outbox
+    let dir = outbox_dir("outbox");
+    let outbox = OutboxTransport::new(&dir);
+    assert!(outbox.mails().unwrap().is_empty());
+
+    // WHEN sending two mails
+    let first = Mail {
+        to: "cook@example.com".to_string(),
+        subject: "Welcome".to_string(),
+        body: "Hello,\n\nwelcome to food.\n".to_string(),
+    };
+    let second = Mail {
+        to: "baker@example.com".to_string(),
+        subject: "Bread".to_string(),
+        body: "Knead it.".to_string(),
+    };
+    outbox.send(&first).await.unwrap();
+    outbox.send(&second).await.unwrap();
+
+    // THEN they are in the outbox in the order they were sent, also for a new transport
+    assert_eq!(outbox.mails().unwrap(), [first.clone(), second.clone()]);
+    let reopened = OutboxTransport::new(&dir);
+    assert_eq!(reopened.mails().unwrap(), [first, second]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,165 @@
+mod repository;
+
+use chrono::NaiveDate;
+use recipe_service::{RecipeError, RecipeService};
+use sqlx::SqlitePool;
+
+pub use crate::repository::MealPlanError;
+use crate::repository::MealPlanRepository;
+
+/// The longest date range that can be asked for at once.
+const MAX_RANGE_DAYS: i64 = 62;
+
+#[must_use]
+#[derive(Clone)]
+pub struct MealPlanService {
+    repo: MealPlanRepository,
+    recipe_service: RecipeService,
+}
+
+impl MealPlanService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            repo: MealPlanRepository::new(pool.clone()),
+            recipe_service: RecipeService::new(pool),
+        }
+    }
+
+    /// The planned meals of a user between `from` and `to`, both inclusive.
+    pub async fn plan(
+        &self,
+        user_id: i64,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<MealPlanEntry>, PlanError> {
+        let days = (to - from).num_days();
+        if !(0..MAX_RANGE_DAYS).contains(&days) {
+            return Err(PlanError::InvalidRange);
+        }
+        Ok(self.repo.entries(user_id, from, to).await?)
+    }
+
+    /// Plan to cook a recipe for a meal, there can be any number of recipes for the same meal.
+    pub async fn add_entry(
+        &self,
+        user_id: i64,
+        recipe_id: i64,
+        date: NaiveDate,
+        slot: MealSlot,
+        servings: u32,
+    ) -> Result<i64, PlanError> {
+        if servings == 0 {
+            return Err(PlanError::InvalidServings);
+        }
+        // Only recipes that the user can see can be planned.
+        self.recipe_service.recipe(recipe_id, Some(user_id)).await?;
+        let id = self
+            .repo
+            .add_entry(user_id, recipe_id, date, slot, servings)
+            .await?;
+        Ok(id)
+    }
+
+    /// Move a planned meal to another day or meal.
+    pub async fn move_entry(
+        &self,
+        user_id: i64,
+        entry_id: i64,
+        date: NaiveDate,
+        slot: MealSlot,
+    ) -> Result<(), PlanError> {
+        self.repo.move_entry(user_id, entry_id, date, slot).await?;
+        Ok(())
+    }
+
+    pub async fn set_servings(
+        &self,
+        user_id: i64,
+        entry_id: i64,
+        servings: u32,
+    ) -> Result<(), PlanError> {
+        if servings == 0 {
+            return Err(PlanError::InvalidServings);
+        }
+        self.repo.set_servings(user_id, entry_id, servings).await?;
+        Ok(())
+    }
+
+    pub async fn remove_entry(&self, user_id: i64, entry_id: i64) -> Result<(), PlanError> {
+        self.repo.remove_entry(user_id, entry_id).await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MealPlanEntry {
+    pub id: i64,
+    pub recipe_id: i64,
+    pub recipe_title: String,
+    pub date: NaiveDate,
+    pub slot: MealSlot,
+    pub servings: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MealSlot {
+    Breakfast,
+    Lunch,
+    Dinner,
+}
+
+impl MealSlot {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Breakfast => "breakfast",
+            Self::Lunch => "lunch",
+            Self::Dinner => "dinner",
+        }
+    }
+}
+
+impl TryFrom<&str> for MealSlot {
+    type Error = MealPlanError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "breakfast" => Ok(Self::Breakfast),
+            "lunch" => Ok(Self::Lunch),
+            "dinner" => Ok(Self::Dinner),
+            _ => Err(MealPlanError::ConsistencyError),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PlanError {
+    #[error("meal plan entry not found")]
+    NotFound,
+    #[error("recipe not found")]
+    RecipeNotFound,
+    #[error("at least one serving must be planned")]
+    InvalidServings,
+    #[error("the date range must be between 1 and {MAX_RANGE_DAYS} days")]
+    InvalidRange,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<MealPlanError> for PlanError {
+    fn from(err: MealPlanError) -> Self {
+        match err {
+            MealPlanError::NotFound => Self::NotFound,
+            _ => Self::Internal,
+        }
+    }
+}
+
+impl From<RecipeError> for PlanError {
+    fn from(err: RecipeError) -> Self {
+        match err {
+            RecipeError::NotFound => Self::RecipeNotFound,
+            _ => Self::Internal,
+        }
+    }
+}
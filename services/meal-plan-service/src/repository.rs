@@ -0,0 +1,198 @@
+use chrono::NaiveDate;
+use sqlx::SqlitePool;
+
+use crate::{MealPlanEntry, MealSlot};
+
+#[derive(Clone)]
+pub(crate) struct MealPlanRepository {
+    pool: SqlitePool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum MealPlanError {
+    #[error("meal plan entry not found")]
+    NotFound,
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("internal integrity was compromised")]
+    ConsistencyError,
+}
+
+/// Dates are stored as `TEXT` in the database.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+impl MealPlanRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// The entries of a user between `from` and `to`, both inclusive, ordered by day and meal.
+    /// Entries for recipes the user can't see anymore are left out.
+    pub async fn entries(
+        &self,
+        user_id: i64,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<MealPlanEntry>, MealPlanError> {
+        let from = from.format(DATE_FORMAT).to_string();
+        let to = to.format(DATE_FORMAT).to_string();
+        sqlx::query!(
+            r#"
+            SELECT
+                mpe.id,
+                mpe.recipe_id,
+                r.title AS recipe_title,
+                mpe.planned_on,
+                mpe.meal_slot,
+                mpe.servings
+            FROM
+                meal_plan_entry AS mpe
+            JOIN
+                recipe AS r
+                ON mpe.recipe_id = r.id
+            WHERE
+                mpe.user_id = ?1
+                AND mpe.planned_on BETWEEN ?2 AND ?3
+                AND (
+                    r.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM recipe_viewer AS vr
+                        WHERE vr.recipe_id = r.id AND vr.viewer_id = ?1
+                    )
+                )
+            ORDER BY
+                mpe.planned_on,
+                CASE mpe.meal_slot
+                    WHEN 'breakfast' THEN 0
+                    WHEN 'lunch' THEN 1
+                    ELSE 2
+                END,
+                mpe.id;
+            "#,
+            user_id,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(MealPlanEntry {
+                id: record.id,
+                recipe_id: record.recipe_id,
+                recipe_title: record.recipe_title,
+                date: NaiveDate::parse_from_str(&record.planned_on, DATE_FORMAT)
+                    .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+                slot: MealSlot::try_from(record.meal_slot.as_str())?,
+                servings: u32::try_from(record.servings)
+                    .map_err(|_| MealPlanError::ConsistencyError)?,
+            })
+        })
+        .collect()
+    }
+
+    pub async fn add_entry(
+        &self,
+        user_id: i64,
+        recipe_id: i64,
+        date: NaiveDate,
+        slot: MealSlot,
+        servings: u32,
+    ) -> Result<i64, MealPlanError> {
+        let date = date.format(DATE_FORMAT).to_string();
+        let slot = slot.as_str();
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO meal_plan_entry (user_id, recipe_id, planned_on, meal_slot, servings)
+            VALUES (?, ?, ?, ?, ?);
+            "#,
+            user_id,
+            recipe_id,
+            date,
+            slot,
+            servings
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn move_entry(
+        &self,
+        user_id: i64,
+        entry_id: i64,
+        date: NaiveDate,
+        slot: MealSlot,
+    ) -> Result<(), MealPlanError> {
+        let date = date.format(DATE_FORMAT).to_string();
+        let slot = slot.as_str();
+        let result = sqlx::query!(
+            r#"
+            UPDATE meal_plan_entry SET
+                planned_on = ?,
+                meal_slot = ?
+            WHERE
+                id = ?
+                AND user_id = ?;
+            "#,
+            date,
+            slot,
+            entry_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(MealPlanError::NotFound),
+            1 => Ok(()),
+            _ => Err(MealPlanError::ConsistencyError),
+        }
+    }
+
+    pub async fn set_servings(
+        &self,
+        user_id: i64,
+        entry_id: i64,
+        servings: u32,
+    ) -> Result<(), MealPlanError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE meal_plan_entry SET servings = ?
+            WHERE
+                id = ?
+                AND user_id = ?;
+            "#,
+            servings,
+            entry_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(MealPlanError::NotFound),
+            1 => Ok(()),
+            _ => Err(MealPlanError::ConsistencyError),
+        }
+    }
+
+    pub async fn remove_entry(&self, user_id: i64, entry_id: i64) -> Result<(), MealPlanError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM meal_plan_entry
+            WHERE
+                id = ?
+                AND user_id = ?;
+            "#,
+            entry_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(MealPlanError::NotFound),
+            1 => Ok(()),
+            _ => Err(MealPlanError::ConsistencyError),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+INSERT INTO user
+(username, email, password_hash)
+VALUES
+('planner', 'planner@example.com', 'hash'),
+('other', 'other@example.com', 'hash');
+
+INSERT INTO source
+(name)
+VALUES
+('Cookbook');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Dinner');
+
+INSERT INTO recipe
+(title, description, meal_type_id, source_id, owner_id, visibility)
+VALUES
+('Pancakes', 'Fluffy.', 1, 1, NULL, 'public'),
+('Lasagna', 'Cheesy.', 1, 1, NULL, 'public'),
+('Secret stew', 'Hush.', 1, 1, 2, 'private');
+
+-- Planned while the stew was still public.
+INSERT INTO meal_plan_entry
+(user_id, recipe_id, planned_on, meal_slot, servings)
+VALUES
+(1, 3, '2025-06-10', 'dinner', 2);
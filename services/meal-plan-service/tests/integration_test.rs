@@ -0,0 +1,149 @@
+use chrono::NaiveDate;
+use meal_plan_service::{MealPlanService, MealSlot, PlanError};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+static MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
+
+const PLANNER: i64 = 1;
+const OTHER: i64 = 2;
+const PANCAKES: i64 = 1;
+const LASAGNA: i64 = 2;
+const SECRET_STEW: i64 = 3;
+
+async fn setup_service(
+    pool_options: SqlitePoolOptions,
+    options: SqliteConnectOptions,
+) -> MealPlanService {
+    let pool = pool_options
+        .connect_with(options.foreign_keys(true))
+        .await
+        .unwrap();
+    MealPlanService::new(pool)
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes"))]
+async fn test_plan_week(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+
+    // WHEN planning meals over a couple of days, and for another user
+    service
+        .add_entry(PLANNER, LASAGNA, date(3), MealSlot::Dinner, 4)
+        .await
+        .unwrap();
+    service
+        .add_entry(PLANNER, PANCAKES, date(3), MealSlot::Breakfast, 2)
+        .await
+        .unwrap();
+    service
+        .add_entry(PLANNER, PANCAKES, date(9), MealSlot::Breakfast, 2)
+        .await
+        .unwrap();
+    service
+        .add_entry(OTHER, SECRET_STEW, date(3), MealSlot::Lunch, 1)
+        .await
+        .unwrap();
+
+    // THEN the plan for a date range is ordered by day and meal
+    let plan = service.plan(PLANNER, date(2), date(8)).await.unwrap();
+    let plan: Vec<_> = plan
+        .iter()
+        .map(|entry| (entry.recipe_title.as_str(), entry.date, entry.slot))
+        .collect();
+    assert_eq!(
+        plan,
+        [
+            ("Pancakes", date(3), MealSlot::Breakfast),
+            ("Lasagna", date(3), MealSlot::Dinner),
+        ]
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes"))]
+async fn test_plan_hidden_recipe(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a planned recipe that was made private by its owner since
+    let service = setup_service(pool_options, options).await;
+    service
+        .add_entry(PLANNER, LASAGNA, date(10), MealSlot::Lunch, 2)
+        .await
+        .unwrap();
+
+    // WHEN looking at the plan for that day
+    let plan = service.plan(PLANNER, date(10), date(10)).await.unwrap();
+
+    // THEN the private recipe is left out
+    let titles: Vec<_> = plan
+        .iter()
+        .map(|entry| entry.recipe_title.as_str())
+        .collect();
+    assert_eq!(titles, ["Lasagna"]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes"))]
+async fn test_plan_invalid(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    let service = setup_service(pool_options, options).await;
+
+    // Planning zero servings is an error.
+    let result = service
+        .add_entry(PLANNER, LASAGNA, date(3), MealSlot::Dinner, 0)
+        .await;
+    assert!(matches!(result, Err(PlanError::InvalidServings)));
+
+    // Recipes that are not visible to the user cannot be planned.
+    let result = service
+        .add_entry(PLANNER, SECRET_STEW, date(3), MealSlot::Dinner, 2)
+        .await;
+    assert!(matches!(result, Err(PlanError::RecipeNotFound)));
+
+    // Ranges must be in order and not too long.
+    let result = service.plan(PLANNER, date(8), date(2)).await;
+    assert!(matches!(result, Err(PlanError::InvalidRange)));
+    let far_away = NaiveDate::from_ymd_opt(2026, 6, 1).unwrap();
+    let result = service.plan(PLANNER, date(1), far_away).await;
+    assert!(matches!(result, Err(PlanError::InvalidRange)));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes"))]
+async fn test_move_and_remove(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let id = service
+        .add_entry(PLANNER, LASAGNA, date(3), MealSlot::Dinner, 4)
+        .await
+        .unwrap();
+
+    // WHEN another user moves the entry, THEN it's not found
+    let result = service
+        .move_entry(OTHER, id, date(4), MealSlot::Lunch)
+        .await;
+    assert!(matches!(result, Err(PlanError::NotFound)));
+
+    // WHEN moving the entry to another day and changing the servings
+    service
+        .move_entry(PLANNER, id, date(4), MealSlot::Lunch)
+        .await
+        .unwrap();
+    service.set_servings(PLANNER, id, 6).await.unwrap();
+
+    // THEN it's planned for that day
+    let plan = service.plan(PLANNER, date(1), date(7)).await.unwrap();
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan[0].date, date(4));
+    assert_eq!(plan[0].slot, MealSlot::Lunch);
+    assert_eq!(plan[0].servings, 6);
+
+    // WHEN removing the entry, THEN the plan is empty
+    service.remove_entry(PLANNER, id).await.unwrap();
+    assert!(
+        service
+            .plan(PLANNER, date(1), date(7))
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,235 @@
+use crate::export::{self, RecipeFormat};
+use crate::{RecipeError, RecipeListing, RecipeService, Visibility};
+
+#[derive(Clone, Debug)]
+pub struct CollectionListing {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub owner_username: String,
+    /// Whether the collection is owned by the user that asked for it.
+    pub is_own: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub owner_username: String,
+    pub visibility: Visibility,
+    /// Whether the collection is owned by the user that asked for it.
+    pub is_own: bool,
+    /// The recipes of the collection that the user can see, in order.
+    pub recipes: Vec<RecipeListing>,
+}
+
+impl RecipeService {
+    /// List the collections visible to `viewer`, where `None` is an anonymous visitor.
+    pub async fn collections(
+        &self,
+        viewer: Option<i64>,
+    ) -> Result<Vec<CollectionListing>, RecipeError> {
+        self.repo.collections(viewer).await
+    }
+
+    pub async fn collection(
+        &self,
+        collection_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Collection, RecipeError> {
+        let record = self.repo.collection(collection_id, viewer).await?;
+        let recipes = self.repo.collection_recipes(collection_id, viewer).await?;
+        Ok(Collection {
+            id: record.id,
+            name: record.name,
+            description: record.description,
+            owner_username: record.owner_username,
+            visibility: record.visibility,
+            is_own: viewer == Some(record.owner_id),
+            recipes,
+        })
+    }
+
+    /// Create a new, private, collection owned by `user_id`.
+    pub async fn create_collection(
+        &self,
+        user_id: i64,
+        name: &str,
+        description: &str,
+    ) -> Result<i64, CollectionError> {
+        let name = validate_collection_name(name)?;
+        let id = self
+            .repo
+            .create_collection(user_id, name, description.trim())
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn update_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+        update: &CollectionUpdate,
+    ) -> Result<(), CollectionError> {
+        self.ensure_collection_owner(user_id, collection_id).await?;
+        let name = validate_collection_name(&update.name)?;
+
+        let household_id = match (update.visibility, update.household_id) {
+            (Visibility::Household, None) => return Err(CollectionError::MissingHousehold),
+            (Visibility::Household, Some(household_id)) => {
+                if !self.repo.is_household_member(household_id, user_id).await? {
+                    return Err(CollectionError::NotHouseholdMember);
+                }
+                Some(household_id)
+            }
+            (Visibility::Private | Visibility::Public, _) => None,
+        };
+
+        self.repo
+            .update_collection(
+                collection_id,
+                name,
+                update.description.trim(),
+                update.visibility,
+                household_id,
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+    ) -> Result<(), CollectionError> {
+        self.ensure_collection_owner(user_id, collection_id).await?;
+        self.repo.delete_collection(collection_id).await?;
+        Ok(())
+    }
+
+    /// Add a recipe last in a collection.
+    pub async fn add_to_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+        recipe_id: i64,
+    ) -> Result<(), CollectionError> {
+        self.ensure_collection_owner(user_id, collection_id).await?;
+        if !self.repo.is_visible(recipe_id, Some(user_id)).await? {
+            return Err(CollectionError::NotFound);
+        }
+        self.repo
+            .add_collection_recipe(collection_id, recipe_id)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_from_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+        recipe_id: i64,
+    ) -> Result<(), CollectionError> {
+        self.ensure_collection_owner(user_id, collection_id).await?;
+        self.repo
+            .remove_collection_recipe(collection_id, recipe_id)
+            .await?;
+        Ok(())
+    }
+
+    /// Reorder the recipes of a collection, `recipe_ids` must contain every recipe of the
+    /// collection exactly once.
+    pub async fn reorder_collection(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+        recipe_ids: &[i64],
+    ) -> Result<(), CollectionError> {
+        self.ensure_collection_owner(user_id, collection_id).await?;
+
+        let mut current = self.repo.collection_recipe_ids(collection_id).await?;
+        let mut requested = recipe_ids.to_vec();
+        current.sort_unstable();
+        requested.sort_unstable();
+        if current != requested {
+            return Err(CollectionError::InvalidOrder);
+        }
+
+        self.repo
+            .set_collection_order(collection_id, recipe_ids)
+            .await?;
+        Ok(())
+    }
+
+    /// Export every recipe in a collection that `viewer` can see, in the order of the collection.
+    pub async fn export_collection(
+        &self,
+        collection_id: i64,
+        viewer: Option<i64>,
+        format: RecipeFormat,
+    ) -> Result<String, RecipeError> {
+        let collection = self.collection(collection_id, viewer).await?;
+        let mut recipes = Vec::with_capacity(collection.recipes.len());
+        for recipe_listing in &collection.recipes {
+            recipes.push(self.repo.recipe(recipe_listing.id, viewer).await?);
+        }
+        export::collection(&collection.name, &collection.description, &recipes, format)
+    }
+
+    async fn ensure_collection_owner(
+        &self,
+        user_id: i64,
+        collection_id: i64,
+    ) -> Result<(), CollectionError> {
+        let owner_id = self.repo.collection_owner(collection_id).await?;
+        if owner_id != user_id {
+            return Err(CollectionError::NotOwner);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CollectionUpdate {
+    pub name: String,
+    pub description: String,
+    pub visibility: Visibility,
+    /// The household to share the collection with, required for [`Visibility::Household`].
+    pub household_id: Option<i64>,
+}
+
+fn validate_collection_name(name: &str) -> Result<&str, CollectionError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CollectionError::EmptyName);
+    }
+    Ok(name)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CollectionError {
+    #[error("collection or recipe not found")]
+    NotFound,
+    #[error("only the owner can change a collection")]
+    NotOwner,
+    #[error("a collection needs a name")]
+    EmptyName,
+    #[error("the new order must contain every recipe in the collection exactly once")]
+    InvalidOrder,
+    #[error("a household is required to share a collection with a household")]
+    MissingHousehold,
+    #[error("the owner is not a member of the household")]
+    NotHouseholdMember,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for CollectionError {
+    fn from(err: RecipeError) -> Self {
+        match err {
+            RecipeError::NotFound => Self::NotFound,
+            _ => Self::Internal,
+        }
+    }
+}
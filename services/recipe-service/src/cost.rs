@@ -0,0 +1,142 @@
+use chrono::NaiveDate;
+
+use crate::units::{self, Weights};
+use crate::{Ingredient, RecipeError, RecipeService};
+
+/// The price of one `unit` of an ingredient in a store.
+#[derive(Clone, Debug)]
+pub struct Price {
+    pub id: i64,
+    pub ingredient: String,
+    pub store: String,
+    pub price: f64,
+    pub unit: String,
+    pub effective_from: NaiveDate,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CostEstimate {
+    /// The cost of the priced ingredients, at the cheapest store for each.
+    pub total: f64,
+    /// The ingredients without a price, or without a price in a unit they can be converted to.
+    pub unpriced: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RecipeCost {
+    pub total: f64,
+    pub per_serving: f64,
+    pub unpriced: Vec<String>,
+}
+
+impl RecipeService {
+    /// The prices that apply today, one per ingredient and store.
+    pub async fn current_prices(&self) -> Result<Vec<Price>, RecipeError> {
+        self.repo.current_prices().await
+    }
+
+    /// Set the price of one `unit` of an ingredient in a store, from `effective_from` on.
+    pub async fn set_price(
+        &self,
+        ingredient: &str,
+        store: &str,
+        price: f64,
+        unit: &str,
+        effective_from: NaiveDate,
+    ) -> Result<i64, PriceError> {
+        let ingredient = ingredient.trim();
+        let store = store.trim();
+        if ingredient.is_empty() {
+            return Err(PriceError::EmptyIngredient);
+        }
+        if store.is_empty() {
+            return Err(PriceError::EmptyStore);
+        }
+        if !price.is_finite() || price < 0.0 {
+            return Err(PriceError::InvalidPrice);
+        }
+        // Prices in units that can't be converted could never be used for an estimate.
+        if units::to_base(1.0, unit).is_none() {
+            return Err(PriceError::UnknownUnit);
+        }
+        let store_id = self.repo.store_id(store).await?;
+        let id = self
+            .repo
+            .set_price(ingredient, store_id, price, unit.trim(), effective_from)
+            .await?;
+        Ok(id)
+    }
+
+    /// Estimate what a list of ingredients costs with today's prices, picking the cheapest store
+    /// for every ingredient.
+    pub async fn estimate_cost(
+        &self,
+        ingredients: &[Ingredient],
+    ) -> Result<CostEstimate, RecipeError> {
+        let prices = self.repo.current_prices().await?;
+        let weights = self.repo.ingredient_weights().await?;
+
+        let mut estimate = CostEstimate::default();
+        for ingredient in ingredients {
+            let name = ingredient.name.trim().to_lowercase();
+            let ingredient_weights = weights
+                .iter()
+                .find(|(weights_name, _)| *weights_name == name)
+                .map_or_else(Weights::default, |(_, weights)| *weights);
+            let cheapest = prices
+                .iter()
+                .filter(|price| price.ingredient.to_lowercase() == name)
+                .filter_map(|price| {
+                    let quantity = units::convert_ingredient(
+                        ingredient.quantity,
+                        &ingredient.unit,
+                        &price.unit,
+                        ingredient_weights,
+                    )?;
+                    Some(quantity * price.price)
+                })
+                .min_by(f64::total_cmp);
+            match cheapest {
+                Some(cost) => estimate.total += cost,
+                None => estimate.unpriced.push(ingredient.name.clone()),
+            }
+        }
+        Ok(estimate)
+    }
+
+    /// Estimate what a recipe that is visible to `viewer` costs with today's prices.
+    pub async fn recipe_cost(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<RecipeCost, RecipeError> {
+        let recipe = self.repo.recipe(recipe_id, viewer).await?;
+        let ingredients = self.expanded_ingredients(recipe_id, viewer).await?;
+        let estimate = self.estimate_cost(&ingredients).await?;
+        Ok(RecipeCost {
+            total: estimate.total,
+            per_serving: estimate.total / f64::from(recipe.servings),
+            unpriced: estimate.unpriced,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PriceError {
+    #[error("an ingredient is required")]
+    EmptyIngredient,
+    #[error("a store is required")]
+    EmptyStore,
+    #[error("a price can't be negative")]
+    InvalidPrice,
+    #[error("unknown unit")]
+    UnknownUnit,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for PriceError {
+    fn from(_: RecipeError) -> Self {
+        Self::Internal
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{RecipeError, RecipeService};
+
+/// A piece of equipment that recipes can need, and whether the user owns it.
+#[derive(Clone, Debug)]
+pub struct Equipment {
+    pub id: i64,
+    pub name: String,
+    pub owned: bool,
+}
+
+impl RecipeService {
+    /// All the equipment that recipes need, by name. Users own everything they haven't marked
+    /// as not owned, which is everything for an anonymous visitor.
+    pub async fn equipment(&self, user_id: Option<i64>) -> Result<Vec<Equipment>, RecipeError> {
+        self.repo.equipment(user_id).await
+    }
+
+    /// Mark whether a user owns a piece of equipment. Recipes that need equipment the user
+    /// doesn't own can be left out of their recipe listing.
+    pub async fn set_equipment_owned(
+        &self,
+        user_id: i64,
+        equipment_id: i64,
+        owned: bool,
+    ) -> Result<(), RecipeError> {
+        self.repo
+            .set_equipment_owned(user_id, equipment_id, owned)
+            .await
+    }
+
+    /// Replace the equipment a recipe needs, only the owner of a recipe can do this. Equipment
+    /// is matched on its name, ignoring case, and created if it's new.
+    pub async fn set_recipe_equipment(
+        &self,
+        recipe_id: i64,
+        user_id: i64,
+        equipment: &[String],
+    ) -> Result<(), EquipmentError> {
+        let owner_id = self.repo.recipe_owner(recipe_id).await?;
+        if owner_id != Some(user_id) {
+            return Err(EquipmentError::NotOwner);
+        }
+        let mut equipment_ids = Vec::new();
+        for name in equipment.iter().map(|name| name.trim()) {
+            if name.is_empty() {
+                continue;
+            }
+            let equipment_id = self.repo.equipment_id(name).await?;
+            if !equipment_ids.contains(&equipment_id) {
+                equipment_ids.push(equipment_id);
+            }
+        }
+        self.repo
+            .set_recipe_equipment(recipe_id, &equipment_ids)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EquipmentError {
+    #[error("recipe not found")]
+    NotFound,
+    #[error("only the owner can change the equipment of a recipe")]
+    NotOwner,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for EquipmentError {
+    fn from(err: RecipeError) -> Self {
+        match err {
+            RecipeError::NotFound => Self::NotFound,
+            _ => Self::Internal,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Rendering of recipes into the formats they can be exported in.
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::{Recipe, RecipeError};
+
+/// The formats recipes can be exported in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecipeFormat {
+    Markdown,
+    Json,
+}
+
+impl RecipeFormat {
+    #[must_use]
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+        }
+    }
+}
+
+pub(crate) fn recipe(recipe: &Recipe, format: RecipeFormat) -> Result<String, RecipeError> {
+    match format {
+        RecipeFormat::Markdown => {
+            let mut out = String::new();
+            write_markdown(&mut out, recipe, "#");
+            Ok(out)
+        }
+        RecipeFormat::Json => to_json(&ExportedRecipe::from(recipe)),
+    }
+}
+
+pub(crate) fn collection(
+    name: &str,
+    description: &str,
+    recipes: &[Recipe],
+    format: RecipeFormat,
+) -> Result<String, RecipeError> {
+    match format {
+        RecipeFormat::Markdown => {
+            let mut out = format!("# {name}\n");
+            if !description.is_empty() {
+                let _ = write!(out, "\n{description}\n");
+            }
+            for recipe in recipes {
+                out.push('\n');
+                write_markdown(&mut out, recipe, "##");
+            }
+            Ok(out)
+        }
+        RecipeFormat::Json => to_json(&ExportedCollection {
+            name,
+            description,
+            recipes: recipes.iter().map(ExportedRecipe::from).collect(),
+        }),
+    }
+}
+
+fn write_markdown(out: &mut String, recipe: &Recipe, heading: &str) {
+    // Writing to a `String` cannot fail.
+    let _ = writeln!(out, "{heading} {}\n", recipe.title);
+    let _ = writeln!(out, "_{}_\n", recipe.meal_type);
+    if !recipe.description.is_empty() {
+        let _ = writeln!(out, "{}\n", recipe.description);
+    }
+    let _ = writeln!(out, "{heading}# Ingredients\n");
+    for ingredient in &recipe.ingredients {
+        let _ = writeln!(
+            out,
+            "- {} {} {}",
+            ingredient.quantity, ingredient.unit, ingredient.name
+        );
+    }
+    if !recipe.equipment.is_empty() {
+        let _ = writeln!(out, "\n{heading}# Equipment\n");
+        for equipment in &recipe.equipment {
+            let _ = writeln!(out, "- {equipment}");
+        }
+    }
+    let _ = writeln!(out, "\n{heading}# Instructions\n");
+    for (step, instruction) in recipe.instructions.iter().enumerate() {
+        let _ = writeln!(out, "{}. {instruction}", step + 1);
+    }
+    match recipe.source_url.as_ref() {
+        Some(url) => {
+            let _ = writeln!(out, "\nSource: [{}]({url})", recipe.source_name);
+        }
+        None => {
+            let _ = writeln!(out, "\nSource: {}", recipe.source_name);
+        }
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, RecipeError> {
+    serde_json::to_string_pretty(value).map_err(|_| RecipeError::ConsistencyError)
+}
+
+#[derive(Serialize)]
+struct ExportedCollection<'a> {
+    name: &'a str,
+    description: &'a str,
+    recipes: Vec<ExportedRecipe<'a>>,
+}
+
+#[derive(Serialize)]
+struct ExportedRecipe<'a> {
+    title: &'a str,
+    description: &'a str,
+    meal_type: &'a str,
+    source_name: &'a str,
+    source_url: Option<&'a str>,
+    servings: u32,
+    ingredients: Vec<ExportedIngredient<'a>>,
+    instructions: &'a [String],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    equipment: &'a [String],
+}
+
+#[derive(Serialize)]
+struct ExportedIngredient<'a> {
+    quantity: f64,
+    unit: &'a str,
+    name: &'a str,
+}
+
+impl<'a> From<&'a Recipe> for ExportedRecipe<'a> {
+    fn from(recipe: &'a Recipe) -> Self {
+        Self {
+            title: &recipe.title,
+            description: &recipe.description,
+            meal_type: &recipe.meal_type,
+            source_name: &recipe.source_name,
+            source_url: recipe.source_url.as_deref(),
+            servings: recipe.servings,
+            ingredients: recipe
+                .ingredients
+                .iter()
+                .map(|ingredient| ExportedIngredient {
+                    quantity: ingredient.quantity,
+                    unit: &ingredient.unit,
+                    name: &ingredient.name,
+                })
+                .collect(),
+            instructions: &recipe.instructions,
+            equipment: &recipe.equipment,
+        }
+    }
+}
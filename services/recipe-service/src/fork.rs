@@ -0,0 +1,40 @@
+use crate::{ListingQuery, RecipeError, RecipeListing, RecipeService};
+
+impl RecipeService {
+    /// Copy a recipe that is visible to the user, with its ingredients and instructions, into a
+    /// private recipe of their own that links back to the original. The copy is titled after the
+    /// original, e.g. "Pancakes (variation)", and can be edited like any other recipe. Returns
+    /// the id of the copy.
+    pub async fn fork_recipe(&self, recipe_id: i64, user_id: i64) -> Result<i64, RecipeError> {
+        let recipe = self.repo.recipe(recipe_id, Some(user_id)).await?;
+        let mut title = format!("{} (variation)", recipe.title);
+        let mut n = 1;
+        // Trying the titles one after the other, rather than checking first, so that two forks
+        // at the same time can't end up with the same title.
+        loop {
+            match self.repo.fork_recipe(recipe_id, user_id, &title).await {
+                Err(RecipeError::DuplicateTitle) => {
+                    n += 1;
+                    title = format!("{} (variation {n})", recipe.title);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// The variations of a recipe that are visible to `viewer`, by title.
+    pub async fn variations(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<RecipeListing>, RecipeError> {
+        if !self.repo.is_visible(recipe_id, viewer).await? {
+            return Err(RecipeError::NotFound);
+        }
+        let query = ListingQuery {
+            variations_of: Some(recipe_id),
+            ..ListingQuery::default()
+        };
+        self.repo.recipe_listing(viewer, &query).await
+    }
+}
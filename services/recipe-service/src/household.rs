@@ -0,0 +1,111 @@
+use rand::Rng as _;
+use rand::distributions::Alphanumeric;
+
+use crate::{RecipeError, RecipeService};
+
+/// How long the codes to join a household are.
+const INVITE_CODE_LENGTH: usize = 12;
+
+/// A group of users that recipes and collections can be shared with.
+#[derive(Clone, Debug)]
+pub struct Household {
+    pub id: i64,
+    pub name: String,
+    /// Lets whoever it is passed on to join, only members get to see it.
+    pub invite_code: String,
+    /// The usernames of the members.
+    pub members: Vec<String>,
+}
+
+impl RecipeService {
+    /// The households a user is a member of, by name.
+    pub async fn households(&self, user_id: i64) -> Result<Vec<Household>, RecipeError> {
+        self.repo.households(user_id).await
+    }
+
+    /// Create a household with the user as its first member.
+    pub async fn create_household(
+        &self,
+        user_id: i64,
+        name: &str,
+    ) -> Result<Household, HouseholdError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(HouseholdError::EmptyName);
+        }
+        let invite_code: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(INVITE_CODE_LENGTH)
+            .map(char::from)
+            .collect();
+        let household_id = self
+            .repo
+            .create_household(user_id, name, &invite_code)
+            .await?;
+        self.household(user_id, household_id).await
+    }
+
+    /// Join the household with an invite code.
+    pub async fn join_household(
+        &self,
+        user_id: i64,
+        invite_code: &str,
+    ) -> Result<Household, HouseholdError> {
+        let household_id = self
+            .repo
+            .household_by_invite_code(invite_code.trim())
+            .await?
+            .ok_or(HouseholdError::InvalidInviteCode)?;
+        self.repo
+            .add_household_member(household_id, user_id)
+            .await?;
+        self.household(user_id, household_id).await
+    }
+
+    /// Leave a household, what the user shared with it becomes private again. A household
+    /// without members is removed.
+    pub async fn leave_household(
+        &self,
+        user_id: i64,
+        household_id: i64,
+    ) -> Result<(), HouseholdError> {
+        if !self.repo.is_household_member(household_id, user_id).await? {
+            return Err(HouseholdError::NotMember);
+        }
+        self.repo
+            .remove_household_member(household_id, user_id)
+            .await?;
+        Ok(())
+    }
+
+    async fn household(
+        &self,
+        user_id: i64,
+        household_id: i64,
+    ) -> Result<Household, HouseholdError> {
+        self.repo
+            .households(user_id)
+            .await?
+            .into_iter()
+            .find(|household| household.id == household_id)
+            .ok_or(HouseholdError::Internal)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HouseholdError {
+    #[error("a household needs a name")]
+    EmptyName,
+    #[error("no household has this invite code")]
+    InvalidInviteCode,
+    #[error("not a member of the household")]
+    NotMember,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for HouseholdError {
+    fn from(_: RecipeError) -> Self {
+        Self::Internal
+    }
+}
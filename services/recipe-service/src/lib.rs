@@ -1,10 +1,35 @@
+mod collection;
+mod cost;
+mod equipment;
+mod export;
+mod fork;
+mod household;
+mod nutrition;
+mod pantry;
 mod repository;
+mod revision;
+mod sub_recipe;
+mod substitution;
+pub mod units;
 
 use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 
+pub use crate::collection::{Collection, CollectionError, CollectionListing, CollectionUpdate};
+pub use crate::cost::{CostEstimate, Price, PriceError, RecipeCost};
+pub use crate::equipment::{Equipment, EquipmentError};
+pub use crate::export::RecipeFormat;
+pub use crate::household::{Household, HouseholdError};
+pub use crate::nutrition::{Nutrients, RecipeNutrition};
+pub use crate::pantry::{PantryError, PantryItem, PantryMatch};
 pub use crate::repository::RecipeError;
 use crate::repository::RecipeRepository;
+pub use crate::revision::{
+    EditError, FieldChange, IngredientChange, InstructionChange, RecipeContent, RecipeField,
+    Revision, RevisionDiff, RevisionListing,
+};
+pub use crate::substitution::{Substitution, SubstitutionError};
 
 #[must_use]
 #[derive(Clone)]
@@ -18,19 +43,220 @@ impl RecipeService {
         Self { repo }
     }
 
-    pub async fn recipe_listing(&self) -> Result<Vec<RecipeListing>, RecipeError> {
-        self.repo.recipe_listing().await
+    /// List the recipes visible to `viewer`, where `None` is an anonymous visitor.
+    pub async fn recipe_listing(
+        &self,
+        viewer: Option<i64>,
+        query: &ListingQuery,
+    ) -> Result<Vec<RecipeListing>, RecipeError> {
+        self.repo.recipe_listing(viewer, query).await
     }
 
-    pub async fn recipe(&self, recipe_id: i64) -> Result<Recipe, RecipeError> {
-        self.repo.recipe(recipe_id).await
+    /// Get a recipe if it is visible to `viewer`, recipes that are not visible are reported as
+    /// [`RecipeError::NotFound`] to not leak their existence.
+    pub async fn recipe(&self, recipe_id: i64, viewer: Option<i64>) -> Result<Recipe, RecipeError> {
+        self.repo.recipe(recipe_id, viewer).await
     }
+
+    /// Export a recipe that is visible to `viewer`.
+    pub async fn export_recipe(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+        format: RecipeFormat,
+    ) -> Result<String, RecipeError> {
+        let recipe = self.repo.recipe(recipe_id, viewer).await?;
+        export::recipe(&recipe, format)
+    }
+
+    /// List the favorite recipes of a user that are still visible to them.
+    pub async fn favorites(&self, user_id: i64) -> Result<Vec<RecipeListing>, RecipeError> {
+        let query = ListingQuery {
+            favorites_only: true,
+            ..ListingQuery::default()
+        };
+        self.repo.recipe_listing(Some(user_id), &query).await
+    }
+
+    pub async fn is_favorite(&self, user_id: i64, recipe_id: i64) -> Result<bool, RecipeError> {
+        self.repo.is_favorite(user_id, recipe_id).await
+    }
+
+    /// Mark a recipe as a favorite, adding a recipe that already is a favorite does nothing.
+    pub async fn add_favorite(&self, user_id: i64, recipe_id: i64) -> Result<(), RecipeError> {
+        if !self.repo.is_visible(recipe_id, Some(user_id)).await? {
+            return Err(RecipeError::NotFound);
+        }
+        self.repo.add_favorite(user_id, recipe_id).await
+    }
+
+    pub async fn remove_favorite(&self, user_id: i64, recipe_id: i64) -> Result<(), RecipeError> {
+        self.repo.remove_favorite(user_id, recipe_id).await
+    }
+
+    /// List the reviews of a recipe, newest first.
+    pub async fn reviews(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<Review>, RecipeError> {
+        if !self.repo.is_visible(recipe_id, viewer).await? {
+            return Err(RecipeError::NotFound);
+        }
+        self.repo.reviews(recipe_id, viewer).await
+    }
+
+    /// Rate and optionally review a recipe, a user has at most one review per recipe so this
+    /// replaces any earlier review by the same user.
+    pub async fn set_review(
+        &self,
+        user_id: i64,
+        recipe_id: i64,
+        rating: u8,
+        text: Option<&str>,
+    ) -> Result<(), ReviewError> {
+        if !(1..=5).contains(&rating) {
+            return Err(ReviewError::InvalidRating);
+        }
+        if !self.repo.is_visible(recipe_id, Some(user_id)).await? {
+            return Err(ReviewError::NotFound);
+        }
+        let text = text.map(str::trim).filter(|text| !text.is_empty());
+        self.repo
+            .set_review(user_id, recipe_id, rating, text)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_review(&self, user_id: i64, recipe_id: i64) -> Result<(), RecipeError> {
+        self.repo.delete_review(user_id, recipe_id).await
+    }
+
+    /// The history of when a recipe was cooked, most recent first.
+    pub async fn cooking_log(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<CookingLogEntry>, RecipeError> {
+        if !self.repo.is_visible(recipe_id, viewer).await? {
+            return Err(RecipeError::NotFound);
+        }
+        self.repo.cooking_log(recipe_id, viewer).await
+    }
+
+    /// Record that a user cooked a recipe, with notes on what they changed.
+    pub async fn log_cooking(
+        &self,
+        user_id: i64,
+        recipe_id: i64,
+        cooked_on: NaiveDate,
+        servings: u32,
+        notes: Option<&str>,
+    ) -> Result<i64, CookingLogError> {
+        if servings == 0 {
+            return Err(CookingLogError::InvalidServings);
+        }
+        if !self.repo.is_visible(recipe_id, Some(user_id)).await? {
+            return Err(CookingLogError::NotFound);
+        }
+        let notes = notes.map(str::trim).filter(|notes| !notes.is_empty());
+        let id = self
+            .repo
+            .add_cooking_log_entry(user_id, recipe_id, cooked_on, servings, notes)
+            .await?;
+        Ok(id)
+    }
+
+    /// Remove an entry from the cooking log, users can only remove their own entries.
+    pub async fn delete_cooking_log_entry(
+        &self,
+        user_id: i64,
+        entry_id: i64,
+    ) -> Result<(), RecipeError> {
+        self.repo.delete_cooking_log_entry(user_id, entry_id).await
+    }
+
+    /// The recipes a user cooked most recently.
+    pub async fn recently_cooked(
+        &self,
+        user_id: i64,
+        limit: u32,
+    ) -> Result<Vec<CookedRecipe>, RecipeError> {
+        self.repo.cooked_recipes(user_id, false, limit).await
+    }
+
+    /// The recipes a user has cooked before, but not for the longest time.
+    pub async fn not_cooked_lately(
+        &self,
+        user_id: i64,
+        limit: u32,
+    ) -> Result<Vec<CookedRecipe>, RecipeError> {
+        self.repo.cooked_recipes(user_id, true, limit).await
+    }
+
+    /// Change who can see a recipe, only the owner of a recipe is allowed to do this.
+    pub async fn set_visibility(
+        &self,
+        recipe_id: i64,
+        user_id: i64,
+        visibility: Visibility,
+        household_id: Option<i64>,
+    ) -> Result<(), SetVisibilityError> {
+        let owner_id = self.repo.recipe_owner(recipe_id).await?;
+        if owner_id != Some(user_id) {
+            return Err(SetVisibilityError::NotOwner);
+        }
+
+        let household_id = match (visibility, household_id) {
+            (Visibility::Household, None) => return Err(SetVisibilityError::MissingHousehold),
+            (Visibility::Household, Some(household_id)) => {
+                if !self.repo.is_household_member(household_id, user_id).await? {
+                    return Err(SetVisibilityError::NotHouseholdMember);
+                }
+                Some(household_id)
+            }
+            (Visibility::Private | Visibility::Public, _) => None,
+        };
+
+        self.repo
+            .set_visibility(recipe_id, visibility, household_id)
+            .await?;
+        Ok(())
+    }
+
+    /// Hand a recipe to another user, for recipes from before they had owners. What isn't public
+    /// becomes private, the new owner may not be in the household it was shared with.
+    pub async fn set_owner(&self, recipe_id: i64, owner_id: i64) -> Result<(), RecipeError> {
+        self.repo.set_owner(recipe_id, owner_id).await
+    }
+}
+
+/// Narrows down and orders a recipe listing.
+#[derive(Clone, Debug, Default)]
+pub struct ListingQuery {
+    /// Only list the favorites of the viewer.
+    pub favorites_only: bool,
+    /// Only list the variations of a recipe.
+    pub variations_of: Option<i64>,
+    /// Leave out the recipes that need equipment the viewer doesn't own.
+    pub owned_equipment_only: bool,
+    pub sort: ListingSort,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ListingSort {
+    #[default]
+    Title,
+    /// Highest average rating first, unrated recipes last.
+    Rating,
 }
 
 #[derive(Clone, Debug)]
 pub struct RecipeListing {
     pub id: i64,
     pub title: String,
+    pub average_rating: Option<f64>,
+    pub rating_count: i64,
 }
 
 #[derive(Clone, Debug)]
@@ -40,14 +266,153 @@ pub struct Recipe {
     pub meal_type: String,
     pub source_name: String,
     pub source_url: Option<String>,
+    /// How many servings the ingredients are for.
+    pub servings: u32,
     pub ingredients: Vec<Ingredient>,
     pub instructions: Vec<String>,
+    /// The equipment needed to cook the recipe, by name.
+    pub equipment: Vec<String>,
     pub creation_date: NaiveDate,
+    pub owner_id: Option<i64>,
+    pub visibility: Visibility,
+    /// The household the recipe is shared with, if it is.
+    pub household_id: Option<i64>,
+    /// The recipe this recipe is a variation of, if it still exists.
+    pub forked_from: Option<i64>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Ingredient {
     pub quantity: f64,
     pub unit: String,
     pub name: String,
+    /// The recipe this ingredient is made by, a sub-recipe. Its quantity is in servings of that
+    /// recipe and its name is the title of that recipe.
+    #[serde(default)]
+    pub sub_recipe_id: Option<i64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Review {
+    pub id: i64,
+    pub username: String,
+    pub rating: u8,
+    pub text: Option<String>,
+    pub creation_date: NaiveDate,
+    /// Whether the review was written by the user that asked for it.
+    pub is_own: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct CookingLogEntry {
+    pub id: i64,
+    pub username: String,
+    pub cooked_on: NaiveDate,
+    pub servings: u32,
+    pub notes: Option<String>,
+    /// Whether the entry was logged by the user that asked for it.
+    pub is_own: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct CookedRecipe {
+    pub id: i64,
+    pub title: String,
+    pub last_cooked: NaiveDate,
+}
+
+/// Who is allowed to see a recipe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    /// Only the owner of the recipe.
+    Private,
+    /// The owner and the members of the household the recipe is shared with.
+    Household,
+    /// Everyone, including anonymous visitors.
+    Public,
+}
+
+impl Visibility {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::Household => "household",
+            Self::Public => "public",
+        }
+    }
+}
+
+impl TryFrom<&str> for Visibility {
+    type Error = RecipeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "private" => Ok(Self::Private),
+            "household" => Ok(Self::Household),
+            "public" => Ok(Self::Public),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReviewError {
+    #[error("recipe not found")]
+    NotFound,
+    #[error("a rating must be between 1 and 5")]
+    InvalidRating,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for ReviewError {
+    fn from(err: RecipeError) -> Self {
+        match err {
+            RecipeError::NotFound => Self::NotFound,
+            _ => Self::Internal,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CookingLogError {
+    #[error("recipe not found")]
+    NotFound,
+    #[error("at least one serving must be cooked")]
+    InvalidServings,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for CookingLogError {
+    fn from(err: RecipeError) -> Self {
+        match err {
+            RecipeError::NotFound => Self::NotFound,
+            _ => Self::Internal,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SetVisibilityError {
+    #[error("recipe not found")]
+    NotFound,
+    #[error("only the owner can change the visibility of a recipe")]
+    NotOwner,
+    #[error("a household is required to share a recipe with a household")]
+    MissingHousehold,
+    #[error("the owner is not a member of the household")]
+    NotHouseholdMember,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for SetVisibilityError {
+    fn from(err: RecipeError) -> Self {
+        match err {
+            RecipeError::NotFound => Self::NotFound,
+            _ => Self::Internal,
+        }
+    }
 }
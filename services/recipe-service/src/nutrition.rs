@@ -0,0 +1,78 @@
+use std::ops::{Add, Mul};
+
+use crate::{RecipeError, RecipeService, units};
+
+/// Energy in kcal and macronutrients in grams.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Nutrients {
+    pub kcal: f64,
+    pub protein: f64,
+    pub fat: f64,
+    pub carbohydrates: f64,
+}
+
+impl Add for Nutrients {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            kcal: self.kcal + rhs.kcal,
+            protein: self.protein + rhs.protein,
+            fat: self.fat + rhs.fat,
+            carbohydrates: self.carbohydrates + rhs.carbohydrates,
+        }
+    }
+}
+
+impl Mul<f64> for Nutrients {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        Self {
+            kcal: self.kcal * rhs,
+            protein: self.protein * rhs,
+            fat: self.fat * rhs,
+            carbohydrates: self.carbohydrates * rhs,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RecipeNutrition {
+    /// The nutrients of the whole recipe, without the unresolved ingredients.
+    pub total: Nutrients,
+    pub per_serving: Nutrients,
+    /// The ingredients without nutrition facts, or in a unit that can't be turned into grams.
+    pub unresolved: Vec<String>,
+}
+
+impl RecipeService {
+    /// Compute the nutrients of a recipe that is visible to `viewer` from the nutrition facts of
+    /// its ingredients, including those of its sub-recipes.
+    pub async fn nutrition(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<RecipeNutrition, RecipeError> {
+        let recipe = self.repo.recipe(recipe_id, viewer).await?;
+        let mut total = Nutrients::default();
+        let mut unresolved = Vec::new();
+        for (id, scale) in self.recipe_tree(recipe_id, viewer).await? {
+            for record in self.repo.nutrition_records(id).await? {
+                let grams = units::to_grams(record.quantity * scale, &record.unit, record.weights);
+                match (record.nutrients, grams) {
+                    (Some(per_100_grams), Some(grams)) => {
+                        total = total + per_100_grams * (grams / 100.0);
+                    }
+                    _ if unresolved.contains(&record.name) => {}
+                    _ => unresolved.push(record.name),
+                }
+            }
+        }
+        Ok(RecipeNutrition {
+            total,
+            per_serving: total * (1.0 / f64::from(recipe.servings)),
+            unresolved,
+        })
+    }
+}
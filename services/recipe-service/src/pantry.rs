@@ -0,0 +1,138 @@
+use chrono::NaiveDate;
+
+use crate::{RecipeError, RecipeService};
+
+#[derive(Clone, Debug)]
+pub struct PantryItem {
+    pub id: i64,
+    pub ingredient: String,
+    /// How much is left, if the user keeps track of it.
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub expires_on: Option<NaiveDate>,
+    pub is_expired: bool,
+}
+
+/// How well the pantry of a user covers the ingredients of a recipe.
+#[derive(Clone, Debug)]
+pub struct PantryMatch {
+    pub recipe_id: i64,
+    pub title: String,
+    /// The number of ingredients of the recipe in the pantry.
+    pub available: u32,
+    /// The number of ingredients of the recipe.
+    pub total: u32,
+    /// The ingredients that aren't in the pantry, in recipe order.
+    pub missing: Vec<String>,
+    /// The ingredients that aren't in the pantry but can be replaced by something that is, with
+    /// their replacement. These count as available.
+    pub substitutions: Vec<(String, String)>,
+}
+
+impl PantryMatch {
+    /// The fraction of the ingredients that are in the pantry.
+    #[must_use]
+    pub fn fraction(&self) -> f64 {
+        f64::from(self.available) / f64::from(self.total)
+    }
+}
+
+impl RecipeService {
+    /// The pantry of a user, what expires first comes first.
+    pub async fn pantry(&self, user_id: i64) -> Result<Vec<PantryItem>, RecipeError> {
+        self.repo.pantry(user_id).await
+    }
+
+    /// Add an ingredient to the pantry of a user, or update how much is left of it. Ingredients
+    /// are matched on their name, ignoring case.
+    pub async fn set_pantry_item(
+        &self,
+        user_id: i64,
+        ingredient: &str,
+        quantity: Option<(f64, &str)>,
+        expires_on: Option<NaiveDate>,
+    ) -> Result<i64, PantryError> {
+        let ingredient = ingredient.trim();
+        if ingredient.is_empty() {
+            return Err(PantryError::EmptyIngredient);
+        }
+        let quantity = match quantity {
+            Some((quantity, unit)) if quantity > 0.0 && !unit.trim().is_empty() => {
+                Some((quantity, unit.trim()))
+            }
+            Some(_) => return Err(PantryError::InvalidQuantity),
+            None => None,
+        };
+        let ingredient_id = self.repo.ingredient_id(ingredient).await?;
+        let id = self
+            .repo
+            .set_pantry_item(user_id, ingredient_id, quantity, expires_on)
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn remove_pantry_item(&self, user_id: i64, item_id: i64) -> Result<(), RecipeError> {
+        self.repo.remove_pantry_item(user_id, item_id).await
+    }
+
+    /// The recipes visible to a user that can be cooked, at least in part, with what's in their
+    /// pantry. The recipes with the largest fraction of their ingredients available come first,
+    /// ingredients that can be substituted count as available but recipes needing fewer
+    /// substitutions win ties. Expired pantry items don't count.
+    pub async fn what_can_i_cook(&self, user_id: i64) -> Result<Vec<PantryMatch>, RecipeError> {
+        let mut matches: Vec<PantryMatch> = Vec::new();
+        for record in self.repo.pantry_matches(user_id).await? {
+            if matches
+                .last()
+                .is_none_or(|last| last.recipe_id != record.recipe_id)
+            {
+                matches.push(PantryMatch {
+                    recipe_id: record.recipe_id,
+                    title: record.title,
+                    available: 0,
+                    total: 0,
+                    missing: Vec::new(),
+                    substitutions: Vec::new(),
+                });
+            }
+            let pantry_match = matches.last_mut().ok_or(RecipeError::ConsistencyError)?;
+            pantry_match.total += 1;
+            if record.in_pantry {
+                pantry_match.available += 1;
+            } else if let Some(substitute) = record.substitute {
+                pantry_match.available += 1;
+                pantry_match
+                    .substitutions
+                    .push((record.ingredient, substitute));
+            } else {
+                pantry_match.missing.push(record.ingredient);
+            }
+        }
+
+        matches.retain(|pantry_match| pantry_match.available > 0);
+        matches.sort_by(|a, b| {
+            b.fraction()
+                .total_cmp(&a.fraction())
+                .then(a.missing.len().cmp(&b.missing.len()))
+                .then(a.substitutions.len().cmp(&b.substitutions.len()))
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        Ok(matches)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PantryError {
+    #[error("an ingredient is required")]
+    EmptyIngredient,
+    #[error("a quantity must be positive and have a unit")]
+    InvalidQuantity,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for PantryError {
+    fn from(_: RecipeError) -> Self {
+        Self::Internal
+    }
+}
@@ -1,7 +1,20 @@
+mod collection;
+mod cooking_log;
+mod equipment;
+mod fork;
+mod household;
+mod nutrition;
+mod pantry;
+mod price;
+mod review;
+mod revision;
+mod sub_recipe;
+mod substitution;
+
 use chrono::NaiveDate;
 use sqlx::SqlitePool;
 
-use crate::{Ingredient, Recipe, RecipeListing};
+use crate::{Ingredient, ListingQuery, ListingSort, Recipe, RecipeListing, Visibility};
 
 #[derive(Clone)]
 pub(crate) struct RecipeRepository {
@@ -12,18 +25,39 @@ pub(crate) struct RecipeRepository {
 pub enum RecipeError {
     #[error("recipe not found")]
     NotFound,
+    #[error("the owner already has a recipe with this title")]
+    DuplicateTitle,
     #[error("database error: {0}")]
     Database(#[from] sqlx::Error),
     #[error("internal integrity was compromised")]
     ConsistencyError,
 }
 
+/// Dates are stored as `TEXT` in the database.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+fn parse_date(date: &str) -> Result<NaiveDate, RecipeError> {
+    let date = NaiveDate::parse_from_str(date, DATE_FORMAT)
+        .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+    Ok(date)
+}
+
+/// Titles are unique among the recipes of an owner.
+fn duplicate_title(err: sqlx::Error) -> RecipeError {
+    match err {
+        sqlx::Error::Database(err) if err.is_unique_violation() => RecipeError::DuplicateTitle,
+        err => err.into(),
+    }
+}
+
 impl RecipeRepository {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
     }
 
-    pub async fn recipe(&self, recipe_id: i64) -> Result<Recipe, RecipeError> {
+    pub async fn recipe(&self, recipe_id: i64, viewer: Option<i64>) -> Result<Recipe, RecipeError> {
+        // Every query that reads recipes on behalf of a user lets through public recipes and those
+        // in `recipe_viewer` for the user, which holds the rules for who may see the others.
         let r = sqlx::query!(
             r#"
             SELECT
@@ -32,7 +66,12 @@ impl RecipeRepository {
                 s.name AS source_name,
                 s.url AS source_url,
                 mt.type_name AS meal_type,
-                r.creation_date
+                r.creation_date,
+                r.servings,
+                r.owner_id,
+                r.visibility,
+                r.household_id,
+                r.forked_from
             FROM
                 recipe AS r
             JOIN
@@ -42,16 +81,26 @@ impl RecipeRepository {
                 meal_type AS mt
                 ON r.meal_type_id = mt.id
             WHERE
-                r.id = ?;
+                r.id = ?1
+                AND (
+                    r.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM recipe_viewer AS vr
+                        WHERE vr.recipe_id = r.id AND vr.viewer_id = ?2
+                    )
+                );
             "#,
-            recipe_id
+            recipe_id,
+            viewer,
         )
         .fetch_optional(&self.pool)
         .await?
         .ok_or(RecipeError::NotFound)?;
 
-        let ingredients = self.ingredients(recipe_id).await?;
+        let ingredients = self.ingredients(recipe_id, viewer).await?;
         let instructions = self.instructions(recipe_id).await?;
+        let equipment = self.recipe_equipment(recipe_id).await?;
 
         let recipe = Recipe {
             title: r.title,
@@ -59,22 +108,89 @@ impl RecipeRepository {
             meal_type: r.meal_type,
             source_name: r.source_name,
             source_url: r.source_url,
+            servings: u32::try_from(r.servings).map_err(|_| RecipeError::ConsistencyError)?,
             ingredients,
             instructions,
-            creation_date: NaiveDate::parse_from_str(&r.creation_date, "%Y-%m-%d")
-                .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+            equipment,
+            creation_date: parse_date(&r.creation_date)?,
+            owner_id: r.owner_id,
+            visibility: Visibility::try_from(r.visibility.as_str())?,
+            household_id: r.household_id,
+            forked_from: r.forked_from,
         };
         Ok(recipe)
     }
 
-    pub async fn recipe_listing(&self) -> Result<Vec<RecipeListing>, RecipeError> {
+    pub async fn recipe_listing(
+        &self,
+        viewer: Option<i64>,
+        query: &ListingQuery,
+    ) -> Result<Vec<RecipeListing>, RecipeError> {
+        let sort_by_rating = query.sort == ListingSort::Rating;
         let r = sqlx::query!(
             r#"
             SELECT
-                id,
-                title
-            FROM recipe;
-            "#
+                r.id,
+                r.title,
+                rating.average AS "average_rating?: f64",
+                COALESCE(rating.count, 0) AS "rating_count!: i64"
+            FROM
+                recipe AS r
+            LEFT JOIN
+                (
+                    SELECT
+                        recipe_id,
+                        AVG(rating) AS average,
+                        COUNT(*) AS count
+                    FROM review
+                    GROUP BY recipe_id
+                ) AS rating
+                ON r.id = rating.recipe_id
+            WHERE
+                (
+                    r.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM recipe_viewer AS vr
+                        WHERE vr.recipe_id = r.id AND vr.viewer_id = ?1
+                    )
+                )
+                AND (
+                    NOT ?2
+                    OR r.id IN (
+                        SELECT f.recipe_id
+                        FROM favorite AS f
+                        WHERE f.user_id = ?1
+                    )
+                )
+                AND (
+                    ?4 IS NULL
+                    OR r.forked_from = ?4
+                )
+                AND (
+                    NOT ?5
+                    OR NOT EXISTS (
+                        SELECT 1
+                        FROM
+                            recipe_equipment AS re
+                        JOIN
+                            unowned_equipment AS ue
+                            ON re.equipment_id = ue.equipment_id
+                        WHERE
+                            re.recipe_id = r.id
+                            AND ue.user_id = ?1
+                    )
+                )
+            ORDER BY
+                CASE WHEN ?3 THEN rating.average END DESC NULLS LAST,
+                CASE WHEN ?3 THEN rating.count END DESC,
+                r.title;
+            "#,
+            viewer,
+            query.favorites_only,
+            sort_by_rating,
+            query.variations_of,
+            query.owned_equipment_only,
         )
         .fetch_all(&self.pool)
         .await?;
@@ -83,32 +199,215 @@ impl RecipeRepository {
             .map(|r| RecipeListing {
                 id: r.id,
                 title: r.title,
+                average_rating: r.average_rating,
+                rating_count: r.rating_count,
             })
             .collect();
         Ok(recipe_listings)
     }
 
-    async fn ingredients(&self, recipe_id: i64) -> Result<Vec<Ingredient>, RecipeError> {
+    pub async fn is_visible(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<bool, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT r.id
+            FROM
+                recipe AS r
+            WHERE
+                r.id = ?1
+                AND (
+                    r.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM recipe_viewer AS vr
+                        WHERE vr.recipe_id = r.id AND vr.viewer_id = ?2
+                    )
+                );
+            "#,
+            recipe_id,
+            viewer,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(r.is_some())
+    }
+
+    pub async fn is_favorite(&self, user_id: i64, recipe_id: i64) -> Result<bool, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT recipe_id
+            FROM favorite
+            WHERE
+                user_id = ?
+                AND recipe_id = ?;
+            "#,
+            user_id,
+            recipe_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(r.is_some())
+    }
+
+    pub async fn add_favorite(&self, user_id: i64, recipe_id: i64) -> Result<(), RecipeError> {
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO favorite (user_id, recipe_id)
+            VALUES (?, ?);
+            "#,
+            user_id,
+            recipe_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_favorite(&self, user_id: i64, recipe_id: i64) -> Result<(), RecipeError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM favorite
+            WHERE
+                user_id = ?
+                AND recipe_id = ?;
+            "#,
+            user_id,
+            recipe_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn recipe_owner(&self, recipe_id: i64) -> Result<Option<i64>, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT owner_id
+            FROM recipe
+            WHERE id = ?;
+            "#,
+            recipe_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RecipeError::NotFound)?;
+        Ok(r.owner_id)
+    }
+
+    pub async fn is_household_member(
+        &self,
+        household_id: i64,
+        user_id: i64,
+    ) -> Result<bool, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT household_id
+            FROM household_member
+            WHERE
+                household_id = ?
+                AND user_id = ?;
+            "#,
+            household_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(r.is_some())
+    }
+
+    pub async fn set_visibility(
+        &self,
+        recipe_id: i64,
+        visibility: Visibility,
+        household_id: Option<i64>,
+    ) -> Result<(), RecipeError> {
+        let visibility = visibility.as_str();
+        let result = sqlx::query!(
+            r#"
+            UPDATE recipe SET
+                visibility = ?,
+                household_id = ?
+            WHERE id = ?;
+            "#,
+            visibility,
+            household_id,
+            recipe_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(RecipeError::NotFound),
+            1 => Ok(()),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+
+    pub async fn set_owner(&self, recipe_id: i64, owner_id: i64) -> Result<(), RecipeError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE recipe SET
+                owner_id = ?,
+                visibility = CASE visibility WHEN 'public' THEN 'public' ELSE 'private' END,
+                household_id = NULL
+            WHERE id = ?;
+            "#,
+            owner_id,
+            recipe_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(duplicate_title)?;
+        match result.rows_affected() {
+            0 => Err(RecipeError::NotFound),
+            1 => Ok(()),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+
+    /// The ingredients of a recipe, leaving out the sub-recipes that `viewer` can't see.
+    pub async fn ingredients(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<Ingredient>, RecipeError> {
+        // Sub-recipes are measured in servings and named after the recipe.
         let ingredients: Vec<_> = sqlx::query!(
             r#"
             SELECT
                 ri.quantity,
-                m.unit,
-                i.name
+                COALESCE(m.unit, 'serving') AS "unit!: String",
+                COALESCE(i.name, sr.title) AS "name!: String",
+                ri.sub_recipe_id
             FROM
                 recipe_ingredient AS ri
-            JOIN
+            LEFT JOIN
                 measurement AS m
                 ON ri.measurement_id = m.id
-            JOIN
+            LEFT JOIN
                 ingredient AS i
                 ON ri.ingredient_id = i.id
+            LEFT JOIN
+                recipe AS sr
+                ON ri.sub_recipe_id = sr.id
             WHERE
-                ri.recipe_id = ?
+                ri.recipe_id = ?1
+                AND (
+                    ri.sub_recipe_id IS NULL
+                    OR sr.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM recipe_viewer AS vr
+                        WHERE vr.recipe_id = sr.id AND vr.viewer_id = ?2
+                    )
+                )
             ORDER BY
                 ri.id;
             "#,
-            recipe_id
+            recipe_id,
+            viewer
         )
         .fetch_all(&self.pool)
         .await?
@@ -117,6 +416,7 @@ impl RecipeRepository {
             quantity: record.quantity,
             unit: record.unit,
             name: record.name,
+            sub_recipe_id: record.sub_recipe_id,
         })
         .collect();
         Ok(ingredients)
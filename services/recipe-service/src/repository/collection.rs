@@ -0,0 +1,347 @@
+use super::{RecipeError, RecipeRepository};
+use crate::{CollectionListing, RecipeListing, Visibility};
+
+/// A collection without its recipes.
+pub(crate) struct CollectionRecord {
+    pub id: i64,
+    pub owner_id: i64,
+    pub owner_username: String,
+    pub name: String,
+    pub description: String,
+    pub visibility: Visibility,
+}
+
+impl RecipeRepository {
+    /// List the collections visible to `viewer`, collections follow the same visibility rules as
+    /// recipes.
+    pub async fn collections(
+        &self,
+        viewer: Option<i64>,
+    ) -> Result<Vec<CollectionListing>, RecipeError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                c.id,
+                c.owner_id,
+                u.username AS owner_username,
+                c.name,
+                c.description
+            FROM
+                collection AS c
+            JOIN
+                user AS u
+                ON c.owner_id = u.id
+            WHERE
+                (
+                    c.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM collection_viewer AS vc
+                        WHERE vc.collection_id = c.id AND vc.viewer_id = ?1
+                    )
+                )
+            ORDER BY
+                c.name;
+            "#,
+            viewer,
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(CollectionListing {
+                id: record.id,
+                name: record.name,
+                description: record.description,
+                owner_username: record.owner_username,
+                is_own: viewer == Some(record.owner_id),
+            })
+        })
+        .collect()
+    }
+
+    pub async fn collection(
+        &self,
+        collection_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<CollectionRecord, RecipeError> {
+        let record = sqlx::query!(
+            r#"
+            SELECT
+                c.id,
+                c.owner_id,
+                u.username AS owner_username,
+                c.name,
+                c.description,
+                c.visibility
+            FROM
+                collection AS c
+            JOIN
+                user AS u
+                ON c.owner_id = u.id
+            WHERE
+                c.id = ?1
+                AND (
+                    c.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM collection_viewer AS vc
+                        WHERE vc.collection_id = c.id AND vc.viewer_id = ?2
+                    )
+                );
+            "#,
+            collection_id,
+            viewer,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RecipeError::NotFound)?;
+
+        Ok(CollectionRecord {
+            id: record.id,
+            owner_id: record.owner_id,
+            owner_username: record.owner_username,
+            name: record.name,
+            description: record.description,
+            visibility: Visibility::try_from(record.visibility.as_str())?,
+        })
+    }
+
+    /// The recipes of a collection in order, recipes that are not visible to `viewer` are left
+    /// out even if the collection itself is visible.
+    pub async fn collection_recipes(
+        &self,
+        collection_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<RecipeListing>, RecipeError> {
+        let recipe_listings = sqlx::query!(
+            r#"
+            SELECT
+                r.id,
+                r.title,
+                rating.average AS "average_rating?: f64",
+                COALESCE(rating.count, 0) AS "rating_count!: i64"
+            FROM
+                collection_recipe AS cr
+            JOIN
+                recipe AS r
+                ON cr.recipe_id = r.id
+            LEFT JOIN
+                (
+                    SELECT
+                        recipe_id,
+                        AVG(rating) AS average,
+                        COUNT(*) AS count
+                    FROM review
+                    GROUP BY recipe_id
+                ) AS rating
+                ON r.id = rating.recipe_id
+            WHERE
+                cr.collection_id = ?1
+                AND (
+                    r.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM recipe_viewer AS vr
+                        WHERE vr.recipe_id = r.id AND vr.viewer_id = ?2
+                    )
+                )
+            ORDER BY
+                cr.position;
+            "#,
+            collection_id,
+            viewer,
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|r| RecipeListing {
+            id: r.id,
+            title: r.title,
+            average_rating: r.average_rating,
+            rating_count: r.rating_count,
+        })
+        .collect();
+        Ok(recipe_listings)
+    }
+
+    /// The ids of all recipes in a collection in order, regardless of who can see them.
+    pub async fn collection_recipe_ids(&self, collection_id: i64) -> Result<Vec<i64>, RecipeError> {
+        let ids = sqlx::query!(
+            r#"
+            SELECT recipe_id
+            FROM collection_recipe
+            WHERE collection_id = ?
+            ORDER BY position;
+            "#,
+            collection_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| record.recipe_id)
+        .collect();
+        Ok(ids)
+    }
+
+    pub async fn collection_owner(&self, collection_id: i64) -> Result<i64, RecipeError> {
+        let record = sqlx::query!(
+            r#"
+            SELECT owner_id
+            FROM collection
+            WHERE id = ?;
+            "#,
+            collection_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RecipeError::NotFound)?;
+        Ok(record.owner_id)
+    }
+
+    pub async fn create_collection(
+        &self,
+        owner_id: i64,
+        name: &str,
+        description: &str,
+    ) -> Result<i64, RecipeError> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO collection (owner_id, name, description)
+            VALUES (?, ?, ?);
+            "#,
+            owner_id,
+            name,
+            description
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn update_collection(
+        &self,
+        collection_id: i64,
+        name: &str,
+        description: &str,
+        visibility: Visibility,
+        household_id: Option<i64>,
+    ) -> Result<(), RecipeError> {
+        let visibility = visibility.as_str();
+        let result = sqlx::query!(
+            r#"
+            UPDATE collection SET
+                name = ?,
+                description = ?,
+                visibility = ?,
+                household_id = ?
+            WHERE id = ?;
+            "#,
+            name,
+            description,
+            visibility,
+            household_id,
+            collection_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(RecipeError::NotFound),
+            1 => Ok(()),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+
+    pub async fn delete_collection(&self, collection_id: i64) -> Result<(), RecipeError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM collection
+            WHERE id = ?;
+            "#,
+            collection_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(RecipeError::NotFound),
+            1 => Ok(()),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+
+    /// Add a recipe last in a collection, adding a recipe that is already in the collection does
+    /// nothing.
+    pub async fn add_collection_recipe(
+        &self,
+        collection_id: i64,
+        recipe_id: i64,
+    ) -> Result<(), RecipeError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO collection_recipe (collection_id, recipe_id, position)
+            SELECT
+                ?1,
+                ?2,
+                COALESCE(MAX(position) + 1, 0)
+            FROM collection_recipe
+            WHERE collection_id = ?1
+            ON CONFLICT (collection_id, recipe_id) DO NOTHING;
+            "#,
+            collection_id,
+            recipe_id,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_collection_recipe(
+        &self,
+        collection_id: i64,
+        recipe_id: i64,
+    ) -> Result<(), RecipeError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM collection_recipe
+            WHERE
+                collection_id = ?
+                AND recipe_id = ?;
+            "#,
+            collection_id,
+            recipe_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(RecipeError::NotFound),
+            1 => Ok(()),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+
+    /// Reorder the recipes of a collection to the order of `recipe_ids`.
+    pub async fn set_collection_order(
+        &self,
+        collection_id: i64,
+        recipe_ids: &[i64],
+    ) -> Result<(), RecipeError> {
+        let mut tx = self.pool.begin().await?;
+        for (position, recipe_id) in (0_i64..).zip(recipe_ids) {
+            sqlx::query!(
+                r#"
+                UPDATE collection_recipe SET position = ?
+                WHERE
+                    collection_id = ?
+                    AND recipe_id = ?;
+                "#,
+                position,
+                collection_id,
+                recipe_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
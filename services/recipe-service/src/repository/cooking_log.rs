@@ -0,0 +1,153 @@
+use chrono::NaiveDate;
+
+use super::{DATE_FORMAT, RecipeError, RecipeRepository, parse_date};
+use crate::{CookedRecipe, CookingLogEntry};
+
+impl RecipeRepository {
+    pub async fn cooking_log(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<CookingLogEntry>, RecipeError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                cl.id,
+                cl.user_id,
+                u.username,
+                cl.cooked_on,
+                cl.servings,
+                cl.notes
+            FROM
+                cooking_log AS cl
+            JOIN
+                user AS u
+                ON cl.user_id = u.id
+            WHERE
+                cl.recipe_id = ?
+            ORDER BY
+                cl.cooked_on DESC,
+                cl.id DESC;
+            "#,
+            recipe_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(CookingLogEntry {
+                id: record.id,
+                username: record.username,
+                cooked_on: parse_date(&record.cooked_on)?,
+                servings: u32::try_from(record.servings)
+                    .map_err(|_| RecipeError::ConsistencyError)?,
+                notes: record.notes,
+                is_own: viewer == Some(record.user_id),
+            })
+        })
+        .collect()
+    }
+
+    pub async fn add_cooking_log_entry(
+        &self,
+        user_id: i64,
+        recipe_id: i64,
+        cooked_on: NaiveDate,
+        servings: u32,
+        notes: Option<&str>,
+    ) -> Result<i64, RecipeError> {
+        let cooked_on = cooked_on.format(DATE_FORMAT).to_string();
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO cooking_log (recipe_id, user_id, cooked_on, servings, notes)
+            VALUES (?, ?, ?, ?, ?);
+            "#,
+            recipe_id,
+            user_id,
+            cooked_on,
+            servings,
+            notes
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn delete_cooking_log_entry(
+        &self,
+        user_id: i64,
+        entry_id: i64,
+    ) -> Result<(), RecipeError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM cooking_log
+            WHERE
+                id = ?
+                AND user_id = ?;
+            "#,
+            entry_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(RecipeError::NotFound),
+            1 => Ok(()),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+
+    /// The recipes a user has cooked, together with when they last cooked them. Ordered by the
+    /// last time they were cooked, most recent first unless `oldest_first` is set.
+    pub async fn cooked_recipes(
+        &self,
+        user_id: i64,
+        oldest_first: bool,
+        limit: u32,
+    ) -> Result<Vec<CookedRecipe>, RecipeError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                r.id,
+                r.title,
+                MAX(cl.cooked_on) AS "last_cooked!: String"
+            FROM
+                cooking_log AS cl
+            JOIN
+                recipe AS r
+                ON cl.recipe_id = r.id
+            WHERE
+                cl.user_id = ?1
+                AND (
+                    r.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM recipe_viewer AS vr
+                        WHERE vr.recipe_id = r.id AND vr.viewer_id = ?1
+                    )
+                )
+            GROUP BY
+                r.id
+            ORDER BY
+                CASE WHEN ?2 THEN MAX(cl.cooked_on) END ASC,
+                MAX(cl.cooked_on) DESC,
+                r.title
+            LIMIT ?3;
+            "#,
+            user_id,
+            oldest_first,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(CookedRecipe {
+                id: record.id,
+                title: record.title,
+                last_cooked: parse_date(&record.last_cooked)?,
+            })
+        })
+        .collect()
+    }
+}
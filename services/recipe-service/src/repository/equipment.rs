@@ -0,0 +1,155 @@
+use super::{RecipeError, RecipeRepository};
+use crate::Equipment;
+
+impl RecipeRepository {
+    /// All the equipment by name, and whether `user_id` owns it.
+    pub async fn equipment(&self, user_id: Option<i64>) -> Result<Vec<Equipment>, RecipeError> {
+        let equipment = sqlx::query!(
+            r#"
+            SELECT
+                e.id,
+                e.name,
+                NOT EXISTS (
+                    SELECT 1
+                    FROM unowned_equipment AS ue
+                    WHERE
+                        ue.user_id = ?
+                        AND ue.equipment_id = e.id
+                ) AS "owned!: bool"
+            FROM
+                equipment AS e
+            ORDER BY
+                e.name;
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| Equipment {
+            id: record.id,
+            name: record.name,
+            owned: record.owned,
+        })
+        .collect();
+        Ok(equipment)
+    }
+
+    pub async fn set_equipment_owned(
+        &self,
+        user_id: i64,
+        equipment_id: i64,
+        owned: bool,
+    ) -> Result<(), RecipeError> {
+        sqlx::query!(
+            r#"
+            SELECT id
+            FROM equipment
+            WHERE id = ?;
+            "#,
+            equipment_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RecipeError::NotFound)?;
+        if owned {
+            sqlx::query!(
+                r#"
+                DELETE FROM unowned_equipment
+                WHERE
+                    user_id = ?
+                    AND equipment_id = ?;
+                "#,
+                user_id,
+                equipment_id
+            )
+            .execute(&self.pool)
+            .await?;
+        } else {
+            sqlx::query!(
+                r#"
+                INSERT OR IGNORE INTO unowned_equipment (user_id, equipment_id)
+                VALUES (?, ?);
+                "#,
+                user_id,
+                equipment_id
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// The id of the equipment with the given name, ignoring case, creating it if it's new.
+    pub async fn equipment_id(&self, name: &str) -> Result<i64, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            INSERT INTO equipment (name)
+            VALUES (?)
+            ON CONFLICT (name) DO UPDATE SET
+                name = name
+            RETURNING id;
+            "#,
+            name
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(r.id)
+    }
+
+    /// The equipment needed for a recipe, by name.
+    pub async fn recipe_equipment(&self, recipe_id: i64) -> Result<Vec<String>, RecipeError> {
+        let equipment = sqlx::query!(
+            r#"
+            SELECT e.name
+            FROM
+                recipe_equipment AS re
+            JOIN
+                equipment AS e
+                ON re.equipment_id = e.id
+            WHERE
+                re.recipe_id = ?
+            ORDER BY
+                e.name;
+            "#,
+            recipe_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| record.name)
+        .collect();
+        Ok(equipment)
+    }
+
+    pub async fn set_recipe_equipment(
+        &self,
+        recipe_id: i64,
+        equipment_ids: &[i64],
+    ) -> Result<(), RecipeError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM recipe_equipment
+            WHERE recipe_id = ?;
+            "#,
+            recipe_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for equipment_id in equipment_ids {
+            sqlx::query!(
+                r#"
+                INSERT INTO recipe_equipment (recipe_id, equipment_id)
+                VALUES (?, ?);
+                "#,
+                recipe_id,
+                equipment_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
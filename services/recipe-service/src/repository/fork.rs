@@ -0,0 +1,108 @@
+use super::{RecipeError, RecipeRepository, duplicate_title};
+
+impl RecipeRepository {
+    /// Copy a recipe with its ingredients, instructions and equipment into a private recipe owned by
+    /// `user_id`, returning the id of the copy. It's `RecipeError::DuplicateTitle` when the user
+    /// already has a recipe with the title.
+    pub async fn fork_recipe(
+        &self,
+        recipe_id: i64,
+        user_id: i64,
+        title: &str,
+    ) -> Result<i64, RecipeError> {
+        let mut tx = self.pool.begin().await?;
+        let fork_id = sqlx::query!(
+            r#"
+            INSERT INTO recipe (
+                title,
+                description,
+                meal_type_id,
+                source_id,
+                servings,
+                owner_id,
+                visibility,
+                forked_from
+            )
+            SELECT
+                ?,
+                description,
+                meal_type_id,
+                source_id,
+                servings,
+                ?,
+                'private',
+                id
+            FROM recipe
+            WHERE id = ?
+            RETURNING id;
+            "#,
+            title,
+            user_id,
+            recipe_id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(duplicate_title)?
+        .ok_or(RecipeError::NotFound)?
+        .id;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO recipe_ingredient (
+                quantity,
+                recipe_id,
+                ingredient_id,
+                measurement_id,
+                sub_recipe_id
+            )
+            SELECT
+                quantity,
+                ?,
+                ingredient_id,
+                measurement_id,
+                sub_recipe_id
+            FROM recipe_ingredient
+            WHERE recipe_id = ?
+            ORDER BY id;
+            "#,
+            fork_id,
+            recipe_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO instruction (step_number, description, recipe_id)
+            SELECT
+                step_number,
+                description,
+                ?
+            FROM instruction
+            WHERE recipe_id = ?
+            ORDER BY step_number;
+            "#,
+            fork_id,
+            recipe_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO recipe_equipment (recipe_id, equipment_id)
+            SELECT
+                ?,
+                equipment_id
+            FROM recipe_equipment
+            WHERE recipe_id = ?;
+            "#,
+            fork_id,
+            recipe_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(fork_id)
+    }
+}
@@ -0,0 +1,182 @@
+use super::{RecipeError, RecipeRepository};
+use crate::Household;
+
+impl RecipeRepository {
+    pub async fn households(&self, user_id: i64) -> Result<Vec<Household>, RecipeError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                h.id,
+                h.name,
+                h.invite_code,
+                u.username
+            FROM
+                household AS h
+            JOIN
+                household_member AS hm
+                ON h.id = hm.household_id
+            JOIN
+                user AS u
+                ON hm.user_id = u.id
+            WHERE
+                h.id IN (
+                    SELECT household_id
+                    FROM household_member
+                    WHERE user_id = ?
+                )
+            ORDER BY
+                h.name,
+                h.id,
+                u.username;
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut households: Vec<Household> = Vec::new();
+        for record in records {
+            match households.last_mut() {
+                Some(household) if household.id == record.id => {
+                    household.members.push(record.username);
+                }
+                _ => households.push(Household {
+                    id: record.id,
+                    name: record.name,
+                    invite_code: record.invite_code,
+                    members: vec![record.username],
+                }),
+            }
+        }
+        Ok(households)
+    }
+
+    pub async fn create_household(
+        &self,
+        user_id: i64,
+        name: &str,
+        invite_code: &str,
+    ) -> Result<i64, RecipeError> {
+        let mut tx = self.pool.begin().await?;
+        let household_id = sqlx::query!(
+            r#"
+            INSERT INTO household (name, invite_code)
+            VALUES (?, ?)
+            RETURNING id;
+            "#,
+            name,
+            invite_code
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .id;
+        sqlx::query!(
+            r#"
+            INSERT INTO household_member (household_id, user_id)
+            VALUES (?, ?);
+            "#,
+            household_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(household_id)
+    }
+
+    pub async fn household_by_invite_code(
+        &self,
+        invite_code: &str,
+    ) -> Result<Option<i64>, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT id
+            FROM household
+            WHERE invite_code = ?;
+            "#,
+            invite_code
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(r.map(|r| r.id))
+    }
+
+    pub async fn add_household_member(
+        &self,
+        household_id: i64,
+        user_id: i64,
+    ) -> Result<(), RecipeError> {
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO household_member (household_id, user_id)
+            VALUES (?, ?);
+            "#,
+            household_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Remove a member, making what they shared with the household private and removing the
+    /// household when nobody is left.
+    pub async fn remove_household_member(
+        &self,
+        household_id: i64,
+        user_id: i64,
+    ) -> Result<(), RecipeError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM household_member
+            WHERE household_id = ? AND user_id = ?;
+            "#,
+            household_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            UPDATE recipe SET
+                visibility = 'private',
+                household_id = NULL
+            WHERE household_id = ? AND owner_id = ?;
+            "#,
+            household_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            UPDATE collection SET
+                visibility = 'private',
+                household_id = NULL
+            WHERE household_id = ? AND owner_id = ?;
+            "#,
+            household_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM household
+            WHERE
+                id = ?1
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM household_member
+                    WHERE household_id = ?1
+                );
+            "#,
+            household_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
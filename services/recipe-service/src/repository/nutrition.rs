@@ -0,0 +1,85 @@
+use super::{RecipeError, RecipeRepository};
+use crate::nutrition::Nutrients;
+use crate::units::Weights;
+
+/// An ingredient of a recipe with what is known about its nutrition and weight.
+pub(crate) struct NutritionRecord {
+    pub name: String,
+    pub quantity: f64,
+    pub unit: String,
+    /// Per 100 g.
+    pub nutrients: Option<Nutrients>,
+    pub weights: Weights,
+}
+
+impl RecipeRepository {
+    pub async fn nutrition_records(
+        &self,
+        recipe_id: i64,
+    ) -> Result<Vec<NutritionRecord>, RecipeError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                i.name,
+                ri.quantity,
+                m.unit,
+                n.kcal AS "kcal?: f64",
+                n.protein AS "protein?: f64",
+                n.fat AS "fat?: f64",
+                n.carbohydrates AS "carbohydrates?: f64",
+                w.grams_per_ml,
+                w.grams_per_piece
+            FROM
+                recipe_ingredient AS ri
+            JOIN
+                measurement AS m
+                ON ri.measurement_id = m.id
+            JOIN
+                ingredient AS i
+                ON ri.ingredient_id = i.id
+            LEFT JOIN
+                nutrition AS n
+                ON TRIM(i.name) = n.ingredient
+            LEFT JOIN
+                ingredient_weight AS w
+                ON TRIM(i.name) = w.ingredient
+            WHERE
+                ri.recipe_id = ?
+            ORDER BY
+                ri.id;
+            "#,
+            recipe_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            let nutrients = match (
+                record.kcal,
+                record.protein,
+                record.fat,
+                record.carbohydrates,
+            ) {
+                (Some(kcal), Some(protein), Some(fat), Some(carbohydrates)) => Some(Nutrients {
+                    kcal,
+                    protein,
+                    fat,
+                    carbohydrates,
+                }),
+                _ => None,
+            };
+            NutritionRecord {
+                name: record.name,
+                quantity: record.quantity,
+                unit: record.unit,
+                nutrients,
+                weights: Weights {
+                    grams_per_ml: record.grams_per_ml,
+                    grams_per_piece: record.grams_per_piece,
+                },
+            }
+        })
+        .collect();
+        Ok(records)
+    }
+}
@@ -0,0 +1,221 @@
+use chrono::NaiveDate;
+
+use super::{DATE_FORMAT, RecipeError, RecipeRepository, parse_date};
+use crate::PantryItem;
+
+/// An ingredient of a recipe and whether the user has it in their pantry.
+pub(crate) struct PantryMatchRecord {
+    pub recipe_id: i64,
+    pub title: String,
+    pub ingredient: String,
+    pub in_pantry: bool,
+    /// A replacement in the pantry, for an ingredient that isn't.
+    pub substitute: Option<String>,
+}
+
+impl RecipeRepository {
+    /// The pantry of a user, what expires first comes first.
+    pub async fn pantry(&self, user_id: i64) -> Result<Vec<PantryItem>, RecipeError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                p.id,
+                i.name AS ingredient,
+                p.quantity,
+                p.unit,
+                p.expires_on,
+                (
+                    p.expires_on IS NOT NULL
+                    AND p.expires_on < STRFTIME('%Y-%m-%d', 'NOW')
+                ) AS "is_expired!: bool"
+            FROM
+                pantry_item AS p
+            JOIN
+                ingredient AS i
+                ON p.ingredient_id = i.id
+            WHERE
+                p.user_id = ?
+            ORDER BY
+                p.expires_on IS NULL,
+                p.expires_on,
+                i.name;
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(PantryItem {
+                id: record.id,
+                ingredient: record.ingredient,
+                quantity: record.quantity,
+                unit: record.unit,
+                expires_on: record.expires_on.as_deref().map(parse_date).transpose()?,
+                is_expired: record.is_expired,
+            })
+        })
+        .collect()
+    }
+
+    /// The id of the ingredient with the given name, ignoring case, creating it if it's new.
+    pub async fn ingredient_id(&self, name: &str) -> Result<i64, RecipeError> {
+        let existing = sqlx::query!(
+            r#"
+            SELECT id
+            FROM ingredient
+            WHERE name = ? COLLATE NOCASE
+            ORDER BY id
+            LIMIT 1;
+            "#,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some(existing) = existing {
+            return Ok(existing.id);
+        }
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO ingredient (name)
+            VALUES (?);
+            "#,
+            name
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Add an ingredient to the pantry of a user, replacing what was known about it before.
+    pub async fn set_pantry_item(
+        &self,
+        user_id: i64,
+        ingredient_id: i64,
+        quantity: Option<(f64, &str)>,
+        expires_on: Option<NaiveDate>,
+    ) -> Result<i64, RecipeError> {
+        let (quantity, unit) = quantity.unzip();
+        let expires_on = expires_on.map(|date| date.format(DATE_FORMAT).to_string());
+        let r = sqlx::query!(
+            r#"
+            INSERT INTO pantry_item (user_id, ingredient_id, quantity, unit, expires_on)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT (user_id, ingredient_id) DO UPDATE SET
+                quantity = excluded.quantity,
+                unit = excluded.unit,
+                expires_on = excluded.expires_on
+            RETURNING id;
+            "#,
+            user_id,
+            ingredient_id,
+            quantity,
+            unit,
+            expires_on
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(r.id)
+    }
+
+    pub async fn remove_pantry_item(&self, user_id: i64, item_id: i64) -> Result<(), RecipeError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM pantry_item
+            WHERE
+                id = ?
+                AND user_id = ?;
+            "#,
+            item_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(RecipeError::NotFound),
+            1 => Ok(()),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+
+    /// Every ingredient of every recipe visible to the user, ordered by recipe, and whether it's
+    /// in the pantry of the user and not expired, or else what can replace it from the pantry.
+    pub async fn pantry_matches(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<PantryMatchRecord>, RecipeError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                r.id AS recipe_id,
+                r.title,
+                i.name AS ingredient,
+                EXISTS (
+                    SELECT 1
+                    FROM pantry_item AS p
+                    WHERE
+                        p.user_id = ?1
+                        AND p.ingredient_id = ri.ingredient_id
+                        AND (
+                            p.expires_on IS NULL
+                            OR p.expires_on >= STRFTIME('%Y-%m-%d', 'NOW')
+                        )
+                ) AS "in_pantry!: bool",
+                (
+                    SELECT replacement.name
+                    FROM
+                        substitution AS s
+                    JOIN
+                        ingredient AS replacement
+                        ON s.replacement_id = replacement.id
+                    JOIN
+                        pantry_item AS p
+                        ON s.replacement_id = p.ingredient_id
+                    WHERE
+                        s.ingredient_id = ri.ingredient_id
+                        AND p.user_id = ?1
+                        AND (
+                            p.expires_on IS NULL
+                            OR p.expires_on >= STRFTIME('%Y-%m-%d', 'NOW')
+                        )
+                    ORDER BY
+                        replacement.name
+                    LIMIT 1
+                ) AS "substitute?: String"
+            FROM
+                recipe AS r
+            JOIN
+                recipe_ingredient AS ri
+                ON r.id = ri.recipe_id
+            JOIN
+                ingredient AS i
+                ON ri.ingredient_id = i.id
+            WHERE
+                (
+                    r.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM recipe_viewer AS vr
+                        WHERE vr.recipe_id = r.id AND vr.viewer_id = ?1
+                    )
+                )
+            ORDER BY
+                r.id,
+                ri.id;
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| PantryMatchRecord {
+            recipe_id: record.recipe_id,
+            title: record.title,
+            ingredient: record.ingredient,
+            in_pantry: record.in_pantry,
+            substitute: record.substitute,
+        })
+        .collect();
+        Ok(records)
+    }
+}
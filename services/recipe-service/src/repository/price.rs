@@ -0,0 +1,135 @@
+use chrono::NaiveDate;
+
+use super::{DATE_FORMAT, RecipeError, RecipeRepository, parse_date};
+use crate::Price;
+use crate::units::Weights;
+
+impl RecipeRepository {
+    /// The prices that apply today, one per ingredient and store.
+    pub async fn current_prices(&self) -> Result<Vec<Price>, RecipeError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                ip.id,
+                ip.ingredient,
+                s.name AS store,
+                ip.price,
+                ip.unit,
+                ip.effective_from
+            FROM
+                ingredient_price AS ip
+            JOIN
+                store AS s
+                ON ip.store_id = s.id
+            WHERE
+                ip.effective_from <= STRFTIME('%Y-%m-%d', 'NOW')
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM ingredient_price AS newer
+                    WHERE
+                        newer.ingredient = ip.ingredient
+                        AND newer.store_id = ip.store_id
+                        AND newer.effective_from > ip.effective_from
+                        AND newer.effective_from <= STRFTIME('%Y-%m-%d', 'NOW')
+                )
+            ORDER BY
+                ip.ingredient,
+                s.name;
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(Price {
+                id: record.id,
+                ingredient: record.ingredient,
+                store: record.store,
+                price: record.price,
+                unit: record.unit,
+                effective_from: parse_date(&record.effective_from)?,
+            })
+        })
+        .collect()
+    }
+
+    /// The weights of every ingredient that has them, by lowercase name.
+    pub async fn ingredient_weights(&self) -> Result<Vec<(String, Weights)>, RecipeError> {
+        let weights = sqlx::query!(
+            r#"
+            SELECT
+                ingredient,
+                grams_per_ml,
+                grams_per_piece
+            FROM ingredient_weight;
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            let weights = Weights {
+                grams_per_ml: record.grams_per_ml,
+                grams_per_piece: record.grams_per_piece,
+            };
+            (record.ingredient.to_lowercase(), weights)
+        })
+        .collect();
+        Ok(weights)
+    }
+
+    /// The id of the store with the given name, ignoring case, creating it if it's new.
+    pub async fn store_id(&self, name: &str) -> Result<i64, RecipeError> {
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO store (name)
+            VALUES (?);
+            "#,
+            name
+        )
+        .execute(&self.pool)
+        .await?;
+        let r = sqlx::query!(
+            r#"
+            SELECT id
+            FROM store
+            WHERE name = ?;
+            "#,
+            name
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(r.id)
+    }
+
+    /// Set the price of an ingredient in a store from a date on, replacing any price set for the
+    /// same date.
+    pub async fn set_price(
+        &self,
+        ingredient: &str,
+        store_id: i64,
+        price: f64,
+        unit: &str,
+        effective_from: NaiveDate,
+    ) -> Result<i64, RecipeError> {
+        let effective_from = effective_from.format(DATE_FORMAT).to_string();
+        let r = sqlx::query!(
+            r#"
+            INSERT INTO ingredient_price (ingredient, store_id, price, unit, effective_from)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT (ingredient, store_id, effective_from) DO UPDATE SET
+                price = excluded.price,
+                unit = excluded.unit
+            RETURNING id;
+            "#,
+            ingredient,
+            store_id,
+            price,
+            unit,
+            effective_from
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(r.id)
+    }
+}
@@ -0,0 +1,94 @@
+use super::{RecipeError, RecipeRepository, parse_date};
+use crate::Review;
+
+impl RecipeRepository {
+    pub async fn reviews(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<Review>, RecipeError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                rv.id,
+                rv.user_id,
+                u.username,
+                rv.rating,
+                rv.text,
+                rv.creation_date
+            FROM
+                review AS rv
+            JOIN
+                user AS u
+                ON rv.user_id = u.id
+            WHERE
+                rv.recipe_id = ?
+            ORDER BY
+                rv.creation_date DESC,
+                rv.id DESC;
+            "#,
+            recipe_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(Review {
+                id: record.id,
+                username: record.username,
+                rating: u8::try_from(record.rating).map_err(|_| RecipeError::ConsistencyError)?,
+                text: record.text,
+                creation_date: parse_date(&record.creation_date)?,
+                is_own: viewer == Some(record.user_id),
+            })
+        })
+        .collect()
+    }
+
+    /// Add a review, or replace the existing review by the same user.
+    pub async fn set_review(
+        &self,
+        user_id: i64,
+        recipe_id: i64,
+        rating: u8,
+        text: Option<&str>,
+    ) -> Result<(), RecipeError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO review (recipe_id, user_id, rating, text)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (recipe_id, user_id) DO UPDATE SET
+                rating = excluded.rating,
+                text = excluded.text,
+                creation_date = STRFTIME('%Y-%m-%d', 'NOW');
+            "#,
+            recipe_id,
+            user_id,
+            rating,
+            text
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_review(&self, user_id: i64, recipe_id: i64) -> Result<(), RecipeError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM review
+            WHERE
+                recipe_id = ?
+                AND user_id = ?;
+            "#,
+            recipe_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(RecipeError::NotFound),
+            1 => Ok(()),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+}
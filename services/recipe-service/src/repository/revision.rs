@@ -0,0 +1,308 @@
+use chrono::NaiveDateTime;
+use sqlx::SqliteConnection;
+
+use super::{RecipeError, RecipeRepository, duplicate_title};
+use crate::{Ingredient, RecipeContent, Revision, RevisionListing};
+
+/// Timestamps are stored as `TEXT` in UTC.
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn parse_datetime(datetime: &str) -> Result<NaiveDateTime, RecipeError> {
+    let datetime = NaiveDateTime::parse_from_str(datetime, DATETIME_FORMAT)
+        .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+    Ok(datetime)
+}
+
+fn parse_number(number: i64) -> Result<u32, RecipeError> {
+    u32::try_from(number).map_err(|_| RecipeError::ConsistencyError)
+}
+
+impl RecipeRepository {
+    /// Whether another recipe of `owner_id` than `recipe_id` already has the title. Titles are
+    /// only unique per owner, so that they don't give away the private recipes of others.
+    pub async fn title_taken(
+        &self,
+        title: &str,
+        recipe_id: i64,
+        owner_id: i64,
+    ) -> Result<bool, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT id
+            FROM recipe
+            WHERE
+                title = ?
+                AND id != ?
+                AND owner_id = ?;
+            "#,
+            title,
+            recipe_id,
+            owner_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(r.is_some())
+    }
+
+    /// Replace the content of a recipe and record it as a new revision by `author_id`. If the
+    /// recipe has no revisions yet, `current` is recorded first as revision 1, by the owner and
+    /// at the creation date of the recipe. Returns the number of the new revision.
+    pub async fn update_recipe(
+        &self,
+        recipe_id: i64,
+        author_id: i64,
+        current: &RecipeContent,
+        content: &RecipeContent,
+    ) -> Result<u32, RecipeError> {
+        let snapshot = serde_json::to_string(content).map_err(|_| RecipeError::ConsistencyError)?;
+        let mut tx = self.pool.begin().await?;
+
+        let latest = sqlx::query!(
+            r#"
+            SELECT COALESCE(MAX(number), 0) AS "number!: i64"
+            FROM recipe_revision
+            WHERE recipe_id = ?;
+            "#,
+            recipe_id
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .number;
+        let mut number = latest + 1;
+        if latest == 0 {
+            let baseline =
+                serde_json::to_string(current).map_err(|_| RecipeError::ConsistencyError)?;
+            sqlx::query!(
+                r#"
+                INSERT INTO recipe_revision (recipe_id, number, author_id, created_at, snapshot)
+                SELECT id, 1, owner_id, creation_date || ' 00:00:00', ?
+                FROM recipe
+                WHERE id = ?;
+                "#,
+                baseline,
+                recipe_id
+            )
+            .execute(&mut *tx)
+            .await?;
+            number += 1;
+        }
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE recipe SET
+                title = ?,
+                description = ?,
+                servings = ?
+            WHERE id = ?;
+            "#,
+            content.title,
+            content.description,
+            content.servings,
+            recipe_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(duplicate_title)?;
+        if result.rows_affected() != 1 {
+            return Err(RecipeError::NotFound);
+        }
+
+        sqlx::query!(
+            r#"
+            DELETE FROM recipe_ingredient
+            WHERE recipe_id = ?;
+            "#,
+            recipe_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for ingredient in &content.ingredients {
+            insert_ingredient(&mut tx, recipe_id, ingredient).await?;
+        }
+
+        sqlx::query!(
+            r#"
+            DELETE FROM instruction
+            WHERE recipe_id = ?;
+            "#,
+            recipe_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for (step_number, instruction) in (1_i64..).zip(&content.instructions) {
+            sqlx::query!(
+                r#"
+                INSERT INTO instruction (step_number, description, recipe_id)
+                VALUES (?, ?, ?);
+                "#,
+                step_number,
+                instruction,
+                recipe_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO recipe_revision (recipe_id, number, author_id, snapshot)
+            VALUES (?, ?, ?, ?);
+            "#,
+            recipe_id,
+            number,
+            author_id,
+            snapshot
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        parse_number(number)
+    }
+
+    /// The revisions of a recipe, newest first.
+    pub async fn revisions(&self, recipe_id: i64) -> Result<Vec<RevisionListing>, RecipeError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                rr.number,
+                u.username AS "author?",
+                rr.created_at
+            FROM
+                recipe_revision AS rr
+            LEFT JOIN
+                user AS u
+                ON rr.author_id = u.id
+            WHERE
+                rr.recipe_id = ?
+            ORDER BY
+                rr.number DESC;
+            "#,
+            recipe_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(RevisionListing {
+                number: parse_number(record.number)?,
+                author: record.author,
+                created_at: parse_datetime(&record.created_at)?,
+            })
+        })
+        .collect()
+    }
+
+    pub async fn revision(&self, recipe_id: i64, number: u32) -> Result<Revision, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT
+                rr.number,
+                u.username AS "author?",
+                rr.created_at,
+                rr.snapshot
+            FROM
+                recipe_revision AS rr
+            LEFT JOIN
+                user AS u
+                ON rr.author_id = u.id
+            WHERE
+                rr.recipe_id = ?
+                AND rr.number = ?;
+            "#,
+            recipe_id,
+            number
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RecipeError::NotFound)?;
+        Ok(Revision {
+            number: parse_number(r.number)?,
+            author: r.author,
+            created_at: parse_datetime(&r.created_at)?,
+            content: serde_json::from_str(&r.snapshot)
+                .map_err(|_| RecipeError::ConsistencyError)?,
+        })
+    }
+}
+
+/// Add an ingredient to a recipe, creating the ingredient and its unit if they are new.
+/// Ingredients and units are matched on their name, ignoring case.
+async fn insert_ingredient(
+    conn: &mut SqliteConnection,
+    recipe_id: i64,
+    ingredient: &Ingredient,
+) -> Result<(), RecipeError> {
+    if let Some(sub_recipe_id) = ingredient.sub_recipe_id {
+        sqlx::query!(
+            r#"
+            INSERT INTO recipe_ingredient (quantity, recipe_id, sub_recipe_id)
+            VALUES (?, ?, ?);
+            "#,
+            ingredient.quantity,
+            recipe_id,
+            sub_recipe_id
+        )
+        .execute(&mut *conn)
+        .await?;
+        return Ok(());
+    }
+    sqlx::query!(
+        r#"
+        INSERT INTO ingredient (name)
+        SELECT ?1
+        WHERE NOT EXISTS (
+            SELECT 1
+            FROM ingredient
+            WHERE name = ?1 COLLATE NOCASE
+        );
+        "#,
+        ingredient.name
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO measurement (unit)
+        SELECT ?1
+        WHERE NOT EXISTS (
+            SELECT 1
+            FROM measurement
+            WHERE unit = ?1 COLLATE NOCASE
+        );
+        "#,
+        ingredient.unit
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO recipe_ingredient (quantity, recipe_id, ingredient_id, measurement_id)
+        VALUES (
+            ?1,
+            ?2,
+            (
+                SELECT id
+                FROM ingredient
+                WHERE name = ?3 COLLATE NOCASE
+                ORDER BY id
+                LIMIT 1
+            ),
+            (
+                SELECT id
+                FROM measurement
+                WHERE unit = ?4 COLLATE NOCASE
+                ORDER BY id
+                LIMIT 1
+            )
+        );
+        "#,
+        ingredient.quantity,
+        recipe_id,
+        ingredient.name,
+        ingredient.unit
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use super::{RecipeError, RecipeRepository};
+
+/// A sub-recipe used by a recipe.
+pub(crate) struct SubRecipeRecord {
+    pub recipe_id: i64,
+    /// In servings of the sub-recipe.
+    pub quantity: f64,
+    /// How many servings the ingredients of the sub-recipe are for.
+    pub servings: u32,
+}
+
+impl RecipeRepository {
+    /// The sub-recipes a recipe uses directly that are visible to `viewer`, in recipe order.
+    pub async fn sub_recipes(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<SubRecipeRecord>, RecipeError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                sr.id,
+                ri.quantity,
+                sr.servings
+            FROM
+                recipe_ingredient AS ri
+            JOIN
+                recipe AS sr
+                ON ri.sub_recipe_id = sr.id
+            WHERE
+                ri.recipe_id = ?1
+                AND (
+                    sr.visibility = 'public'
+                    OR EXISTS (
+                        SELECT 1
+                        FROM recipe_viewer AS vr
+                        WHERE vr.recipe_id = sr.id AND vr.viewer_id = ?2
+                    )
+                )
+            ORDER BY
+                ri.id;
+            "#,
+            recipe_id,
+            viewer
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(SubRecipeRecord {
+                recipe_id: record.id,
+                quantity: record.quantity,
+                servings: u32::try_from(record.servings)
+                    .map_err(|_| RecipeError::ConsistencyError)?,
+            })
+        })
+        .collect()
+    }
+
+    /// Whether recipe `to` is `from` itself or used by it, directly or through other
+    /// sub-recipes.
+    pub async fn uses_recipe(&self, from: i64, to: i64) -> Result<bool, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            WITH RECURSIVE used (id) AS (
+                SELECT ?1
+                UNION
+                SELECT ri.sub_recipe_id
+                FROM
+                    recipe_ingredient AS ri
+                JOIN
+                    used
+                    ON ri.recipe_id = used.id
+                WHERE
+                    ri.sub_recipe_id IS NOT NULL
+            )
+            SELECT EXISTS (
+                SELECT 1
+                FROM used
+                WHERE id = ?2
+            ) AS "uses!: bool";
+            "#,
+            from,
+            to
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(r.uses)
+    }
+}
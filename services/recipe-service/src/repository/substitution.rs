@@ -0,0 +1,96 @@
+use super::{RecipeError, RecipeRepository};
+use crate::Substitution;
+
+impl RecipeRepository {
+    /// The substitutions by ingredient and replacement, only those for the ingredients of
+    /// `recipe_id` if given.
+    pub async fn substitutions(
+        &self,
+        recipe_id: Option<i64>,
+    ) -> Result<Vec<Substitution>, RecipeError> {
+        let substitutions = sqlx::query!(
+            r#"
+            SELECT
+                s.id,
+                i.name AS ingredient,
+                r.name AS replacement,
+                s.ratio,
+                s.note
+            FROM
+                substitution AS s
+            JOIN
+                ingredient AS i
+                ON s.ingredient_id = i.id
+            JOIN
+                ingredient AS r
+                ON s.replacement_id = r.id
+            WHERE
+                ?1 IS NULL
+                OR s.ingredient_id IN (
+                    SELECT ri.ingredient_id
+                    FROM recipe_ingredient AS ri
+                    WHERE ri.recipe_id = ?1
+                )
+            ORDER BY
+                i.name,
+                r.name;
+            "#,
+            recipe_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| Substitution {
+            id: record.id,
+            ingredient: record.ingredient,
+            replacement: record.replacement,
+            ratio: record.ratio,
+            note: record.note,
+        })
+        .collect();
+        Ok(substitutions)
+    }
+
+    pub async fn set_substitution(
+        &self,
+        ingredient_id: i64,
+        replacement_id: i64,
+        ratio: f64,
+        note: &str,
+    ) -> Result<i64, RecipeError> {
+        let r = sqlx::query!(
+            r#"
+            INSERT INTO substitution (ingredient_id, replacement_id, ratio, note)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (ingredient_id, replacement_id) DO UPDATE SET
+                ratio = excluded.ratio,
+                note = excluded.note
+            RETURNING id;
+            "#,
+            ingredient_id,
+            replacement_id,
+            ratio,
+            note
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(r.id)
+    }
+
+    pub async fn remove_substitution(&self, substitution_id: i64) -> Result<(), RecipeError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM substitution
+            WHERE id = ?;
+            "#,
+            substitution_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(RecipeError::NotFound),
+            1 => Ok(()),
+            _ => Err(RecipeError::ConsistencyError),
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{Ingredient, Recipe, RecipeError, RecipeService};
+
+/// The parts of a recipe that can be edited, and that every revision keeps a copy of.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecipeContent {
+    pub title: String,
+    pub description: String,
+    pub servings: u32,
+    pub ingredients: Vec<Ingredient>,
+    pub instructions: Vec<String>,
+}
+
+impl From<Recipe> for RecipeContent {
+    fn from(recipe: Recipe) -> Self {
+        Self {
+            title: recipe.title,
+            description: recipe.description,
+            servings: recipe.servings,
+            ingredients: recipe.ingredients,
+            instructions: recipe.instructions,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RevisionListing {
+    /// Counts up from 1 per recipe.
+    pub number: u32,
+    /// `None` if the author deleted their account.
+    pub author: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug)]
+pub struct Revision {
+    pub number: u32,
+    pub author: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub content: RecipeContent,
+}
+
+/// What changed between two revisions of a recipe.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RevisionDiff {
+    pub fields: Vec<FieldChange>,
+    pub ingredients: Vec<IngredientChange>,
+    pub instructions: Vec<InstructionChange>,
+}
+
+impl RevisionDiff {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.ingredients.is_empty() && self.instructions.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecipeField {
+    Title,
+    Description,
+    Servings,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: RecipeField,
+    pub old: String,
+    pub new: String,
+}
+
+/// Ingredients are matched on their name, ignoring case.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IngredientChange {
+    Added(Ingredient),
+    Removed(Ingredient),
+    /// The quantity or unit changed.
+    Changed {
+        old: Ingredient,
+        new: Ingredient,
+    },
+}
+
+/// Steps are numbered from 1, removed steps by their number in the old revision and added or
+/// edited steps by their number in the new revision.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InstructionChange {
+    Added {
+        step: usize,
+        text: String,
+    },
+    Removed {
+        step: usize,
+        text: String,
+    },
+    Edited {
+        step: usize,
+        old: String,
+        new: String,
+    },
+}
+
+impl RecipeService {
+    /// Edit a recipe, only the owner of a recipe is allowed to do this. Every edit is kept as a
+    /// new revision, the first edit also keeps the recipe as it was before as revision 1.
+    /// Returns the number of the new revision.
+    pub async fn update_recipe(
+        &self,
+        recipe_id: i64,
+        user_id: i64,
+        content: &RecipeContent,
+    ) -> Result<u32, EditError> {
+        let mut content = validate(content)?;
+        let owner_id = self.repo.recipe_owner(recipe_id).await?;
+        if owner_id != Some(user_id) {
+            return Err(EditError::NotOwner);
+        }
+        if self
+            .repo
+            .title_taken(&content.title, recipe_id, user_id)
+            .await?
+        {
+            return Err(EditError::DuplicateTitle);
+        }
+        for ingredient in &mut content.ingredients {
+            let Some(sub_recipe_id) = ingredient.sub_recipe_id else {
+                continue;
+            };
+            let sub_recipe = match self.repo.recipe(sub_recipe_id, Some(user_id)).await {
+                Ok(sub_recipe) => sub_recipe,
+                Err(RecipeError::NotFound) => return Err(EditError::SubRecipeNotFound),
+                Err(err) => return Err(err.into()),
+            };
+            if self.repo.uses_recipe(sub_recipe_id, recipe_id).await? {
+                return Err(EditError::Cycle);
+            }
+            ingredient.name = sub_recipe.title;
+            ingredient.unit = "serving".to_string();
+        }
+        let current = RecipeContent::from(self.repo.recipe(recipe_id, Some(user_id)).await?);
+        let number = self
+            .repo
+            .update_recipe(recipe_id, user_id, &current, &content)
+            .await?;
+        Ok(number)
+    }
+
+    /// The revisions of a recipe that is visible to `viewer`, newest first. Recipes that were
+    /// never edited have no revisions.
+    pub async fn revisions(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<RevisionListing>, RecipeError> {
+        if !self.repo.is_visible(recipe_id, viewer).await? {
+            return Err(RecipeError::NotFound);
+        }
+        self.repo.revisions(recipe_id).await
+    }
+
+    pub async fn revision(
+        &self,
+        recipe_id: i64,
+        number: u32,
+        viewer: Option<i64>,
+    ) -> Result<Revision, RecipeError> {
+        if !self.repo.is_visible(recipe_id, viewer).await? {
+            return Err(RecipeError::NotFound);
+        }
+        self.repo.revision(recipe_id, number).await
+    }
+
+    /// What changed from revision `from` to revision `to` of a recipe.
+    pub async fn revision_diff(
+        &self,
+        recipe_id: i64,
+        from: u32,
+        to: u32,
+        viewer: Option<i64>,
+    ) -> Result<RevisionDiff, RecipeError> {
+        let old = self.revision(recipe_id, from, viewer).await?;
+        let new = self.revision(recipe_id, to, viewer).await?;
+        Ok(diff(&old.content, &new.content))
+    }
+
+    /// Bring a recipe back to how it was in an older revision. History is never rewritten, the
+    /// revert is kept as a new revision. Returns the number of the new revision.
+    pub async fn revert_recipe(
+        &self,
+        recipe_id: i64,
+        user_id: i64,
+        number: u32,
+    ) -> Result<u32, EditError> {
+        // Before looking at the revision, so that others can't find out which revisions exist.
+        let owner_id = self.repo.recipe_owner(recipe_id).await?;
+        if owner_id != Some(user_id) {
+            return Err(EditError::NotOwner);
+        }
+        let revision = self.repo.revision(recipe_id, number).await?;
+        self.update_recipe(recipe_id, user_id, &revision.content)
+            .await
+    }
+}
+
+/// Trim the content of a recipe, dropping empty instructions.
+fn validate(content: &RecipeContent) -> Result<RecipeContent, EditError> {
+    let title = content.title.trim();
+    if title.is_empty() {
+        return Err(EditError::EmptyTitle);
+    }
+    if content.servings == 0 {
+        return Err(EditError::InvalidServings);
+    }
+    let mut ingredients = Vec::with_capacity(content.ingredients.len());
+    for ingredient in &content.ingredients {
+        let name = ingredient.name.trim();
+        let unit = ingredient.unit.trim();
+        let quantity = ingredient.quantity;
+        // The name and unit of a sub-recipe are filled in from the sub-recipe.
+        let is_sub_recipe = ingredient.sub_recipe_id.is_some();
+        if (!is_sub_recipe && (name.is_empty() || unit.is_empty()))
+            || !quantity.is_finite()
+            || quantity <= 0.0
+        {
+            return Err(EditError::InvalidIngredient);
+        }
+        ingredients.push(Ingredient {
+            quantity,
+            unit: unit.to_string(),
+            name: name.to_string(),
+            sub_recipe_id: ingredient.sub_recipe_id,
+        });
+    }
+    Ok(RecipeContent {
+        title: title.to_string(),
+        description: content.description.trim().to_string(),
+        servings: content.servings,
+        ingredients,
+        instructions: content
+            .instructions
+            .iter()
+            .map(|instruction| instruction.trim())
+            .filter(|instruction| !instruction.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+fn diff(old: &RecipeContent, new: &RecipeContent) -> RevisionDiff {
+    let mut fields = Vec::new();
+    let mut field = |field, old: String, new: String| {
+        if old != new {
+            fields.push(FieldChange { field, old, new });
+        }
+    };
+    field(RecipeField::Title, old.title.clone(), new.title.clone());
+    field(
+        RecipeField::Description,
+        old.description.clone(),
+        new.description.clone(),
+    );
+    field(
+        RecipeField::Servings,
+        old.servings.to_string(),
+        new.servings.to_string(),
+    );
+
+    RevisionDiff {
+        fields,
+        ingredients: diff_ingredients(&old.ingredients, &new.ingredients),
+        instructions: diff_instructions(&old.instructions, &new.instructions),
+    }
+}
+
+fn diff_ingredients(old: &[Ingredient], new: &[Ingredient]) -> Vec<IngredientChange> {
+    let key = |ingredient: &Ingredient| ingredient.name.trim().to_lowercase();
+    let mut matched = vec![false; new.len()];
+    let mut changes = Vec::new();
+    for old_ingredient in old {
+        let position = new.iter().enumerate().position(|(i, new_ingredient)| {
+            !matched[i] && key(new_ingredient) == key(old_ingredient)
+        });
+        match position {
+            Some(i) => {
+                matched[i] = true;
+                let new_ingredient = &new[i];
+                if new_ingredient != old_ingredient {
+                    changes.push(IngredientChange::Changed {
+                        old: old_ingredient.clone(),
+                        new: new_ingredient.clone(),
+                    });
+                }
+            }
+            None => changes.push(IngredientChange::Removed(old_ingredient.clone())),
+        }
+    }
+    for (new_ingredient, _) in new.iter().zip(&matched).filter(|(_, matched)| !**matched) {
+        changes.push(IngredientChange::Added(new_ingredient.clone()));
+    }
+    changes
+}
+
+/// A line diff of the steps, based on their longest common subsequence. A run of removed steps
+/// followed by added steps is reported as edits, pairwise.
+fn diff_instructions(old: &[String], new: &[String]) -> Vec<InstructionChange> {
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+    let mut common = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            flush(&mut changes, &mut removed, &mut added, old, new);
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            added.push(j);
+            j += 1;
+        } else {
+            removed.push(i);
+            i += 1;
+        }
+    }
+    flush(&mut changes, &mut removed, &mut added, old, new);
+    changes
+}
+
+fn flush(
+    changes: &mut Vec<InstructionChange>,
+    removed: &mut Vec<usize>,
+    added: &mut Vec<usize>,
+    old: &[String],
+    new: &[String],
+) {
+    let edited = removed.len().min(added.len());
+    for (&i, &j) in removed.iter().zip(added.iter()) {
+        changes.push(InstructionChange::Edited {
+            step: j + 1,
+            old: old[i].clone(),
+            new: new[j].clone(),
+        });
+    }
+    for &i in &removed[edited..] {
+        changes.push(InstructionChange::Removed {
+            step: i + 1,
+            text: old[i].clone(),
+        });
+    }
+    for &j in &added[edited..] {
+        changes.push(InstructionChange::Added {
+            step: j + 1,
+            text: new[j].clone(),
+        });
+    }
+    removed.clear();
+    added.clear();
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EditError {
+    #[error("recipe not found")]
+    NotFound,
+    #[error("only the owner can edit a recipe")]
+    NotOwner,
+    #[error("a recipe needs a title")]
+    EmptyTitle,
+    #[error("you already have a recipe with this title")]
+    DuplicateTitle,
+    #[error("a recipe must serve at least one")]
+    InvalidServings,
+    #[error("an ingredient needs a name, a unit and a positive quantity")]
+    InvalidIngredient,
+    #[error("sub-recipe not found")]
+    SubRecipeNotFound,
+    #[error("a recipe can't use itself, not even through other recipes")]
+    Cycle,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for EditError {
+    fn from(err: RecipeError) -> Self {
+        match err {
+            RecipeError::NotFound => Self::NotFound,
+            RecipeError::DuplicateTitle => Self::DuplicateTitle,
+            _ => Self::Internal,
+        }
+    }
+}
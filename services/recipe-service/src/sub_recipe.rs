@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use crate::{Ingredient, RecipeError, RecipeService};
+
+impl RecipeService {
+    /// The ingredients of a recipe that is visible to `viewer`, with its sub-recipes replaced by
+    /// their ingredients, recursively. The quantities of the ingredients of a sub-recipe are
+    /// scaled to the servings of it that the recipe uses.
+    pub async fn expanded_ingredients(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<Ingredient>, RecipeError> {
+        if !self.repo.is_visible(recipe_id, viewer).await? {
+            return Err(RecipeError::NotFound);
+        }
+        let mut expanded = Vec::new();
+        for (id, scale) in self.recipe_tree(recipe_id, viewer).await? {
+            for ingredient in self.repo.ingredients(id, viewer).await? {
+                if ingredient.sub_recipe_id.is_none() {
+                    expanded.push(Ingredient {
+                        quantity: ingredient.quantity * scale,
+                        ..ingredient
+                    });
+                }
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// A recipe and all the sub-recipes it uses that are visible to `viewer`, recursively, each
+    /// with how many times its ingredients are needed for the recipe. A sub-recipe that is used
+    /// more than once, directly or through other sub-recipes, is listed once with its uses added
+    /// up, so that recipes sharing sub-recipes aren't expanded over and over.
+    pub(crate) async fn recipe_tree(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<(i64, f64)>, RecipeError> {
+        // Which sub-recipes each recipe uses, and how many times.
+        let mut uses: HashMap<i64, Vec<(i64, f64)>> = HashMap::new();
+        let mut pending = vec![recipe_id];
+        while let Some(id) = pending.pop() {
+            if uses.contains_key(&id) {
+                continue;
+            }
+            let sub_recipes: Vec<_> = self
+                .repo
+                .sub_recipes(id, viewer)
+                .await?
+                .into_iter()
+                .map(|sub_recipe| {
+                    let scale = sub_recipe.quantity / f64::from(sub_recipe.servings);
+                    (sub_recipe.recipe_id, scale)
+                })
+                .collect();
+            pending.extend(sub_recipes.iter().map(|&(sub_recipe_id, _)| sub_recipe_id));
+            uses.insert(id, sub_recipes);
+        }
+
+        // How many times a sub-recipe is needed is only known once every recipe that uses it was
+        // handled, so they are handled in topological order.
+        let mut unhandled_users: HashMap<i64, usize> = HashMap::new();
+        for &(sub_recipe_id, _) in uses.values().flatten() {
+            *unhandled_users.entry(sub_recipe_id).or_default() += 1;
+        }
+        let mut scales = HashMap::from([(recipe_id, 1.0)]);
+        let mut ready = vec![recipe_id];
+        let mut tree = Vec::with_capacity(uses.len());
+        while let Some(id) = ready.pop() {
+            let scale = scales.get(&id).copied().unwrap_or_default();
+            tree.push((id, scale));
+            for &(sub_recipe_id, sub_scale) in uses.get(&id).into_iter().flatten() {
+                *scales.entry(sub_recipe_id).or_default() += scale * sub_scale;
+                if let Some(users) = unhandled_users.get_mut(&sub_recipe_id) {
+                    *users -= 1;
+                    if *users == 0 {
+                        ready.push(sub_recipe_id);
+                    }
+                }
+            }
+        }
+        // Cycles are refused when a recipe is edited, recipes that were never or twice ready mean
+        // one slipped through anyway.
+        if tree.len() != uses.len() {
+            return Err(RecipeError::ConsistencyError);
+        }
+        Ok(tree)
+    }
+}
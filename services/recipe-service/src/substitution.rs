@@ -0,0 +1,82 @@
+use crate::{RecipeError, RecipeService};
+
+/// Something to use when an ingredient is missing, `ratio` of the replacement for every one of
+/// the ingredient.
+#[derive(Clone, Debug)]
+pub struct Substitution {
+    pub id: i64,
+    pub ingredient: String,
+    pub replacement: String,
+    pub ratio: f64,
+    pub note: String,
+}
+
+impl RecipeService {
+    /// All the known substitutions, by ingredient.
+    pub async fn substitutions(&self) -> Result<Vec<Substitution>, RecipeError> {
+        self.repo.substitutions(None).await
+    }
+
+    /// The substitutions for the ingredients of a recipe that is visible to `viewer`.
+    pub async fn recipe_substitutions(
+        &self,
+        recipe_id: i64,
+        viewer: Option<i64>,
+    ) -> Result<Vec<Substitution>, RecipeError> {
+        if !self.repo.is_visible(recipe_id, viewer).await? {
+            return Err(RecipeError::NotFound);
+        }
+        self.repo.substitutions(Some(recipe_id)).await
+    }
+
+    /// Add a substitution, or update the ratio and note of an existing one. Ingredients are
+    /// matched on their name, ignoring case.
+    pub async fn set_substitution(
+        &self,
+        ingredient: &str,
+        replacement: &str,
+        ratio: f64,
+        note: &str,
+    ) -> Result<i64, SubstitutionError> {
+        let ingredient = ingredient.trim();
+        let replacement = replacement.trim();
+        if ingredient.is_empty() || replacement.is_empty() {
+            return Err(SubstitutionError::EmptyIngredient);
+        }
+        if !ratio.is_finite() || ratio <= 0.0 {
+            return Err(SubstitutionError::InvalidRatio);
+        }
+        let ingredient_id = self.repo.ingredient_id(ingredient).await?;
+        let replacement_id = self.repo.ingredient_id(replacement).await?;
+        if ingredient_id == replacement_id {
+            return Err(SubstitutionError::SameIngredient);
+        }
+        let id = self
+            .repo
+            .set_substitution(ingredient_id, replacement_id, ratio, note.trim())
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn remove_substitution(&self, substitution_id: i64) -> Result<(), RecipeError> {
+        self.repo.remove_substitution(substitution_id).await
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SubstitutionError {
+    #[error("an ingredient and its replacement are required")]
+    EmptyIngredient,
+    #[error("an ingredient can't replace itself")]
+    SameIngredient,
+    #[error("the ratio must be positive")]
+    InvalidRatio,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<RecipeError> for SubstitutionError {
+    fn from(_: RecipeError) -> Self {
+        Self::Internal
+    }
+}
@@ -0,0 +1,115 @@
+//! Conversion between the units that ingredients are measured in.
+//!
+//! Every known unit belongs to a [`Dimension`] and is converted to the base unit of that
+//! dimension: grams, millilitres or pieces. Quantities in units that aren't known, like a pinch,
+//! can't be converted and are only comparable to quantities in the exact same unit.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    /// Measured in grams.
+    Mass,
+    /// Measured in millilitres.
+    Volume,
+    /// Measured in pieces.
+    Count,
+}
+
+/// A quantity in the base unit of its dimension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Amount {
+    pub value: f64,
+    pub dimension: Dimension,
+}
+
+/// The known units with the number of base units they are worth.
+const UNITS: &[(&str, Dimension, f64)] = &[
+    ("mg", Dimension::Mass, 0.001),
+    ("g", Dimension::Mass, 1.0),
+    ("gram", Dimension::Mass, 1.0),
+    ("grams", Dimension::Mass, 1.0),
+    ("hg", Dimension::Mass, 100.0),
+    ("kg", Dimension::Mass, 1000.0),
+    ("oz", Dimension::Mass, 28.349_523),
+    ("lb", Dimension::Mass, 453.592_37),
+    ("ml", Dimension::Volume, 1.0),
+    ("cl", Dimension::Volume, 10.0),
+    ("dl", Dimension::Volume, 100.0),
+    ("l", Dimension::Volume, 1000.0),
+    ("krm", Dimension::Volume, 1.0),
+    ("tsp", Dimension::Volume, 5.0),
+    ("tbsp", Dimension::Volume, 15.0),
+    ("cup", Dimension::Volume, 240.0),
+    ("cups", Dimension::Volume, 240.0),
+    ("piece", Dimension::Count, 1.0),
+    ("pieces", Dimension::Count, 1.0),
+    ("pc", Dimension::Count, 1.0),
+    ("pcs", Dimension::Count, 1.0),
+];
+
+fn lookup(unit: &str) -> Option<(Dimension, f64)> {
+    let unit = unit.trim().to_lowercase();
+    UNITS
+        .iter()
+        .find(|(name, _, _)| *name == unit)
+        .map(|(_, dimension, factor)| (*dimension, *factor))
+}
+
+/// Convert a quantity to the base unit of its dimension, `None` if the unit isn't known.
+#[must_use]
+pub fn to_base(quantity: f64, unit: &str) -> Option<Amount> {
+    let (dimension, factor) = lookup(unit)?;
+    Some(Amount {
+        value: quantity * factor,
+        dimension,
+    })
+}
+
+/// Convert a quantity from one unit to another, `None` if either unit isn't known or the units
+/// measure different dimensions.
+#[must_use]
+pub fn convert(quantity: f64, from: &str, to: &str) -> Option<f64> {
+    let amount = to_base(quantity, from)?;
+    let (dimension, factor) = lookup(to)?;
+    (dimension == amount.dimension).then(|| amount.value / factor)
+}
+
+/// Express an amount in the unit that reads best, e.g. 1500 g as 1.5 kg.
+#[must_use]
+pub fn humanize(amount: Amount) -> (f64, &'static str) {
+    let value = amount.value;
+    match amount.dimension {
+        Dimension::Mass if value >= 1000.0 => (value / 1000.0, "kg"),
+        Dimension::Mass => (value, "g"),
+        Dimension::Volume if value >= 1000.0 => (value / 1000.0, "l"),
+        Dimension::Volume if value >= 100.0 => (value / 100.0, "dl"),
+        Dimension::Volume => (value, "ml"),
+        Dimension::Count => (value, "piece"),
+    }
+}
+
+/// What a millilitre or a piece of a particular ingredient weighs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Weights {
+    pub grams_per_ml: Option<f64>,
+    pub grams_per_piece: Option<f64>,
+}
+
+/// Convert a quantity of an ingredient to grams, `None` if the unit isn't known or the weight of
+/// a millilitre or piece of the ingredient is needed but unknown.
+#[must_use]
+pub fn to_grams(quantity: f64, unit: &str, weights: Weights) -> Option<f64> {
+    let amount = to_base(quantity, unit)?;
+    match amount.dimension {
+        Dimension::Mass => Some(amount.value),
+        Dimension::Volume => Some(amount.value * weights.grams_per_ml?),
+        Dimension::Count => Some(amount.value * weights.grams_per_piece?),
+    }
+}
+
+/// Convert a quantity of an ingredient from one unit to another, going through its weight when
+/// the units measure different dimensions, e.g. from decilitres of flour to kilograms.
+#[must_use]
+pub fn convert_ingredient(quantity: f64, from: &str, to: &str, weights: Weights) -> Option<f64> {
+    convert(quantity, from, to)
+        .or_else(|| Some(to_grams(quantity, from, weights)? / to_grams(1.0, to, weights)?))
+}
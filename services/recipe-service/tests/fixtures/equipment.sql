@@ -0,0 +1,34 @@
+INSERT INTO user
+(username, email, password_hash)
+VALUES
+('baker', 'baker@example.com', 'hash'),
+('student', 'student@example.com', 'hash');
+
+INSERT INTO source
+(name)
+VALUES
+('Cookbook');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Dinner');
+
+INSERT INTO recipe
+(title, description, meal_type_id, source_id, servings, owner_id, visibility)
+VALUES
+('Bread', 'Kneaded by machine.', 1, 1, 4, 1, 'public'),
+('Stew', 'Low and slow.', 1, 1, 4, 1, 'public'),
+('Salad', 'Just a bowl.', 1, 1, 2, 1, 'public');
+
+INSERT INTO equipment
+(name)
+VALUES
+('Stand mixer'),
+('Dutch oven');
+
+INSERT INTO recipe_equipment
+(recipe_id, equipment_id)
+VALUES
+(1, 1),
+(2, 2);
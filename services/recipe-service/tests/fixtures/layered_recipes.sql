@@ -0,0 +1,54 @@
+INSERT INTO user
+(username, email, password_hash)
+VALUES
+('cook', 'cook@example.com', 'hash');
+
+INSERT INTO source
+(name)
+VALUES
+('Cookbook');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Dessert');
+
+INSERT INTO ingredient
+(name)
+VALUES
+('flour');
+
+INSERT INTO measurement
+(unit)
+VALUES
+('g');
+
+-- Layer 0 uses layer 1 twice, which uses layer 2 twice and so on, down to layer 24 which is a
+-- gram of flour.
+INSERT INTO recipe
+(id, title, description, meal_type_id, source_id, servings, owner_id, visibility)
+WITH RECURSIVE layer (n) AS (
+    SELECT 0
+    UNION ALL
+    SELECT n + 1 FROM layer WHERE n < 24
+)
+SELECT n + 1, 'Layer ' || n, 'Layered.', 1, 1, 1, 1, 'public'
+FROM layer;
+
+INSERT INTO recipe_ingredient
+(quantity, recipe_id, ingredient_id, measurement_id, sub_recipe_id)
+WITH RECURSIVE layer (n) AS (
+    SELECT 0
+    UNION ALL
+    SELECT n + 1 FROM layer WHERE n < 23
+)
+SELECT 1, n + 1, NULL, NULL, n + 2
+FROM layer
+UNION ALL
+SELECT 1, n + 1, NULL, NULL, n + 2
+FROM layer;
+
+INSERT INTO recipe_ingredient
+(quantity, recipe_id, ingredient_id, measurement_id)
+VALUES
+(1, 25, 1, 1);
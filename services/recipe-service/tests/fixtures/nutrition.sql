@@ -0,0 +1,40 @@
+INSERT INTO source
+(name)
+VALUES
+('Cookbook');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Breakfast');
+
+INSERT INTO ingredient
+(name)
+VALUES
+('Milk'),
+('egg'),
+('flour'),
+('salt'),
+('secret spice');
+
+INSERT INTO measurement
+(unit)
+VALUES
+('dl'),
+('piece'),
+('g'),
+('pinch');
+
+INSERT INTO recipe
+(title, description, meal_type_id, source_id, servings)
+VALUES
+('Pancakes', 'Fluffy.', 1, 1, 4);
+
+INSERT INTO recipe_ingredient
+(quantity, recipe_id, ingredient_id, measurement_id)
+VALUES
+(3, 1, 1, 1),
+(2, 1, 2, 2),
+(200, 1, 3, 3),
+(1, 1, 4, 4),
+(5, 1, 5, 3);
@@ -0,0 +1,51 @@
+INSERT INTO user
+(username, email, password_hash)
+VALUES
+('cook', 'cook@example.com', 'hash'),
+('other', 'other@example.com', 'hash');
+
+INSERT INTO source
+(name)
+VALUES
+('Cookbook');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Lunch');
+
+INSERT INTO ingredient
+(name)
+VALUES
+('egg'),
+('milk'),
+('flour'),
+('lettuce'),
+('tomato');
+
+INSERT INTO measurement
+(unit)
+VALUES
+('piece'),
+('dl'),
+('g');
+
+INSERT INTO recipe
+(title, description, meal_type_id, source_id, owner_id, visibility)
+VALUES
+('Pancakes', 'Fluffy.', 1, 1, NULL, 'public'),
+('Omelette', 'Quick.', 1, 1, NULL, 'public'),
+('Salad', 'Fresh.', 1, 1, NULL, 'public'),
+('Secret eggs', 'Hush.', 1, 1, 2, 'private');
+
+INSERT INTO recipe_ingredient
+(quantity, recipe_id, ingredient_id, measurement_id)
+VALUES
+(2, 1, 1, 1),
+(3, 1, 2, 2),
+(200, 1, 3, 3),
+(3, 2, 1, 1),
+(1, 2, 2, 2),
+(1, 3, 4, 1),
+(2, 3, 5, 1),
+(4, 4, 1, 1);
@@ -0,0 +1,56 @@
+INSERT INTO source
+(name)
+VALUES
+('Cookbook');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Breakfast');
+
+INSERT INTO ingredient
+(name)
+VALUES
+('milk'),
+('Egg'),
+('flour'),
+('sugar'),
+('secret spice');
+
+INSERT INTO measurement
+(unit)
+VALUES
+('dl'),
+('piece'),
+('g');
+
+INSERT INTO recipe
+(title, description, meal_type_id, source_id, servings)
+VALUES
+('Pancakes', 'Fluffy.', 1, 1, 4);
+
+INSERT INTO recipe_ingredient
+(quantity, recipe_id, ingredient_id, measurement_id)
+VALUES
+(3, 1, 1, 1),
+(2, 1, 2, 2),
+(200, 1, 3, 3),
+(1, 1, 4, 1),
+(5, 1, 5, 3);
+
+INSERT INTO store
+(name)
+VALUES
+('Corner shop'),
+('Market');
+
+INSERT INTO ingredient_price
+(ingredient, store_id, price, unit, effective_from)
+VALUES
+('milk', 1, 12.0, 'l', '2020-01-01'),
+('milk', 1, 14.0, 'l', '2024-01-01'),
+('milk', 1, 1.0, 'l', '2999-01-01'),
+('milk', 2, 13.0, 'l', '2020-01-01'),
+('egg', 1, 3.0, 'piece', '2020-01-01'),
+('flour', 2, 20.0, 'kg', '2020-01-01'),
+('sugar', 2, 10.0, 'kg', '2020-01-01');
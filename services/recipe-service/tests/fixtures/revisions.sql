@@ -0,0 +1,52 @@
+INSERT INTO user
+(username, email, password_hash)
+VALUES
+('cook', 'cook@example.com', 'hash'),
+('stranger', 'stranger@example.com', 'hash');
+
+INSERT INTO source
+(name)
+VALUES
+('Cookbook');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Breakfast');
+
+INSERT INTO ingredient
+(name)
+VALUES
+('milk'),
+('egg'),
+('flour'),
+('salt');
+
+INSERT INTO measurement
+(unit)
+VALUES
+('dl'),
+('piece'),
+('g'),
+('pinch');
+
+INSERT INTO recipe
+(title, description, meal_type_id, source_id, servings, creation_date, owner_id, visibility)
+VALUES
+('Pancakes', 'Fluffy.', 1, 1, 4, '2025-01-01', 1, 'public'),
+('Waffles', 'Crispy.', 1, 1, 2, '2025-01-01', 1, 'public');
+
+INSERT INTO recipe_ingredient
+(quantity, recipe_id, ingredient_id, measurement_id)
+VALUES
+(3, 1, 1, 1),
+(2, 1, 2, 2),
+(200, 1, 3, 3),
+(1, 1, 4, 4);
+
+INSERT INTO instruction
+(step_number, description, recipe_id)
+VALUES
+(1, 'Whisk everything.', 1),
+(2, 'Let it rest.', 1),
+(3, 'Fry.', 1);
@@ -0,0 +1,47 @@
+INSERT INTO user
+(username, email, password_hash)
+VALUES
+('cook', 'cook@example.com', 'hash'),
+('stranger', 'stranger@example.com', 'hash');
+
+INSERT INTO source
+(name)
+VALUES
+('Cookbook');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Dinner');
+
+INSERT INTO ingredient
+(name)
+VALUES
+('flour'),
+('water'),
+('yeast'),
+('tomato sauce'),
+('cheese');
+
+INSERT INTO measurement
+(unit)
+VALUES
+('g'),
+('dl');
+
+INSERT INTO recipe
+(title, description, meal_type_id, source_id, servings, owner_id, visibility)
+VALUES
+('Pizza dough', 'Makes two bases.', 1, 1, 2, 1, 'public'),
+('Pizza', 'Margherita.', 1, 1, 2, 1, 'public'),
+('Secret dough', 'Hush.', 1, 1, 1, 2, 'private');
+
+INSERT INTO recipe_ingredient
+(quantity, recipe_id, ingredient_id, measurement_id, sub_recipe_id)
+VALUES
+(500, 1, 1, 1, NULL),
+(3, 1, 2, 2, NULL),
+(10, 1, 3, 1, NULL),
+(1, 2, NULL, NULL, 1),
+(2, 2, 4, 2, NULL),
+(150, 2, 5, 1, NULL);
@@ -0,0 +1,34 @@
+INSERT INTO user
+(username, email, password_hash)
+VALUES
+('owner', 'owner@example.com', 'hash'),
+('family', 'family@example.com', 'hash'),
+('stranger', 'stranger@example.com', 'hash');
+
+INSERT INTO household
+(name, invite_code)
+VALUES
+('The family', 'family-code');
+
+INSERT INTO household_member
+(household_id, user_id)
+VALUES
+(1, 1),
+(1, 2);
+
+INSERT INTO source
+(name)
+VALUES
+('Family archives');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Dessert');
+
+INSERT INTO recipe
+(title, description, meal_type_id, source_id, owner_id, household_id, visibility)
+VALUES
+('Public pie', 'For everyone.', 1, 1, 1, NULL, 'public'),
+('Family pie', 'For the family.', 1, 1, 1, 1, 'household'),
+('Secret pie', 'For me.', 1, 1, 1, NULL, 'private');
@@ -1,4 +1,10 @@
-use recipe_service::RecipeService;
+use chrono::NaiveDate;
+use recipe_service::{
+    CollectionError, CollectionUpdate, CookingLogError, EditError, EquipmentError, FieldChange,
+    HouseholdError, Ingredient, IngredientChange, InstructionChange, ListingQuery, ListingSort,
+    PantryError, PriceError, RecipeContent, RecipeError, RecipeField, RecipeFormat, RecipeService,
+    ReviewError, SetVisibilityError, SubstitutionError, Visibility,
+};
 use sqlx::migrate::Migrator;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 
@@ -20,7 +26,10 @@ async fn setup_service(
 async fn test_glass_of_water(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
     let service = setup_service(pool_options, options).await;
 
-    let all_titles = service.recipe_listing().await.unwrap();
+    let all_titles = service
+        .recipe_listing(None, &ListingQuery::default())
+        .await
+        .unwrap();
     let id = all_titles
         .iter()
         .find_map(|recipe_listing| {
@@ -32,7 +41,7 @@ async fn test_glass_of_water(pool_options: SqlitePoolOptions, options: SqliteCon
         })
         .unwrap();
 
-    let r = service.recipe(id).await.unwrap();
+    let r = service.recipe(id, None).await.unwrap();
 
     assert_eq!(r.title, "Glass of water");
     assert_eq!(r.description, "Refreshing, isn't it?");
@@ -53,7 +62,1097 @@ async fn test_glass_of_water(pool_options: SqlitePoolOptions, options: SqliteCon
     );
 
     let non_existing_id = 5;
-    let r = service.recipe(non_existing_id).await;
-    assert!(matches!(r, Err(recipe_service::RecipeError::NotFound)));
+    let r = service.recipe(non_existing_id, None).await;
+    assert!(matches!(r, Err(RecipeError::NotFound)));
+}
+
+const OWNER: i64 = 1;
+const FAMILY: i64 = 2;
+const STRANGER: i64 = 3;
+
+async fn visible_titles(service: &RecipeService, viewer: Option<i64>) -> Vec<String> {
+    let mut titles: Vec<_> = service
+        .recipe_listing(viewer, &ListingQuery::default())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|recipe_listing| recipe_listing.title)
+        .collect();
+    titles.sort();
+    titles
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_visibility_listing(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    let service = setup_service(pool_options, options).await;
+
+    assert_eq!(visible_titles(&service, None).await, ["Public pie"]);
+    assert_eq!(
+        visible_titles(&service, Some(STRANGER)).await,
+        ["Public pie"]
+    );
+    assert_eq!(
+        visible_titles(&service, Some(FAMILY)).await,
+        ["Family pie", "Public pie"]
+    );
+    assert_eq!(
+        visible_titles(&service, Some(OWNER)).await,
+        ["Family pie", "Public pie", "Secret pie"]
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_visibility_recipe(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    let service = setup_service(pool_options, options).await;
+    let (public, family, secret) = (1, 2, 3);
+
+    let r = service.recipe(public, None).await.unwrap();
+    assert_eq!(r.visibility, Visibility::Public);
+    assert_eq!(r.owner_id, Some(OWNER));
+
+    // Recipes that are not visible look like they do not exist.
+    let r = service.recipe(family, None).await;
+    assert!(matches!(r, Err(RecipeError::NotFound)));
+    let r = service.recipe(family, Some(STRANGER)).await;
+    assert!(matches!(r, Err(RecipeError::NotFound)));
+    let r = service.recipe(family, Some(FAMILY)).await.unwrap();
+    assert_eq!(r.visibility, Visibility::Household);
+
+    let r = service.recipe(secret, Some(FAMILY)).await;
+    assert!(matches!(r, Err(RecipeError::NotFound)));
+    let r = service.recipe(secret, Some(OWNER)).await.unwrap();
+    assert_eq!(r.visibility, Visibility::Private);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_set_visibility(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let (secret, household) = (3, 1);
+
+    // WHEN someone else than the owner changes the visibility, THEN it's an error
+    let result = service
+        .set_visibility(secret, FAMILY, Visibility::Public, None)
+        .await;
+    assert!(matches!(result, Err(SetVisibilityError::NotOwner)));
+
+    // WHEN sharing with a household without saying which, THEN it's an error
+    let result = service
+        .set_visibility(secret, OWNER, Visibility::Household, None)
+        .await;
+    assert!(matches!(result, Err(SetVisibilityError::MissingHousehold)));
+
+    // WHEN the owner shares the recipe with their household
+    service
+        .set_visibility(secret, OWNER, Visibility::Household, Some(household))
+        .await
+        .unwrap();
+    // THEN the household can see it, but no one else
+    service.recipe(secret, Some(FAMILY)).await.unwrap();
+    let r = service.recipe(secret, Some(STRANGER)).await;
+    assert!(matches!(r, Err(RecipeError::NotFound)));
+
+    // WHEN the owner makes the recipe public, THEN anyone can see it
+    service
+        .set_visibility(secret, OWNER, Visibility::Public, Some(household))
+        .await
+        .unwrap();
+    service.recipe(secret, None).await.unwrap();
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_households(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let family_pie = 2;
+
+    // WHEN creating a household without a name, THEN it's an error
+    let result = service.create_household(STRANGER, " ").await;
+    assert!(matches!(result, Err(HouseholdError::EmptyName)));
+
+    // WHEN joining with a code that doesn't exist, THEN it's an error
+    let result = service.join_household(STRANGER, "no-such-code").await;
+    assert!(matches!(result, Err(HouseholdError::InvalidInviteCode)));
+
+    // WHEN the stranger joins the family with its invite code
+    let household = service
+        .join_household(STRANGER, "family-code")
+        .await
+        .unwrap();
+    // THEN they are a member and see what's shared with the family
+    assert_eq!(household.name, "The family");
+    assert_eq!(household.members, ["family", "owner", "stranger"]);
+    service.recipe(family_pie, Some(STRANGER)).await.unwrap();
+
+    // WHEN the owner leaves the family
+    service.leave_household(OWNER, household.id).await.unwrap();
+    // THEN what they shared with it is private again
+    let r = service.recipe(family_pie, Some(OWNER)).await.unwrap();
+    assert_eq!(r.visibility, Visibility::Private);
+    let r = service.recipe(family_pie, Some(STRANGER)).await;
+    assert!(matches!(r, Err(RecipeError::NotFound)));
+    let result = service.leave_household(OWNER, household.id).await;
+    assert!(matches!(result, Err(HouseholdError::NotMember)));
+
+    // WHEN the owner creates a household of their own
+    let own = service.create_household(OWNER, "Just me").await.unwrap();
+    // THEN they are its only member, with a code to invite others
+    assert_eq!(own.members, ["owner"]);
+    assert_eq!(own.invite_code.len(), 12);
+    let names: Vec<_> = service
+        .households(OWNER)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|household| household.name)
+        .collect();
+    assert_eq!(names, ["Just me"]);
+
+    // WHEN the last member leaves, THEN the household is gone
+    service.leave_household(OWNER, own.id).await.unwrap();
+    let result = service.join_household(FAMILY, &own.invite_code).await;
+    assert!(matches!(result, Err(HouseholdError::InvalidInviteCode)));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_set_owner(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let (public, family) = (1, 2);
+
+    // WHEN a shared recipe is handed to someone else
+    service.set_owner(family, STRANGER).await.unwrap();
+    // THEN only the new owner sees it, it isn't shared with a household they aren't in
+    let r = service.recipe(family, Some(STRANGER)).await.unwrap();
+    assert_eq!(r.owner_id, Some(STRANGER));
+    assert_eq!(r.visibility, Visibility::Private);
+    let r = service.recipe(family, Some(FAMILY)).await;
+    assert!(matches!(r, Err(RecipeError::NotFound)));
+
+    // WHEN a public recipe is handed to someone else, THEN it stays public
+    service.set_owner(public, STRANGER).await.unwrap();
+    let r = service.recipe(public, None).await.unwrap();
+    assert_eq!(r.owner_id, Some(STRANGER));
+
+    // WHEN the recipe doesn't exist, THEN it's an error
+    let result = service.set_owner(100, STRANGER).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_favorites(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let (public, family, secret) = (1, 2, 3);
+    assert!(service.favorites(FAMILY).await.unwrap().is_empty());
+
+    // WHEN adding favorites, also more than once
+    service.add_favorite(FAMILY, public).await.unwrap();
+    service.add_favorite(FAMILY, family).await.unwrap();
+    service.add_favorite(FAMILY, family).await.unwrap();
+
+    // THEN they are listed by title, but only for that user
+    let favorites: Vec<_> = service
+        .favorites(FAMILY)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|recipe_listing| recipe_listing.id)
+        .collect();
+    assert_eq!(favorites, [family, public]);
+    assert!(service.is_favorite(FAMILY, family).await.unwrap());
+    assert!(!service.is_favorite(OWNER, family).await.unwrap());
+    assert!(service.favorites(OWNER).await.unwrap().is_empty());
+
+    // WHEN adding a recipe that is not visible to the user, THEN it does not exist
+    let result = service.add_favorite(FAMILY, secret).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+
+    // WHEN removing a favorite, THEN it's no longer listed
+    service.remove_favorite(FAMILY, public).await.unwrap();
+    assert!(!service.is_favorite(FAMILY, public).await.unwrap());
+    assert_eq!(service.favorites(FAMILY).await.unwrap().len(), 1);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_reviews(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let (public, secret) = (1, 3);
+
+    // WHEN rating outside of 1 to 5, THEN it's an error
+    let result = service.set_review(FAMILY, public, 0, None).await;
+    assert!(matches!(result, Err(ReviewError::InvalidRating)));
+    let result = service.set_review(FAMILY, public, 6, None).await;
+    assert!(matches!(result, Err(ReviewError::InvalidRating)));
+
+    // WHEN reviewing a recipe that is not visible, THEN it does not exist
+    let result = service.set_review(FAMILY, secret, 5, None).await;
+    assert!(matches!(result, Err(ReviewError::NotFound)));
+
+    // WHEN two users review a recipe, and one of them changes their mind
+    service
+        .set_review(FAMILY, public, 2, Some("Too sweet."))
+        .await
+        .unwrap();
+    service.set_review(STRANGER, public, 3, None).await.unwrap();
+    service
+        .set_review(FAMILY, public, 4, Some("  Better with less sugar.  "))
+        .await
+        .unwrap();
+
+    // THEN each user has one review
+    let reviews = service.reviews(public, Some(FAMILY)).await.unwrap();
+    assert_eq!(reviews.len(), 2);
+    let own = reviews.iter().find(|review| review.is_own).unwrap();
+    assert_eq!(own.username, "family");
+    assert_eq!(own.rating, 4);
+    assert_eq!(own.text.as_deref(), Some("Better with less sugar."));
+    let other = reviews.iter().find(|review| !review.is_own).unwrap();
+    assert_eq!(other.username, "stranger");
+    assert_eq!(other.text, None);
+
+    // WHEN deleting a review, THEN only the other one is left
+    service.delete_review(FAMILY, public).await.unwrap();
+    let reviews = service.reviews(public, None).await.unwrap();
+    assert_eq!(reviews.len(), 1);
+    assert!(!reviews[0].is_own);
+    let result = service.delete_review(FAMILY, public).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_listing_sorted_by_rating(
+    pool_options: SqlitePoolOptions,
+    options: SqliteConnectOptions,
+) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let (public, family) = (1, 2);
+    service.set_review(OWNER, public, 5, None).await.unwrap();
+    service.set_review(FAMILY, public, 4, None).await.unwrap();
+    service.set_review(FAMILY, family, 3, None).await.unwrap();
+
+    // WHEN listing by rating
+    let query = ListingQuery {
+        sort: ListingSort::Rating,
+        ..ListingQuery::default()
+    };
+    let listing = service.recipe_listing(Some(OWNER), &query).await.unwrap();
+
+    // THEN the best rated comes first and unrated recipes last
+    let titles: Vec<_> = listing.iter().map(|r| r.title.as_str()).collect();
+    assert_eq!(titles, ["Public pie", "Family pie", "Secret pie"]);
+    assert_eq!(listing[0].average_rating, Some(4.5));
+    assert_eq!(listing[0].rating_count, 2);
+    assert_eq!(listing[1].average_rating, Some(3.0));
+    assert_eq!(listing[2].average_rating, None);
+    assert_eq!(listing[2].rating_count, 0);
+}
+
+fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_cooking_log(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let (public, secret) = (1, 3);
+
+    // WHEN cooking zero servings, THEN it's an error
+    let result = service
+        .log_cooking(FAMILY, public, date(3, 1), 0, None)
+        .await;
+    assert!(matches!(result, Err(CookingLogError::InvalidServings)));
+
+    // WHEN logging a recipe that is not visible, THEN it does not exist
+    let result = service
+        .log_cooking(FAMILY, secret, date(3, 1), 4, None)
+        .await;
+    assert!(matches!(result, Err(CookingLogError::NotFound)));
+
+    // WHEN two users cook the same recipe
+    service
+        .log_cooking(FAMILY, public, date(3, 1), 4, Some("Used half the sugar."))
+        .await
+        .unwrap();
+    let entry_id = service
+        .log_cooking(STRANGER, public, date(3, 8), 2, Some("   "))
+        .await
+        .unwrap();
+
+    // THEN the log is listed with the most recent first
+    let log = service.cooking_log(public, Some(FAMILY)).await.unwrap();
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].username, "stranger");
+    assert_eq!(log[0].cooked_on, date(3, 8));
+    assert_eq!(log[0].notes, None);
+    assert!(!log[0].is_own);
+    assert_eq!(log[1].servings, 4);
+    assert_eq!(log[1].notes.as_deref(), Some("Used half the sugar."));
+    assert!(log[1].is_own);
+
+    // WHEN deleting someone else's entry, THEN it's not found
+    let result = service.delete_cooking_log_entry(FAMILY, entry_id).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+
+    // WHEN deleting an own entry, THEN it's gone
+    service
+        .delete_cooking_log_entry(STRANGER, entry_id)
+        .await
+        .unwrap();
+    assert_eq!(service.cooking_log(public, None).await.unwrap().len(), 1);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_recently_cooked(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let (public, family, secret) = (1, 2, 3);
+    for (recipe_id, cooked_on) in [
+        (public, date(1, 1)),
+        (family, date(2, 1)),
+        (secret, date(3, 1)),
+        (public, date(4, 1)),
+    ] {
+        service
+            .log_cooking(OWNER, recipe_id, cooked_on, 2, None)
+            .await
+            .unwrap();
+    }
+    service
+        .log_cooking(FAMILY, family, date(5, 1), 2, None)
+        .await
+        .unwrap();
+
+    // WHEN listing recently cooked, THEN it's ordered by the last time it was cooked by the user
+    let recent = service.recently_cooked(OWNER, 10).await.unwrap();
+    let recent: Vec<_> = recent.iter().map(|r| (r.id, r.last_cooked)).collect();
+    assert_eq!(
+        recent,
+        [
+            (public, date(4, 1)),
+            (secret, date(3, 1)),
+            (family, date(2, 1))
+        ]
+    );
+
+    // WHEN listing recipes not cooked in a while, THEN the oldest comes first
+    let not_lately = service.not_cooked_lately(OWNER, 2).await.unwrap();
+    let not_lately: Vec<_> = not_lately.iter().map(|r| r.id).collect();
+    assert_eq!(not_lately, [family, secret]);
+
+    // WHEN another user lists what they cooked, THEN it's only their own log
+    let recent = service.recently_cooked(FAMILY, 10).await.unwrap();
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].last_cooked, date(5, 1));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("visibility"))]
+async fn test_collections(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let (public, family, secret) = (1, 2, 3);
+
+    // WHEN creating a collection without a name, THEN it's an error
+    let result = service.create_collection(OWNER, "  ", "").await;
+    assert!(matches!(result, Err(CollectionError::EmptyName)));
+
+    // WHEN creating a collection with recipes
+    let id = service
+        .create_collection(OWNER, "Pies", "All the pies.")
+        .await
+        .unwrap();
+    for recipe_id in [secret, public, family, public] {
+        service
+            .add_to_collection(OWNER, id, recipe_id)
+            .await
+            .unwrap();
+    }
+
+    // THEN the recipes are in the order they were added
+    let collection = service.collection(id, Some(OWNER)).await.unwrap();
+    assert!(collection.is_own);
+    assert_eq!(collection.visibility, Visibility::Private);
+    let order: Vec<_> = collection.recipes.iter().map(|r| r.id).collect();
+    assert_eq!(order, [secret, public, family]);
+
+    // WHEN someone else changes the collection, THEN it's an error
+    let result = service.add_to_collection(FAMILY, id, public).await;
+    assert!(matches!(result, Err(CollectionError::NotOwner)));
+
+    // WHEN reordering with recipes missing, THEN it's an error
+    let result = service
+        .reorder_collection(OWNER, id, &[public, family])
+        .await;
+    assert!(matches!(result, Err(CollectionError::InvalidOrder)));
+
+    // WHEN reordering and removing a recipe
+    service
+        .reorder_collection(OWNER, id, &[family, public, secret])
+        .await
+        .unwrap();
+    service
+        .remove_from_collection(OWNER, id, public)
+        .await
+        .unwrap();
+    let collection = service.collection(id, Some(OWNER)).await.unwrap();
+    let order: Vec<_> = collection.recipes.iter().map(|r| r.id).collect();
+    assert_eq!(order, [family, secret]);
+
+    // WHEN the collection is private, THEN nobody else sees it
+    assert!(service.collections(Some(FAMILY)).await.unwrap().is_empty());
+    let result = service.collection(id, None).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+
+    // WHEN the collection is made public
+    let update = CollectionUpdate {
+        name: "Family pies".to_string(),
+        description: String::new(),
+        visibility: Visibility::Public,
+        household_id: None,
+    };
+    service.update_collection(OWNER, id, &update).await.unwrap();
+
+    // THEN everyone sees it, but only the recipes they are allowed to see
+    let collections = service.collections(None).await.unwrap();
+    assert_eq!(collections.len(), 1);
+    assert_eq!(collections[0].name, "Family pies");
+    assert_eq!(collections[0].owner_username, "owner");
+    assert!(!collections[0].is_own);
+    let collection = service.collection(id, Some(FAMILY)).await.unwrap();
+    let order: Vec<_> = collection.recipes.iter().map(|r| r.id).collect();
+    assert_eq!(order, [family]);
+    assert!(
+        service
+            .collection(id, None)
+            .await
+            .unwrap()
+            .recipes
+            .is_empty()
+    );
+
+    // WHEN deleting the collection, THEN it's gone
+    service.delete_collection(OWNER, id).await.unwrap();
+    assert!(service.collections(Some(OWNER)).await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("glass_of_water"))]
+async fn test_export(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    let service = setup_service(pool_options, options).await;
+    let glass_of_water = 1;
+
+    let markdown = service
+        .export_recipe(glass_of_water, None, RecipeFormat::Markdown)
+        .await
+        .unwrap();
+    assert_eq!(
+        markdown,
+        "# Glass of water
+
+_Drink_
+
+Refreshing, isn't it?
+
+## Ingredients
+
+- 1 piece any drinking glass
+- 2.5 dl water
+
+## Instructions
+
+1. Pour the water into the glass.
+2. Enjoy the nice water.
+
+Source: Cool source
+"
+    );
+
+    let json = service
+        .export_recipe(glass_of_water, None, RecipeFormat::Json)
+        .await
+        .unwrap();
+    assert!(json.contains(r#""title": "Glass of water""#));
+    assert!(json.contains(r#""unit": "dl""#));
 }
 
+#[sqlx::test(migrator = "MIGRATOR", fixtures("pantry"))]
+async fn test_pantry(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let cook = 1;
+    let other = 2;
+    let expired = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+
+    // WHEN stocking the pantry with eggs, milk and an old tomato
+    service
+        .set_pantry_item(cook, "Egg", None, None)
+        .await
+        .unwrap();
+    let milk = service
+        .set_pantry_item(cook, "milk", Some((5.0, "dl")), None)
+        .await
+        .unwrap();
+    service
+        .set_pantry_item(cook, "tomato", None, Some(expired))
+        .await
+        .unwrap();
+
+    // THEN the recipes with the most ingredients at hand come first, without the recipes that
+    // need the expired tomato or are not visible
+    let matches = service.what_can_i_cook(cook).await.unwrap();
+    let matches: Vec<_> = matches
+        .iter()
+        .map(|m| (m.title.as_str(), m.available, m.total, m.missing.clone()))
+        .collect();
+    assert_eq!(
+        matches,
+        [
+            ("Omelette", 2, 2, vec![]),
+            ("Pancakes", 2, 3, vec!["flour".to_string()]),
+        ]
+    );
+
+    // THEN the pantry lists what expires first first, and names match existing ingredients
+    let pantry = service.pantry(cook).await.unwrap();
+    let pantry: Vec<_> = pantry
+        .iter()
+        .map(|item| (item.ingredient.as_str(), item.is_expired))
+        .collect();
+    assert_eq!(pantry, [("tomato", true), ("egg", false), ("milk", false)]);
+
+    // WHEN updating what's left of the milk, THEN it's the same item
+    let updated = service
+        .set_pantry_item(cook, "Milk", Some((2.0, "dl")), None)
+        .await
+        .unwrap();
+    assert_eq!(updated, milk);
+    assert_eq!(service.pantry(cook).await.unwrap().len(), 3);
+
+    // Invalid items are rejected.
+    let result = service.set_pantry_item(cook, " ", None, None).await;
+    assert!(matches!(result, Err(PantryError::EmptyIngredient)));
+    let result = service
+        .set_pantry_item(cook, "milk", Some((0.0, "dl")), None)
+        .await;
+    assert!(matches!(result, Err(PantryError::InvalidQuantity)));
+
+    // WHEN another user removes the milk, THEN it's not found
+    let result = service.remove_pantry_item(other, milk).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+
+    // WHEN removing the milk, THEN the pantry no longer has it
+    service.remove_pantry_item(cook, milk).await.unwrap();
+    assert_eq!(service.pantry(cook).await.unwrap().len(), 2);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("nutrition"))]
+async fn test_nutrition(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    let service = setup_service(pool_options, options).await;
+    let pancakes = 1;
+
+    let nutrition = service.nutrition(pancakes, None).await.unwrap();
+
+    // 3 dl of milk weighs 309 g, 2 eggs weigh 100 g, plus 200 g of flour.
+    let kcal = 61.0 * 3.09 + 143.0 + 364.0 * 2.0;
+    assert!((nutrition.total.kcal - kcal).abs() < 1e-6);
+    assert!((nutrition.per_serving.kcal - kcal / 4.0).abs() < 1e-6);
+    let protein = 3.2 * 3.09 + 12.6 + 10.3 * 2.0;
+    assert!((nutrition.total.protein - protein).abs() < 1e-6);
+    // A pinch can't be weighed, and there are no nutrition facts for the secret spice.
+    assert_eq!(nutrition.unresolved, ["salt", "secret spice"]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("prices"))]
+async fn test_recipe_cost(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    let service = setup_service(pool_options, options).await;
+    let pancakes = 1;
+
+    let cost = service.recipe_cost(pancakes, None).await.unwrap();
+
+    // Milk is cheapest at the market, the corner shop raised its price and its future price
+    // doesn't apply yet. A decilitre of sugar weighs 85 g.
+    let total = 13.0 * 0.3 + 3.0 * 2.0 + 20.0 * 0.2 + 10.0 * 0.085;
+    assert!((cost.total - total).abs() < 1e-6);
+    assert!((cost.per_serving - total / 4.0).abs() < 1e-6);
+    assert_eq!(cost.unpriced, ["secret spice"]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("prices"))]
+async fn test_set_price(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    let service = setup_service(pool_options, options).await;
+    let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+
+    // WHEN the market starts selling the secret spice
+    service
+        .set_price("secret spice", "market", 2.0, "g", date)
+        .await
+        .unwrap();
+
+    // THEN it's priced in the store with the same name
+    let prices = service.current_prices().await.unwrap();
+    let spice = prices
+        .iter()
+        .find(|price| price.ingredient == "secret spice")
+        .unwrap();
+    assert_eq!(spice.store, "Market");
+    let cost = service.recipe_cost(1, None).await.unwrap();
+    assert!(cost.unpriced.is_empty());
+
+    // Invalid prices are rejected.
+    let result = service.set_price("salt", "Market", -1.0, "kg", date).await;
+    assert!(matches!(result, Err(PriceError::InvalidPrice)));
+    let result = service
+        .set_price("salt", "Market", 1.0, "pinch", date)
+        .await;
+    assert!(matches!(result, Err(PriceError::UnknownUnit)));
+    let result = service.set_price("salt", " ", 1.0, "kg", date).await;
+    assert!(matches!(result, Err(PriceError::EmptyStore)));
+}
+
+fn ingredient(quantity: f64, unit: &str, name: &str) -> Ingredient {
+    Ingredient {
+        quantity,
+        unit: unit.to_string(),
+        name: name.to_string(),
+        sub_recipe_id: None,
+    }
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("revisions"))]
+async fn test_revisions(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN pancakes that were never edited
+    let service = setup_service(pool_options, options).await;
+    let (cook, stranger, pancakes) = (1, 2, 1);
+    let original = RecipeContent::from(service.recipe(pancakes, None).await.unwrap());
+    assert!(service.revisions(pancakes, None).await.unwrap().is_empty());
+    let content = RecipeContent {
+        title: " Fluffy pancakes ".to_string(),
+        description: "Fluffy.".to_string(),
+        servings: 6,
+        ingredients: vec![
+            ingredient(4.0, "dl", "milk"),
+            ingredient(2.0, "piece", "egg"),
+            ingredient(1.0, "pinch", "salt"),
+            ingredient(1.0, "tbsp", "Sugar"),
+        ],
+        instructions: vec![
+            "Whisk everything.".to_string(),
+            "Let it rest for an hour.".to_string(),
+            "Fry.".to_string(),
+            "Serve warm.".to_string(),
+            " ".to_string(),
+        ],
+    };
+
+    // WHEN a stranger edits them, THEN that's not allowed
+    let result = service.update_recipe(pancakes, stranger, &content).await;
+    assert!(matches!(result, Err(EditError::NotOwner)));
+
+    // WHEN the owner edits them
+    let number = service
+        .update_recipe(pancakes, cook, &content)
+        .await
+        .unwrap();
+
+    // THEN the original is kept as the first revision and the edit as the second
+    assert_eq!(number, 2);
+    let recipe = service.recipe(pancakes, None).await.unwrap();
+    assert_eq!(recipe.title, "Fluffy pancakes");
+    assert_eq!(recipe.servings, 6);
+    assert_eq!(recipe.ingredients, content.ingredients);
+    assert_eq!(recipe.instructions.len(), 4);
+    let revisions = service.revisions(pancakes, None).await.unwrap();
+    let numbers: Vec<_> = revisions.iter().map(|revision| revision.number).collect();
+    assert_eq!(numbers, [2, 1]);
+    assert_eq!(revisions[1].author.as_deref(), Some("cook"));
+    assert_eq!(
+        revisions[1].created_at.date(),
+        NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+    );
+    let first = service.revision(pancakes, 1, None).await.unwrap();
+    assert_eq!(first.content, original);
+
+    // THEN the diff shows what changed
+    let diff = service.revision_diff(pancakes, 1, 2, None).await.unwrap();
+    assert_eq!(
+        diff.fields,
+        [
+            FieldChange {
+                field: RecipeField::Title,
+                old: "Pancakes".to_string(),
+                new: "Fluffy pancakes".to_string(),
+            },
+            FieldChange {
+                field: RecipeField::Servings,
+                old: "4".to_string(),
+                new: "6".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        diff.ingredients,
+        [
+            IngredientChange::Changed {
+                old: ingredient(3.0, "dl", "milk"),
+                new: ingredient(4.0, "dl", "milk"),
+            },
+            IngredientChange::Removed(ingredient(200.0, "g", "flour")),
+            IngredientChange::Added(ingredient(1.0, "tbsp", "Sugar")),
+        ]
+    );
+    assert_eq!(
+        diff.instructions,
+        [
+            InstructionChange::Edited {
+                step: 2,
+                old: "Let it rest.".to_string(),
+                new: "Let it rest for an hour.".to_string(),
+            },
+            InstructionChange::Added {
+                step: 4,
+                text: "Serve warm.".to_string(),
+            },
+        ]
+    );
+
+    // WHEN reverting to the first revision
+    let number = service.revert_recipe(pancakes, cook, 1).await.unwrap();
+
+    // THEN the recipe is back to the original, as a new revision
+    assert_eq!(number, 3);
+    let recipe = service.recipe(pancakes, None).await.unwrap();
+    assert_eq!(RecipeContent::from(recipe), original);
+    let diff = service.revision_diff(pancakes, 1, 3, None).await.unwrap();
+    assert!(diff.is_empty());
+
+    // Titles stay unique and missing revisions are not found.
+    let mut duplicate = content.clone();
+    duplicate.title = "Waffles".to_string();
+    let result = service.update_recipe(pancakes, cook, &duplicate).await;
+    assert!(matches!(result, Err(EditError::DuplicateTitle)));
+    let result = service.revert_recipe(pancakes, cook, 9).await;
+    assert!(matches!(result, Err(EditError::NotFound)));
+    let result = service.revert_recipe(pancakes, stranger, 9).await;
+    assert!(matches!(result, Err(EditError::NotOwner)));
+    let result = service.revision(pancakes, 9, None).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("revisions"))]
+async fn test_fork(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN the pancakes of someone else
+    let service = setup_service(pool_options, options).await;
+    let (cook, stranger, pancakes) = (1, 2, 1);
+    let original = service.recipe(pancakes, None).await.unwrap();
+
+    // WHEN forking them
+    let fork = service.fork_recipe(pancakes, stranger).await.unwrap();
+
+    // THEN a private copy links back to the original
+    let recipe = service.recipe(fork, Some(stranger)).await.unwrap();
+    assert_eq!(recipe.title, "Pancakes (variation)");
+    assert_eq!(recipe.owner_id, Some(stranger));
+    assert_eq!(recipe.visibility, Visibility::Private);
+    assert_eq!(recipe.forked_from, Some(pancakes));
+    assert_eq!(recipe.servings, original.servings);
+    assert_eq!(recipe.ingredients, original.ingredients);
+    assert_eq!(recipe.instructions, original.instructions);
+    let result = service.recipe(fork, Some(cook)).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+
+    // THEN the variation is only listed for who can see it
+    let variations = service.variations(pancakes, Some(stranger)).await.unwrap();
+    let ids: Vec<_> = variations.iter().map(|listing| listing.id).collect();
+    assert_eq!(ids, [fork]);
+    assert!(service.variations(pancakes, None).await.unwrap().is_empty());
+
+    // WHEN editing the fork, THEN the original stays the same
+    let mut content = RecipeContent::from(recipe);
+    content.ingredients.pop();
+    service
+        .update_recipe(fork, stranger, &content)
+        .await
+        .unwrap();
+    let recipe = service.recipe(pancakes, None).await.unwrap();
+    assert_eq!(recipe.ingredients, original.ingredients);
+
+    // Forking again picks a new title, private recipes of others can't be forked.
+    let second = service.fork_recipe(pancakes, stranger).await.unwrap();
+    let recipe = service.recipe(second, Some(stranger)).await.unwrap();
+    assert_eq!(recipe.title, "Pancakes (variation 2)");
+    let result = service.fork_recipe(fork, cook).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("revisions"))]
+async fn test_titles_unique_per_owner(
+    pool_options: SqlitePoolOptions,
+    options: SqliteConnectOptions,
+) {
+    // GIVEN a private variation of the pancakes by a stranger
+    let service = setup_service(pool_options, options).await;
+    let (cook, stranger, pancakes, waffles) = (1, 2, 1, 2);
+    let fork = service.fork_recipe(pancakes, stranger).await.unwrap();
+
+    // WHEN the cook titles their waffles like it, THEN that's allowed, it's not theirs
+    let mut content = RecipeContent::from(service.recipe(waffles, None).await.unwrap());
+    content.title = "Pancakes (variation)".to_string();
+    service
+        .update_recipe(waffles, cook, &content)
+        .await
+        .unwrap();
+
+    // WHEN the variation is handed to the cook, THEN it's an error as they have the title already
+    let result = service.set_owner(fork, cook).await;
+    assert!(matches!(result, Err(RecipeError::DuplicateTitle)));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("sub_recipes"))]
+async fn test_sub_recipes(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a pizza that uses half of the pizza dough recipe
+    let service = setup_service(pool_options, options).await;
+    let (cook, stranger, dough, pizza, secret_dough) = (1, 2, 1, 2, 3);
+
+    // WHEN looking at the pizza, THEN the dough is an ingredient of it
+    let recipe = service.recipe(pizza, None).await.unwrap();
+    assert_eq!(recipe.ingredients[0].name, "Pizza dough");
+    assert_eq!(recipe.ingredients[0].unit, "serving");
+    assert_eq!(recipe.ingredients[0].sub_recipe_id, Some(dough));
+
+    // WHEN expanding the ingredients, THEN the dough is replaced by half of its ingredients
+    let expanded = service.expanded_ingredients(pizza, None).await.unwrap();
+    assert_eq!(
+        expanded,
+        [
+            ingredient(2.0, "dl", "tomato sauce"),
+            ingredient(150.0, "g", "cheese"),
+            ingredient(250.0, "g", "flour"),
+            ingredient(1.5, "dl", "water"),
+            ingredient(5.0, "g", "yeast"),
+        ]
+    );
+
+    // THEN the nutrition includes half of the dough
+    let pizza_nutrition = service.nutrition(pizza, None).await.unwrap();
+    let dough_nutrition = service.nutrition(dough, None).await.unwrap();
+    let cheese_kcal = 403.0 * 1.5;
+    assert!(
+        (pizza_nutrition.total.kcal - dough_nutrition.total.kcal / 2.0 - cheese_kcal).abs() < 1e-6
+    );
+    assert_eq!(pizza_nutrition.unresolved, ["tomato sauce", "yeast"]);
+
+    // WHEN the dough would use the pizza, THEN that's refused as a cycle
+    let mut content = RecipeContent::from(service.recipe(dough, None).await.unwrap());
+    let mut uses_pizza = ingredient(1.0, "", "");
+    uses_pizza.sub_recipe_id = Some(pizza);
+    content.ingredients.push(uses_pizza.clone());
+    let result = service.update_recipe(dough, cook, &content).await;
+    assert!(matches!(result, Err(EditError::Cycle)));
+    let mut content = RecipeContent::from(service.recipe(pizza, None).await.unwrap());
+    content.ingredients.push(uses_pizza);
+    let result = service.update_recipe(pizza, cook, &content).await;
+    assert!(matches!(result, Err(EditError::Cycle)));
+
+    // Private recipes of others can't be used, the name of a sub-recipe comes from the recipe.
+    let mut content = RecipeContent::from(service.recipe(pizza, None).await.unwrap());
+    content.ingredients[0].sub_recipe_id = Some(secret_dough);
+    let result = service.update_recipe(pizza, cook, &content).await;
+    assert!(matches!(result, Err(EditError::SubRecipeNotFound)));
+    content.ingredients[0].sub_recipe_id = Some(dough);
+    content.ingredients[0].name = "Bread".to_string();
+    service.update_recipe(pizza, cook, &content).await.unwrap();
+    let recipe = service.recipe(pizza, None).await.unwrap();
+    assert_eq!(recipe.ingredients[0].name, "Pizza dough");
+
+    // WHEN the dough is made private
+    service
+        .set_visibility(dough, cook, Visibility::Private, None)
+        .await
+        .unwrap();
+    // THEN others don't see it in the pizza, nor its ingredients in the expanded ones
+    let recipe = service.recipe(pizza, Some(stranger)).await.unwrap();
+    assert_eq!(recipe.ingredients[0].name, "tomato sauce");
+    let expanded = service
+        .expanded_ingredients(pizza, Some(stranger))
+        .await
+        .unwrap();
+    assert_eq!(
+        expanded,
+        [
+            ingredient(2.0, "dl", "tomato sauce"),
+            ingredient(150.0, "g", "cheese"),
+        ]
+    );
+    let nutrition = service.nutrition(pizza, None).await.unwrap();
+    assert!((nutrition.total.kcal - cheese_kcal).abs() < 1e-6);
+    // THEN the owner still does
+    let expanded = service
+        .expanded_ingredients(pizza, Some(cook))
+        .await
+        .unwrap();
+    assert_eq!(expanded.len(), 5);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("layered_recipes"))]
+async fn test_shared_sub_recipes(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN 25 layers of recipes that each use the next layer twice
+    let service = setup_service(pool_options, options).await;
+    let top = 1;
+
+    // WHEN expanding the ingredients of the top layer, THEN every layer is only expanded once
+    // and the flour at the bottom adds up over all the ways it is used
+    let expanded = service.expanded_ingredients(top, None).await.unwrap();
+    assert_eq!(expanded, [ingredient(16_777_216.0, "g", "flour")]);
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("equipment"))]
+async fn test_equipment(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN bread that needs a stand mixer and a stew that needs a Dutch oven
+    let service = setup_service(pool_options, options).await;
+    let (baker, student) = (1, 2);
+    let (bread, stew) = (1, 2);
+    let (stand_mixer, dutch_oven) = (1, 2);
+    let recipe = service.recipe(bread, None).await.unwrap();
+    assert_eq!(recipe.equipment, ["Stand mixer"]);
+
+    // WHEN the student doesn't own a Dutch oven
+    service
+        .set_equipment_owned(student, dutch_oven, false)
+        .await
+        .unwrap();
+    let equipment = service.equipment(Some(student)).await.unwrap();
+    let owned: Vec<_> = equipment
+        .iter()
+        .map(|e| (e.name.as_str(), e.owned))
+        .collect();
+    assert_eq!(owned, [("Dutch oven", false), ("Stand mixer", true)]);
+
+    // THEN the stew is left out of their listing, but only when asked to
+    let query = ListingQuery {
+        owned_equipment_only: true,
+        ..ListingQuery::default()
+    };
+    let titles = |listing: Vec<recipe_service::RecipeListing>| -> Vec<String> {
+        listing.into_iter().map(|r| r.title).collect()
+    };
+    let listing = service.recipe_listing(Some(student), &query).await.unwrap();
+    assert_eq!(titles(listing), ["Bread", "Salad"]);
+    let listing = service.recipe_listing(Some(baker), &query).await.unwrap();
+    assert_eq!(titles(listing), ["Bread", "Salad", "Stew"]);
+    let listing = service
+        .recipe_listing(Some(student), &ListingQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(titles(listing), ["Bread", "Salad", "Stew"]);
+
+    // WHEN they get one after all, THEN the stew is back
+    service
+        .set_equipment_owned(student, dutch_oven, true)
+        .await
+        .unwrap();
+    let listing = service.recipe_listing(Some(student), &query).await.unwrap();
+    assert_eq!(titles(listing), ["Bread", "Salad", "Stew"]);
+    let result = service.set_equipment_owned(student, 99, false).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+
+    // Only the owner can change the equipment of a recipe, names are matched ignoring case.
+    let equipment = [
+        "stand mixer ".to_string(),
+        "Whisk".to_string(),
+        "whisk".to_string(),
+    ];
+    let result = service
+        .set_recipe_equipment(stew, student, &equipment)
+        .await;
+    assert!(matches!(result, Err(EquipmentError::NotOwner)));
+    service
+        .set_recipe_equipment(stew, baker, &equipment)
+        .await
+        .unwrap();
+    let recipe = service.recipe(stew, None).await.unwrap();
+    assert_eq!(recipe.equipment, ["Stand mixer", "Whisk"]);
+    let all = service.equipment(None).await.unwrap();
+    assert_eq!(all.len(), 3);
+    assert!(all.iter().any(|e| e.id == stand_mixer && e.owned));
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("pantry"))]
+async fn test_substitutions(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN oat milk as a replacement for milk
+    let service = setup_service(pool_options, options).await;
+    let cook = 1;
+    let (pancakes, salad, secret_eggs) = (1, 3, 4);
+    let id = service
+        .set_substitution("Milk", "oat milk", 1.0, "")
+        .await
+        .unwrap();
+
+    // WHEN setting it again, THEN the same substitution is updated
+    let updated = service
+        .set_substitution("milk", "Oat milk", 1.2, " thinner ")
+        .await
+        .unwrap();
+    assert_eq!(updated, id);
+    let substitutions = service.substitutions().await.unwrap();
+    assert_eq!(substitutions.len(), 1);
+    assert_eq!(substitutions[0].ingredient, "milk");
+    assert_eq!(substitutions[0].replacement, "oat milk");
+    assert!((substitutions[0].ratio - 1.2).abs() < 1e-9);
+    assert_eq!(substitutions[0].note, "thinner");
+
+    // THEN it's suggested for the recipes that use milk, and only for visible recipes
+    let for_pancakes = service.recipe_substitutions(pancakes, None).await.unwrap();
+    assert_eq!(for_pancakes.len(), 1);
+    let for_salad = service.recipe_substitutions(salad, None).await.unwrap();
+    assert!(for_salad.is_empty());
+    let result = service.recipe_substitutions(secret_eggs, None).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+
+    // WHEN only eggs and oat milk are in the pantry
+    service
+        .set_pantry_item(cook, "egg", None, None)
+        .await
+        .unwrap();
+    service
+        .set_pantry_item(cook, "oat milk", None, None)
+        .await
+        .unwrap();
+
+    // THEN the oat milk counts for the milk
+    let matches = service.what_can_i_cook(cook).await.unwrap();
+    let matches: Vec<_> = matches
+        .iter()
+        .map(|m| {
+            (
+                m.title.as_str(),
+                m.available,
+                m.total,
+                m.substitutions.clone(),
+            )
+        })
+        .collect();
+    let oat_milk = vec![("milk".to_string(), "oat milk".to_string())];
+    assert_eq!(
+        matches,
+        [
+            ("Omelette", 2, 2, oat_milk.clone()),
+            ("Pancakes", 2, 3, oat_milk)
+        ]
+    );
+
+    // Invalid substitutions are rejected.
+    let result = service.set_substitution("milk", "MILK", 1.0, "").await;
+    assert!(matches!(result, Err(SubstitutionError::SameIngredient)));
+    let result = service.set_substitution("milk", "water", 0.0, "").await;
+    assert!(matches!(result, Err(SubstitutionError::InvalidRatio)));
+    let result = service.set_substitution(" ", "water", 1.0, "").await;
+    assert!(matches!(result, Err(SubstitutionError::EmptyIngredient)));
+
+    // WHEN removing it, THEN it's gone
+    service.remove_substitution(id).await.unwrap();
+    assert!(service.substitutions().await.unwrap().is_empty());
+    let result = service.remove_substitution(id).await;
+    assert!(matches!(result, Err(RecipeError::NotFound)));
+}
@@ -0,0 +1,109 @@
+//! Merging the ingredients of several recipes into the lines of a shopping list.
+use recipe_service::Ingredient;
+use recipe_service::units::{self, Amount, Dimension};
+
+use crate::Item;
+
+/// What a line is measured in, quantities are only added up within the same line unit.
+#[derive(Debug, PartialEq)]
+enum LineUnit {
+    /// A known unit, summed in the base unit of its dimension.
+    Base(Dimension),
+    /// A unit that can't be converted, like a pinch, only summed with the exact same unit.
+    Other(String),
+}
+
+#[derive(Debug)]
+struct Line {
+    /// Ingredients are matched on their lowercase name.
+    key: String,
+    name: String,
+    unit: LineUnit,
+    quantity: f64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Aggregator {
+    lines: Vec<Line>,
+}
+
+impl Aggregator {
+    /// Add an ingredient, with its quantity multiplied by `scale`.
+    pub fn add(&mut self, ingredient: &Ingredient, scale: f64) {
+        let (quantity, unit) = match units::to_base(ingredient.quantity * scale, &ingredient.unit) {
+            Some(amount) => (amount.value, LineUnit::Base(amount.dimension)),
+            None => (
+                ingredient.quantity * scale,
+                LineUnit::Other(ingredient.unit.trim().to_lowercase()),
+            ),
+        };
+        let key = ingredient.name.trim().to_lowercase();
+        match self
+            .lines
+            .iter_mut()
+            .find(|line| line.key == key && line.unit == unit)
+        {
+            Some(line) => line.quantity += quantity,
+            None => self.lines.push(Line {
+                key,
+                name: ingredient.name.trim().to_string(),
+                unit,
+                quantity,
+            }),
+        }
+    }
+
+    /// Take what's already in stock off the list. Stock without a known quantity is assumed to
+    /// be enough, stock in a unit that can't be compared to a line leaves that line alone.
+    pub fn subtract(&mut self, name: &str, stock: Option<(f64, &str)>) {
+        let key = name.trim().to_lowercase();
+        match stock {
+            None => self.lines.retain(|line| line.key != key),
+            Some((quantity, unit)) => {
+                let (quantity, unit) = match units::to_base(quantity, unit) {
+                    Some(amount) => (amount.value, LineUnit::Base(amount.dimension)),
+                    None => (quantity, LineUnit::Other(unit.trim().to_lowercase())),
+                };
+                // Only the lines that ran out go, ingredients without a quantity like salt to taste
+                // stay on the list.
+                self.lines.retain_mut(|line| {
+                    if line.key != key || line.unit != unit {
+                        return true;
+                    }
+                    line.quantity -= quantity;
+                    line.quantity > 0.0
+                });
+            }
+        }
+    }
+
+    /// The lines of the shopping list ordered by name, in the units that read best.
+    pub fn into_items(mut self) -> Vec<Item> {
+        self.lines.sort_by(|a, b| a.key.cmp(&b.key));
+        self.lines
+            .into_iter()
+            .map(|line| {
+                let (quantity, unit) = match line.unit {
+                    LineUnit::Base(dimension) => {
+                        let (quantity, unit) = units::humanize(Amount {
+                            value: line.quantity,
+                            dimension,
+                        });
+                        (quantity, unit.to_string())
+                    }
+                    LineUnit::Other(unit) => (line.quantity, unit),
+                };
+                Item {
+                    name: line.name,
+                    quantity: round(quantity),
+                    unit,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Scaled quantities are rounded to two decimals to not end up buying 0.30000000000000004 l.
+fn round(quantity: f64) -> f64 {
+    (quantity * 100.0).round() / 100.0
+}
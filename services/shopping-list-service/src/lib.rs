@@ -0,0 +1,245 @@
+mod aggregate;
+mod repository;
+
+use chrono::NaiveDate;
+use meal_plan_service::{MealPlanService, PlanError};
+use recipe_service::{CostEstimate, Ingredient, RecipeError, RecipeService};
+use sqlx::SqlitePool;
+
+use crate::aggregate::Aggregator;
+pub use crate::repository::ShoppingListError;
+use crate::repository::ShoppingListRepository;
+
+#[must_use]
+#[derive(Clone)]
+pub struct ShoppingListService {
+    repo: ShoppingListRepository,
+    recipe_service: RecipeService,
+    meal_plan_service: MealPlanService,
+}
+
+impl ShoppingListService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            repo: ShoppingListRepository::new(pool.clone()),
+            recipe_service: RecipeService::new(pool.clone()),
+            meal_plan_service: MealPlanService::new(pool),
+        }
+    }
+
+    /// The shopping lists of a user, newest first.
+    pub async fn lists(&self, user_id: i64) -> Result<Vec<ShoppingListListing>, ShoppingListError> {
+        self.repo.lists(user_id).await
+    }
+
+    pub async fn list(
+        &self,
+        user_id: i64,
+        list_id: i64,
+    ) -> Result<ShoppingList, ShoppingListError> {
+        self.repo.list(user_id, list_id).await
+    }
+
+    /// Estimate what the items of a shopping list that aren't checked off yet cost with today's
+    /// prices.
+    pub async fn cost(
+        &self,
+        user_id: i64,
+        list_id: i64,
+    ) -> Result<CostEstimate, ShoppingListError> {
+        let list = self.repo.list(user_id, list_id).await?;
+        let ingredients: Vec<Ingredient> = list
+            .items
+            .into_iter()
+            .filter(|item| !item.checked)
+            .map(|item| Ingredient {
+                quantity: item.quantity,
+                unit: item.unit,
+                name: item.name,
+                sub_recipe_id: None,
+            })
+            .collect();
+        let estimate = self.recipe_service.estimate_cost(&ingredients).await?;
+        Ok(estimate)
+    }
+
+    /// Create a shopping list with everything needed to cook the given recipes, each for the
+    /// given number of servings, minus what's in the pantry of the user. Sub-recipes are shopped
+    /// for by their ingredients.
+    pub async fn create_from_recipes(
+        &self,
+        user_id: i64,
+        name: &str,
+        recipes: &[RecipeSelection],
+    ) -> Result<i64, GenerateError> {
+        self.create_list(user_id, name, recipes, false).await
+    }
+
+    /// Create a shopping list for the meals a user planned between `from` and `to`, both
+    /// inclusive. Planned recipes that the user can't see anymore, because they were made private
+    /// since, are left out.
+    pub async fn create_from_plan(
+        &self,
+        user_id: i64,
+        name: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<i64, GenerateError> {
+        let recipes: Vec<_> = self
+            .meal_plan_service
+            .plan(user_id, from, to)
+            .await?
+            .into_iter()
+            .map(|entry| RecipeSelection {
+                recipe_id: entry.recipe_id,
+                servings: entry.servings,
+            })
+            .collect();
+        self.create_list(user_id, name, &recipes, true).await
+    }
+
+    /// Shop for recipes, leaving out the ones that aren't visible to the user with `skip_hidden`
+    /// rather than failing.
+    async fn create_list(
+        &self,
+        user_id: i64,
+        name: &str,
+        recipes: &[RecipeSelection],
+        skip_hidden: bool,
+    ) -> Result<i64, GenerateError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(GenerateError::EmptyName);
+        }
+        if recipes.iter().any(|selection| selection.servings == 0) {
+            return Err(GenerateError::InvalidServings);
+        }
+
+        let mut aggregator = Aggregator::default();
+        for selection in recipes {
+            let recipe = match self
+                .recipe_service
+                .recipe(selection.recipe_id, Some(user_id))
+                .await
+            {
+                Ok(recipe) => recipe,
+                Err(RecipeError::NotFound) if skip_hidden => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let scale = f64::from(selection.servings) / f64::from(recipe.servings);
+            let ingredients = self
+                .recipe_service
+                .expanded_ingredients(selection.recipe_id, Some(user_id))
+                .await?;
+            for ingredient in &ingredients {
+                aggregator.add(ingredient, scale);
+            }
+        }
+        for item in self.recipe_service.pantry(user_id).await? {
+            if item.is_expired {
+                continue;
+            }
+            let stock = item.quantity.zip(item.unit.as_deref());
+            aggregator.subtract(&item.ingredient, stock);
+        }
+
+        let id = self
+            .repo
+            .create_list(user_id, name, &aggregator.into_items())
+            .await?;
+        Ok(id)
+    }
+
+    /// Check off an item as bought, or uncheck it again.
+    pub async fn set_checked(
+        &self,
+        user_id: i64,
+        item_id: i64,
+        checked: bool,
+    ) -> Result<(), ShoppingListError> {
+        self.repo.set_checked(user_id, item_id, checked).await
+    }
+
+    pub async fn delete_list(&self, user_id: i64, list_id: i64) -> Result<(), ShoppingListError> {
+        self.repo.delete_list(user_id, list_id).await
+    }
+}
+
+/// A recipe to shop for, and for how many servings.
+#[derive(Clone, Copy, Debug)]
+pub struct RecipeSelection {
+    pub recipe_id: i64,
+    pub servings: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct ShoppingListListing {
+    pub id: i64,
+    pub name: String,
+    pub creation_date: NaiveDate,
+    pub item_count: i64,
+    pub checked_count: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ShoppingList {
+    pub id: i64,
+    pub name: String,
+    pub creation_date: NaiveDate,
+    pub items: Vec<ShoppingListItem>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ShoppingListItem {
+    pub id: i64,
+    pub name: String,
+    pub quantity: f64,
+    pub unit: String,
+    pub checked: bool,
+}
+
+/// A line of a shopping list that is about to be created.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Item {
+    pub name: String,
+    pub quantity: f64,
+    pub unit: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GenerateError {
+    #[error("recipe not found")]
+    RecipeNotFound,
+    #[error("a shopping list needs a name")]
+    EmptyName,
+    #[error("at least one serving must be shopped for")]
+    InvalidServings,
+    #[error("invalid date range")]
+    InvalidRange,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<ShoppingListError> for GenerateError {
+    fn from(_: ShoppingListError) -> Self {
+        Self::Internal
+    }
+}
+
+impl From<RecipeError> for GenerateError {
+    fn from(err: RecipeError) -> Self {
+        match err {
+            RecipeError::NotFound => Self::RecipeNotFound,
+            _ => Self::Internal,
+        }
+    }
+}
+
+impl From<PlanError> for GenerateError {
+    fn from(err: PlanError) -> Self {
+        match err {
+            PlanError::InvalidRange => Self::InvalidRange,
+            _ => Self::Internal,
+        }
+    }
+}
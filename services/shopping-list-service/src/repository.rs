@@ -0,0 +1,229 @@
+use chrono::NaiveDate;
+use recipe_service::RecipeError;
+use sqlx::SqlitePool;
+
+use crate::{Item, ShoppingList, ShoppingListItem, ShoppingListListing};
+
+#[derive(Clone)]
+pub(crate) struct ShoppingListRepository {
+    pool: SqlitePool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ShoppingListError {
+    #[error("shopping list not found")]
+    NotFound,
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("internal integrity was compromised")]
+    ConsistencyError,
+}
+
+impl From<RecipeError> for ShoppingListError {
+    fn from(err: RecipeError) -> Self {
+        match err {
+            RecipeError::Database(err) => Self::Database(err),
+            _ => Self::ConsistencyError,
+        }
+    }
+}
+
+/// Dates are stored as `TEXT` in the database.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+fn parse_date(date: &str) -> Result<NaiveDate, ShoppingListError> {
+    let date = NaiveDate::parse_from_str(date, DATE_FORMAT)
+        .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+    Ok(date)
+}
+
+impl ShoppingListRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// The shopping lists of a user, newest first.
+    pub async fn lists(&self, user_id: i64) -> Result<Vec<ShoppingListListing>, ShoppingListError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                sl.id,
+                sl.name,
+                sl.creation_date,
+                COUNT(sli.id) AS "item_count!: i64",
+                COALESCE(SUM(sli.checked), 0) AS "checked_count!: i64"
+            FROM
+                shopping_list AS sl
+            LEFT JOIN
+                shopping_list_item AS sli
+                ON sl.id = sli.shopping_list_id
+            WHERE
+                sl.user_id = ?
+            GROUP BY
+                sl.id
+            ORDER BY
+                sl.creation_date DESC,
+                sl.id DESC;
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(ShoppingListListing {
+                id: record.id,
+                name: record.name,
+                creation_date: parse_date(&record.creation_date)?,
+                item_count: record.item_count,
+                checked_count: record.checked_count,
+            })
+        })
+        .collect()
+    }
+
+    pub async fn list(
+        &self,
+        user_id: i64,
+        list_id: i64,
+    ) -> Result<ShoppingList, ShoppingListError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT
+                name,
+                creation_date
+            FROM shopping_list
+            WHERE
+                id = ?
+                AND user_id = ?;
+            "#,
+            list_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(ShoppingListError::NotFound)?;
+
+        let items = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                name,
+                quantity,
+                unit,
+                checked
+            FROM shopping_list_item
+            WHERE
+                shopping_list_id = ?
+            ORDER BY
+                position;
+            "#,
+            list_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| ShoppingListItem {
+            id: record.id,
+            name: record.name,
+            quantity: record.quantity,
+            unit: record.unit,
+            checked: record.checked,
+        })
+        .collect();
+
+        Ok(ShoppingList {
+            id: list_id,
+            name: r.name,
+            creation_date: parse_date(&r.creation_date)?,
+            items,
+        })
+    }
+
+    pub async fn create_list(
+        &self,
+        user_id: i64,
+        name: &str,
+        items: &[Item],
+    ) -> Result<i64, ShoppingListError> {
+        let mut tx = self.pool.begin().await?;
+        let list_id = sqlx::query!(
+            r#"
+            INSERT INTO shopping_list (user_id, name)
+            VALUES (?, ?);
+            "#,
+            user_id,
+            name
+        )
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+        for (position, item) in (0_i64..).zip(items) {
+            sqlx::query!(
+                r#"
+                INSERT INTO shopping_list_item (shopping_list_id, name, quantity, unit, position)
+                VALUES (?, ?, ?, ?, ?);
+                "#,
+                list_id,
+                item.name,
+                item.quantity,
+                item.unit,
+                position
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(list_id)
+    }
+
+    pub async fn set_checked(
+        &self,
+        user_id: i64,
+        item_id: i64,
+        checked: bool,
+    ) -> Result<(), ShoppingListError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE shopping_list_item SET checked = ?
+            WHERE
+                id = ?
+                AND shopping_list_id IN (
+                    SELECT id
+                    FROM shopping_list
+                    WHERE user_id = ?
+                );
+            "#,
+            checked,
+            item_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(ShoppingListError::NotFound),
+            1 => Ok(()),
+            _ => Err(ShoppingListError::ConsistencyError),
+        }
+    }
+
+    pub async fn delete_list(&self, user_id: i64, list_id: i64) -> Result<(), ShoppingListError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM shopping_list
+            WHERE
+                id = ?
+                AND user_id = ?;
+            "#,
+            list_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(ShoppingListError::NotFound),
+            1 => Ok(()),
+            _ => Err(ShoppingListError::ConsistencyError),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+INSERT INTO pantry_item
+(user_id, ingredient_id, quantity, unit, expires_on)
+VALUES
+(1, 1, 2, 'dl', NULL),
+(1, 2, NULL, NULL, NULL),
+(1, 3, 1, 'kg', '2999-12-31'),
+(1, 4, NULL, NULL, '2000-01-01'),
+(2, 1, 10, 'l', NULL);
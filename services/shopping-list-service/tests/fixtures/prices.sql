@@ -0,0 +1,10 @@
+INSERT INTO store
+(name)
+VALUES
+('Corner shop');
+
+INSERT INTO ingredient_price
+(ingredient, store_id, price, unit, effective_from)
+VALUES
+('milk', 1, 10.0, 'l', '2020-01-01'),
+('egg', 1, 2.0, 'piece', '2020-01-01');
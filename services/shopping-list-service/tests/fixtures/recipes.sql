@@ -0,0 +1,61 @@
+INSERT INTO user
+(username, email, password_hash)
+VALUES
+('shopper', 'shopper@example.com', 'hash'),
+('other', 'other@example.com', 'hash');
+
+INSERT INTO source
+(name)
+VALUES
+('Cookbook');
+
+INSERT INTO meal_type
+(type_name)
+VALUES
+('Breakfast');
+
+INSERT INTO ingredient
+(name)
+VALUES
+('milk'),
+('egg'),
+('flour'),
+('salt'),
+('saffron');
+
+INSERT INTO measurement
+(unit)
+VALUES
+('dl'),
+('ml'),
+('piece'),
+('g'),
+('tbsp'),
+('pinch');
+
+INSERT INTO recipe
+(title, description, meal_type_id, source_id, servings, owner_id, visibility)
+VALUES
+('Pancakes', 'Fluffy.', 1, 1, 4, NULL, 'public'),
+('Omelette', 'Quick.', 1, 1, 1, NULL, 'public'),
+('Secret bun', 'Hush.', 1, 1, 2, 2, 'private');
+
+INSERT INTO recipe_ingredient
+(quantity, recipe_id, ingredient_id, measurement_id)
+VALUES
+(3, 1, 1, 1),
+(2, 1, 2, 3),
+(200, 1, 3, 4),
+(1, 1, 4, 6),
+(50, 2, 1, 2),
+(2, 2, 2, 3),
+(1, 2, 3, 5),
+(1, 3, 5, 6);
+
+INSERT INTO meal_plan_entry
+(user_id, recipe_id, planned_on, meal_slot, servings)
+VALUES
+(1, 1, '2025-06-02', 'breakfast', 4),
+(1, 2, '2025-06-03', 'lunch', 1),
+(1, 3, '2025-06-04', 'dinner', 2),
+(1, 2, '2025-06-20', 'lunch', 3);
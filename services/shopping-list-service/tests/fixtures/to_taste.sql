@@ -0,0 +1,14 @@
+INSERT INTO ingredient
+(name)
+VALUES
+('pepper');
+
+INSERT INTO measurement
+(unit)
+VALUES
+('to taste');
+
+INSERT INTO recipe_ingredient
+(quantity, recipe_id, ingredient_id, measurement_id)
+VALUES
+(0, 2, 6, 7);
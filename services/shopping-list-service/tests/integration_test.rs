@@ -0,0 +1,257 @@
+use chrono::NaiveDate;
+use shopping_list_service::{
+    GenerateError, RecipeSelection, ShoppingListError, ShoppingListService,
+};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+static MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
+
+const SHOPPER: i64 = 1;
+const OTHER: i64 = 2;
+const PANCAKES: i64 = 1;
+const OMELETTE: i64 = 2;
+const SECRET_BUN: i64 = 3;
+
+async fn setup_service(
+    pool_options: SqlitePoolOptions,
+    options: SqliteConnectOptions,
+) -> ShoppingListService {
+    let pool = pool_options
+        .connect_with(options.foreign_keys(true))
+        .await
+        .unwrap();
+    ShoppingListService::new(pool)
+}
+
+async fn items(service: &ShoppingListService, list_id: i64) -> Vec<(String, f64, String)> {
+    service
+        .list(SHOPPER, list_id)
+        .await
+        .unwrap()
+        .items
+        .into_iter()
+        .map(|item| (item.name, item.quantity, item.unit))
+        .collect()
+}
+
+fn line(name: &str, quantity: f64, unit: &str) -> (String, f64, String) {
+    (name.to_string(), quantity, unit.to_string())
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes"))]
+async fn test_create_from_recipes(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+
+    // WHEN shopping for pancakes for 8 and omelettes for 2
+    let recipes = [
+        RecipeSelection {
+            recipe_id: PANCAKES,
+            servings: 8,
+        },
+        RecipeSelection {
+            recipe_id: OMELETTE,
+            servings: 2,
+        },
+    ];
+    let id = service
+        .create_from_recipes(SHOPPER, "Brunch", &recipes)
+        .await
+        .unwrap();
+
+    // THEN the ingredients are scaled and merged, and flour by weight and by volume are kept
+    // apart
+    assert_eq!(
+        items(&service, id).await,
+        [
+            line("egg", 8.0, "piece"),
+            line("flour", 400.0, "g"),
+            line("flour", 30.0, "ml"),
+            line("milk", 7.0, "dl"),
+            line("salt", 2.0, "pinch"),
+        ]
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes"))]
+async fn test_create_from_plan(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let from = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+    let to = NaiveDate::from_ymd_opt(2025, 6, 7).unwrap();
+
+    // WHEN shopping for the first week of June
+    let id = service
+        .create_from_plan(SHOPPER, "Week 23", from, to)
+        .await
+        .unwrap();
+
+    // THEN only the meals planned for that week are on the list, leaving out the bun that was
+    // made private after it was planned
+    assert_eq!(
+        items(&service, id).await,
+        [
+            line("egg", 4.0, "piece"),
+            line("flour", 200.0, "g"),
+            line("flour", 15.0, "ml"),
+            line("milk", 3.5, "dl"),
+            line("salt", 1.0, "pinch"),
+        ]
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes"))]
+async fn test_create_invalid(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    let service = setup_service(pool_options, options).await;
+    let selection = |recipe_id, servings| {
+        [RecipeSelection {
+            recipe_id,
+            servings,
+        }]
+    };
+
+    let result = service
+        .create_from_recipes(SHOPPER, " ", &selection(PANCAKES, 4))
+        .await;
+    assert!(matches!(result, Err(GenerateError::EmptyName)));
+
+    let result = service
+        .create_from_recipes(SHOPPER, "Brunch", &selection(PANCAKES, 0))
+        .await;
+    assert!(matches!(result, Err(GenerateError::InvalidServings)));
+
+    // Recipes that are not visible to the user can't be shopped for.
+    let result = service
+        .create_from_recipes(SHOPPER, "Buns", &selection(SECRET_BUN, 2))
+        .await;
+    assert!(matches!(result, Err(GenerateError::RecipeNotFound)));
+
+    // Nothing was created.
+    assert!(service.lists(SHOPPER).await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes"))]
+async fn test_check_off_and_delete(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN
+    let service = setup_service(pool_options, options).await;
+    let recipes = [RecipeSelection {
+        recipe_id: OMELETTE,
+        servings: 1,
+    }];
+    let id = service
+        .create_from_recipes(SHOPPER, "Lunch", &recipes)
+        .await
+        .unwrap();
+    let egg = service.list(SHOPPER, id).await.unwrap().items[0].id;
+
+    // WHEN another user checks off an item or looks at the list, THEN it's not found
+    let result = service.set_checked(OTHER, egg, true).await;
+    assert!(matches!(result, Err(ShoppingListError::NotFound)));
+    let result = service.list(OTHER, id).await;
+    assert!(matches!(result, Err(ShoppingListError::NotFound)));
+
+    // WHEN checking off an item
+    service.set_checked(SHOPPER, egg, true).await.unwrap();
+
+    // THEN it's checked off
+    let list = service.list(SHOPPER, id).await.unwrap();
+    assert!(list.items[0].checked);
+    assert!(!list.items[1].checked);
+    let lists = service.lists(SHOPPER).await.unwrap();
+    assert_eq!(lists.len(), 1);
+    assert_eq!(lists[0].item_count, 3);
+    assert_eq!(lists[0].checked_count, 1);
+
+    // WHEN deleting the list, THEN it's gone
+    let result = service.delete_list(OTHER, id).await;
+    assert!(matches!(result, Err(ShoppingListError::NotFound)));
+    service.delete_list(SHOPPER, id).await.unwrap();
+    assert!(service.lists(SHOPPER).await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes", "pantry"))]
+async fn test_subtract_pantry(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a pantry with some milk, eggs, a bag of flour and expired salt
+    let service = setup_service(pool_options, options).await;
+
+    // WHEN shopping for pancakes for 8 and omelettes for 2
+    let recipes = [
+        RecipeSelection {
+            recipe_id: PANCAKES,
+            servings: 8,
+        },
+        RecipeSelection {
+            recipe_id: OMELETTE,
+            servings: 2,
+        },
+    ];
+    let id = service
+        .create_from_recipes(SHOPPER, "Brunch", &recipes)
+        .await
+        .unwrap();
+
+    // THEN only what's missing from the pantry is on the list, the flour measured by volume
+    // can't be compared to the bag of flour so it stays
+    assert_eq!(
+        items(&service, id).await,
+        [
+            line("flour", 30.0, "ml"),
+            line("milk", 5.0, "dl"),
+            line("salt", 2.0, "pinch"),
+        ]
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes", "pantry", "to_taste"))]
+async fn test_subtract_pantry_keeps_to_taste(
+    pool_options: SqlitePoolOptions,
+    options: SqliteConnectOptions,
+) {
+    // GIVEN an omelette with pepper to taste and a pantry with enough milk
+    let service = setup_service(pool_options, options).await;
+
+    // WHEN shopping for an omelette
+    let recipes = [RecipeSelection {
+        recipe_id: OMELETTE,
+        servings: 1,
+    }];
+    let id = service
+        .create_from_recipes(SHOPPER, "Lunch", &recipes)
+        .await
+        .unwrap();
+
+    // THEN the milk is taken off the list, but the pepper without a quantity stays
+    assert_eq!(
+        items(&service, id).await,
+        [line("flour", 15.0, "ml"), line("pepper", 0.0, "to taste")]
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR", fixtures("recipes", "prices"))]
+async fn test_cost(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a list for an omelette, with prices for milk and eggs
+    let service = setup_service(pool_options, options).await;
+    let recipes = [RecipeSelection {
+        recipe_id: OMELETTE,
+        servings: 1,
+    }];
+    let id = service
+        .create_from_recipes(SHOPPER, "Lunch", &recipes)
+        .await
+        .unwrap();
+
+    // WHEN estimating the cost, THEN the unpriced flour is left out
+    let cost = service.cost(SHOPPER, id).await.unwrap();
+    assert!((cost.total - 4.5).abs() < 1e-6);
+    assert_eq!(cost.unpriced, ["flour"]);
+
+    // WHEN the eggs are bought, THEN they no longer count
+    let egg = service.list(SHOPPER, id).await.unwrap().items[0].id;
+    service.set_checked(SHOPPER, egg, true).await.unwrap();
+    let cost = service.cost(SHOPPER, id).await.unwrap();
+    assert!((cost.total - 0.5).abs() < 1e-6);
+
+    let result = service.cost(OTHER, id).await;
+    assert!(matches!(result, Err(ShoppingListError::NotFound)));
+}
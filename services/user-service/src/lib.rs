@@ -1,12 +1,33 @@
+mod mail;
 mod repository;
+mod role;
+mod token;
+mod totp;
 mod user;
 
+pub use mail::MailKind;
 pub use repository::UserError;
-pub use user::User;
+pub use role::{Permission, Role};
+pub use totp::totp_code;
+pub use user::{ApiToken, ApiTokenGrant, NewApiToken, NewUser, TotpEnrolment, User, UserSession};
+
+use std::net::IpAddr;
 
 use sqlx::SqlitePool;
 
 use crate::repository::UserRepository;
+use crate::token::TokenPurpose;
+
+/// How long a link to verify an email address keeps working.
+const VERIFY_EMAIL_HOURS: i64 = 24;
+/// How long a link to reset a password keeps working.
+const RESET_PASSWORD_HOURS: i64 = 1;
+/// How many recovery codes users get when they enable two-factor authentication.
+const RECOVERY_CODES: usize = 10;
+/// How long a session may go unused before it is logged out.
+const SESSION_IDLE_DAYS: i64 = 30;
+/// How long to wait before mailing the same address the same kind of mail again.
+const MAIL_COOLDOWN_MINUTES: i64 = 5;
 
 #[must_use]
 #[derive(Clone)]
@@ -21,47 +42,485 @@ impl UserService {
     }
 
     pub async fn user_by_email(&self, email: &str) -> Result<User, UserError> {
-        self.repo.user_by_email(email).await
+        self.repo.user_by_email(&normalize_email(email)).await
     }
 
     pub async fn user_by_id(&self, user_id: i64) -> Result<User, UserError> {
         self.repo.user_by_id(user_id).await
     }
 
+    /// Whether any user was added yet, until then anyone can add the first user.
+    pub async fn has_users(&self) -> Result<bool, UserError> {
+        self.repo.has_users().await
+    }
+
+    /// Add a user, the first user that is added becomes an admin and everyone after a viewer
+    /// until an admin gives them more roles.
+    ///
+    /// New users have to verify their email address with the returned token before they can log
+    /// in.
     pub async fn add_user(
         &self,
         username: &str,
         email: &str,
         password: &str,
-    ) -> Result<i64, AddUserError> {
+    ) -> Result<NewUser, AddUserError> {
         validate_password(password)?;
 
         let password_hash = password_auth::generate_hash(password);
-        let id = self.repo.add_user(username, email, &password_hash).await?;
-        Ok(id)
+        let id = self
+            .repo
+            .add_user(username, &normalize_email(email), &password_hash)
+            .await?;
+        let verification_token = self.new_token(id, TokenPurpose::VerifyEmail).await?;
+        Ok(NewUser {
+            id,
+            verification_token,
+        })
+    }
+
+    /// A new token to verify the email address of a user, replacing any earlier one. Users that
+    /// are verified already don't get one.
+    pub async fn verification_token(&self, email: &str) -> Result<Option<String>, UserError> {
+        let user = self.repo.user_by_email(&normalize_email(email)).await?;
+        if user.email_verified {
+            return Ok(None);
+        }
+        let token = self.new_token(user.id, TokenPurpose::VerifyEmail).await?;
+        Ok(Some(token))
+    }
+
+    /// Whether a mail of `kind` may be sent to `email`, which is then recorded as sent. Anyone can
+    /// have most mails sent, so the same address only gets the same kind of mail once every
+    /// `MAIL_COOLDOWN_MINUTES`. Ask before creating the token for a mail, a new token replaces the
+    /// one in the last mail.
+    pub async fn claim_mail(&self, email: &str, kind: MailKind) -> Result<bool, UserError> {
+        self.repo
+            .claim_mail(
+                &normalize_email(email),
+                kind.as_str(),
+                MAIL_COOLDOWN_MINUTES,
+            )
+            .await
+    }
+
+    /// Mark the email address a verification token was sent to as verified.
+    pub async fn verify_email(&self, token: &str) -> Result<User, VerifyEmailError> {
+        let user_id = self
+            .repo
+            .take_token(&token::hash(token), TokenPurpose::VerifyEmail)
+            .await?;
+        self.repo.set_email_verified(user_id).await?;
+        Ok(self.repo.user_by_id(user_id).await?)
+    }
+
+    /// Change the name a user is shown with.
+    pub async fn change_username(
+        &self,
+        user_id: i64,
+        username: &str,
+    ) -> Result<User, ChangeUsernameError> {
+        let username = username.trim();
+        if username.is_empty() {
+            return Err(ChangeUsernameError::EmptyUsername);
+        }
+        self.repo.set_username(user_id, username).await?;
+        Ok(self.repo.user_by_id(user_id).await?)
+    }
+
+    /// Ask to change the email address of a user, returning a token to mail to the new address.
+    /// The address only changes once `confirm_email_change` gets the token, until then the user
+    /// keeps their old one.
+    pub async fn request_email_change(
+        &self,
+        user_id: i64,
+        new_email: &str,
+    ) -> Result<String, ChangeEmailError> {
+        let new_email = normalize_email(new_email);
+        if !new_email.contains('@') {
+            return Err(ChangeEmailError::InvalidEmail);
+        }
+        let user = self.repo.user_by_id(user_id).await?;
+        if user.email == new_email {
+            return Err(ChangeEmailError::SameEmail);
+        }
+        match self.repo.user_by_email(&new_email).await {
+            Ok(_) => return Err(ChangeEmailError::AlreadyExists),
+            Err(UserError::NotFound) => {}
+            Err(err) => return Err(err.into()),
+        }
+        self.repo.set_pending_email(user_id, &new_email).await?;
+        Ok(self.new_token(user_id, TokenPurpose::ChangeEmail).await?)
+    }
+
+    /// Change the email address of a user to the one the token was mailed to.
+    pub async fn confirm_email_change(&self, token: &str) -> Result<User, ChangeEmailError> {
+        let user_id = match self
+            .repo
+            .take_token(&token::hash(token), TokenPurpose::ChangeEmail)
+            .await
+        {
+            Ok(user_id) => user_id,
+            Err(UserError::NotFound) => return Err(ChangeEmailError::InvalidToken),
+            Err(err) => return Err(err.into()),
+        };
+        // Someone may have taken the address since it was asked for.
+        match self.repo.apply_pending_email(user_id).await {
+            Ok(()) => {}
+            Err(UserError::NotFound) => return Err(ChangeEmailError::InvalidToken),
+            Err(err) => return Err(err.into()),
+        }
+        Ok(self.repo.user_by_id(user_id).await?)
+    }
+
+    /// A new token to reset the password of a user, replacing any earlier one.
+    pub async fn password_reset_token(&self, user_id: i64) -> Result<String, UserError> {
+        self.new_token(user_id, TokenPurpose::ResetPassword).await
+    }
+
+    /// Set a new password with a reset token, which ends all sessions of the user.
+    pub async fn reset_password(
+        &self,
+        token: &str,
+        new_password: &str,
+    ) -> Result<User, ResetPasswordError> {
+        // Before taking the token, so that it can be tried again with a better password.
+        validate_password(new_password)?;
+
+        let user_id = self
+            .repo
+            .take_token(&token::hash(token), TokenPurpose::ResetPassword)
+            .await?;
+        let user = self.repo.user_by_id(user_id).await?;
+        let password_hash = password_auth::generate_hash(new_password);
+        self.repo
+            .set_user_password_hash(&user.email, &password_hash)
+            .await?;
+        // The reset link was mailed to the user, so they proved the address is theirs.
+        self.repo.set_email_verified(user_id).await?;
+        self.password_changed(user_id, None).await?;
+        Ok(self.repo.user_by_id(user_id).await?)
+    }
+
+    /// Start enabling two-factor authentication, with a new secret that has to be confirmed with
+    /// `confirm_totp`.
+    pub async fn begin_totp(&self, user_id: i64) -> Result<TotpEnrolment, TotpError> {
+        if self.has_totp(user_id).await? {
+            return Err(TotpError::AlreadyEnabled);
+        }
+        let user = self.repo.user_by_id(user_id).await?;
+        let secret = totp::generate_secret();
+        self.repo.set_pending_totp(user_id, &secret).await?;
+        Ok(TotpEnrolment {
+            otpauth_uri: totp::otpauth_uri(&secret, &user.email),
+            secret,
+        })
+    }
+
+    /// Enable two-factor authentication once the user entered a first code, returning their
+    /// recovery codes. These are shown once, only their hashes are kept.
+    pub async fn confirm_totp(&self, user_id: i64, code: &str) -> Result<Vec<String>, TotpError> {
+        let totp = self
+            .repo
+            .totp(user_id)
+            .await?
+            .ok_or(TotpError::NotEnrolled)?;
+        if totp.confirmed {
+            return Err(TotpError::AlreadyEnabled);
+        }
+        let step = totp::matching_step(&totp.secret, code, totp::current_step())
+            .ok_or(TotpError::InvalidCode)?;
+        let recovery_codes = (0..RECOVERY_CODES)
+            .map(|_| {
+                let code = hex::encode(rand::random::<[u8; 5]>());
+                format!("{}-{}", &code[..5], &code[5..])
+            })
+            .collect::<Vec<_>>();
+        let hashes = recovery_codes
+            .iter()
+            .map(|code| token::hash(&normalize_recovery_code(code)))
+            .collect::<Vec<_>>();
+        self.repo.confirm_totp(user_id, step, &hashes).await?;
+        Ok(recovery_codes)
+    }
+
+    /// Whether the user has confirmed two-factor authentication.
+    pub async fn has_totp(&self, user_id: i64) -> Result<bool, UserError> {
+        let totp = self.repo.totp(user_id).await?;
+        Ok(totp.is_some_and(|totp| totp.confirmed))
+    }
+
+    /// Check a code from the authenticator of a user, or one of their recovery codes. Either can
+    /// only be used once.
+    pub async fn verify_second_factor(&self, user_id: i64, code: &str) -> Result<(), TotpError> {
+        let totp = self
+            .repo
+            .totp(user_id)
+            .await?
+            .filter(|totp| totp.confirmed)
+            .ok_or(TotpError::NotEnrolled)?;
+        if let Some(step) = totp::matching_step(&totp.secret, code, totp::current_step()) {
+            if self.repo.use_totp_step(user_id, step).await? {
+                return Ok(());
+            }
+            return Err(TotpError::InvalidCode);
+        }
+        let code_hash = token::hash(&normalize_recovery_code(code));
+        if self.repo.take_recovery_code(user_id, &code_hash).await? {
+            return Ok(());
+        }
+        Err(TotpError::InvalidCode)
+    }
+
+    /// Disable two-factor authentication, which takes a code like logging in does.
+    pub async fn disable_totp(&self, user_id: i64, code: &str) -> Result<(), TotpError> {
+        self.verify_second_factor(user_id, code).await?;
+        self.repo.delete_totp(user_id).await?;
+        Ok(())
+    }
+
+    /// Create a personal API token for a user, with at most the permissions they have. The
+    /// secret is only returned here, only its hash is kept.
+    pub async fn create_api_token(
+        &self,
+        user_id: i64,
+        name: &str,
+        permissions: &[Permission],
+        valid_for_days: Option<i64>,
+    ) -> Result<NewApiToken, ApiTokenError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ApiTokenError::EmptyName);
+        }
+        if valid_for_days.is_some_and(|days| days <= 0) {
+            return Err(ApiTokenError::InvalidExpiry);
+        }
+        let held = self.repo.permissions(user_id).await?;
+        if let Some(&permission) = permissions.iter().find(|p| !held.contains(p)) {
+            return Err(ApiTokenError::PermissionNotHeld(permission));
+        }
+
+        // The prefix makes tokens recognizable, like for secret scanners.
+        let secret = format!("food_{}", hex::encode(rand::random::<[u8; 32]>()));
+        let token_id = self
+            .repo
+            .add_api_token(
+                user_id,
+                name,
+                &token::hash(&secret),
+                permissions,
+                valid_for_days,
+            )
+            .await?;
+        let api_token = self
+            .repo
+            .api_tokens(user_id)
+            .await?
+            .into_iter()
+            .find(|api_token| api_token.id == token_id)
+            .ok_or(ApiTokenError::Internal)?;
+        Ok(NewApiToken { api_token, secret })
+    }
+
+    pub async fn api_tokens(&self, user_id: i64) -> Result<Vec<ApiToken>, UserError> {
+        self.repo.api_tokens(user_id).await
+    }
+
+    pub async fn revoke_api_token(&self, user_id: i64, token_id: i64) -> Result<(), ApiTokenError> {
+        self.repo.delete_api_token(user_id, token_id).await?;
+        Ok(())
+    }
+
+    /// Who an API token acts as. It may do what it was created for, as far as its user still
+    /// may.
+    pub async fn authenticate_api_token(
+        &self,
+        secret: &str,
+    ) -> Result<ApiTokenGrant, ApiTokenError> {
+        let (token_id, user_id) = match self.repo.use_api_token(&token::hash(secret)).await {
+            Ok(token) => token,
+            Err(UserError::NotFound) => return Err(ApiTokenError::InvalidToken),
+            Err(err) => return Err(err.into()),
+        };
+        let held = self.repo.permissions(user_id).await?;
+        let permissions = self
+            .repo
+            .api_token_permissions(token_id)
+            .await?
+            .into_iter()
+            .filter(|permission| held.contains(permission))
+            .collect();
+        Ok(ApiTokenGrant {
+            user_id,
+            permissions,
+        })
+    }
+
+    /// Remember that a session is logged in as a user, along with where it was logged in from.
+    pub async fn start_session(
+        &self,
+        session_id: &str,
+        user_id: i64,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<(), UserError> {
+        let ip = ip.map(|ip| ip.to_string());
+        self.repo
+            .add_session(session_id, user_id, user_agent, ip.as_deref())
+            .await
+    }
+
+    /// Whether a session is still logged in as a user, it isn't once it was ended or after not
+    /// being used for `SESSION_IDLE_DAYS`.
+    pub async fn is_session_active(
+        &self,
+        session_id: &str,
+        user_id: i64,
+    ) -> Result<bool, UserError> {
+        self.repo
+            .has_session(session_id, user_id, SESSION_IDLE_DAYS)
+            .await
+    }
+
+    /// Record that a session is in use, from `ip` if it is known.
+    pub async fn touch_session(
+        &self,
+        session_id: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<(), UserError> {
+        let ip = ip.map(|ip| ip.to_string());
+        self.repo.touch_session(session_id, ip.as_deref()).await
+    }
+
+    /// The sessions a user is logged in with, most recently used first. Idle sessions are
+    /// cleaned up here, they can't be used anymore anyway.
+    pub async fn sessions(&self, user_id: i64) -> Result<Vec<UserSession>, UserError> {
+        self.repo
+            .delete_idle_sessions(user_id, SESSION_IDLE_DAYS)
+            .await?;
+        self.repo.sessions(user_id).await
+    }
+
+    /// End a session of a user by the id they see, returning its session id.
+    pub async fn end_session_by_id(&self, user_id: i64, id: i64) -> Result<String, UserError> {
+        self.repo.delete_session_by_id(user_id, id).await
+    }
+
+    /// End every session of a user except the one with `session_id`.
+    pub async fn end_other_sessions(
+        &self,
+        user_id: i64,
+        session_id: &str,
+    ) -> Result<(), UserError> {
+        self.repo.delete_other_sessions(user_id, session_id).await
+    }
+
+    pub async fn end_session(&self, session_id: &str) -> Result<(), UserError> {
+        self.repo.delete_session(session_id).await
+    }
+
+    /// End every session of a user, logging them out everywhere.
+    pub async fn end_sessions(&self, user_id: i64) -> Result<(), UserError> {
+        self.repo.delete_sessions(user_id).await
+    }
+
+    /// Whoever knew the old password or had a reset link loses access, only `keep_session` stays
+    /// logged in.
+    async fn password_changed(
+        &self,
+        user_id: i64,
+        keep_session: Option<&str>,
+    ) -> Result<(), UserError> {
+        self.repo
+            .delete_tokens(user_id, TokenPurpose::ResetPassword)
+            .await?;
+        match keep_session {
+            Some(session_id) => self.repo.delete_other_sessions(user_id, session_id).await,
+            None => self.repo.delete_sessions(user_id).await,
+        }
+    }
+
+    async fn new_token(&self, user_id: i64, purpose: TokenPurpose) -> Result<String, UserError> {
+        let hours = match purpose {
+            TokenPurpose::VerifyEmail | TokenPurpose::ChangeEmail => VERIFY_EMAIL_HOURS,
+            TokenPurpose::ResetPassword => RESET_PASSWORD_HOURS,
+        };
+        let (token, token_hash) = token::generate();
+        self.repo
+            .add_token(user_id, &token_hash, purpose, hours)
+            .await?;
+        Ok(token)
+    }
+
+    pub async fn roles(&self, user_id: i64) -> Result<Vec<Role>, UserError> {
+        self.repo.roles(user_id).await
+    }
+
+    /// Replace the roles of a user.
+    pub async fn set_roles(&self, user_id: i64, roles: &[Role]) -> Result<(), UserError> {
+        self.repo.set_roles(user_id, roles).await
+    }
+
+    /// The permissions a user has through any of their roles.
+    pub async fn permissions(&self, user_id: i64) -> Result<Vec<Permission>, UserError> {
+        self.repo.permissions(user_id).await
+    }
+
+    pub async fn has_permission(
+        &self,
+        user_id: i64,
+        permission: Permission,
+    ) -> Result<bool, UserError> {
+        let permissions = self.repo.permissions(user_id).await?;
+        Ok(permissions.contains(&permission))
     }
 
     pub async fn delete_user(&self, email: &str) -> Result<(), DeleteUserError> {
-        self.repo.delete_user(email).await?;
+        self.repo.delete_user(&normalize_email(email)).await?;
         Ok(())
     }
 
+    /// Change the password of a user, which ends all of their sessions except `keep_session`, the
+    /// one they changed it in.
     pub async fn change_password(
         &self,
         email: &str,
         current_password: &str,
         new_password: &str,
+        keep_session: Option<&str>,
     ) -> Result<(), ChangePasswordError> {
-        let user = self.repo.user_by_email(email).await?;
+        let user = self.repo.user_by_email(&normalize_email(email)).await?;
 
         password_auth::verify_password(current_password, &user.password_hash)?;
 
+        self.update_password(&user, new_password, keep_session)
+            .await
+    }
+
+    /// Set the password of a user without their current one, for user managers. This ends all of
+    /// their sessions as well.
+    pub async fn set_password(
+        &self,
+        user_id: i64,
+        new_password: &str,
+    ) -> Result<(), ChangePasswordError> {
+        let user = self.repo.user_by_id(user_id).await?;
+        self.update_password(&user, new_password, None).await
+    }
+
+    async fn update_password(
+        &self,
+        user: &User,
+        new_password: &str,
+        keep_session: Option<&str>,
+    ) -> Result<(), ChangePasswordError> {
         validate_password(new_password)?;
 
         let password_hash = password_auth::generate_hash(new_password);
         self.repo
             .set_user_password_hash(&user.email, &password_hash)
             .await?;
+        self.password_changed(user.id, keep_session).await?;
 
         Ok(())
     }
@@ -92,6 +551,151 @@ impl From<Vec<PasswordRequirement>> for AddUserError {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum VerifyEmailError {
+    #[error("invalid or expired token")]
+    InvalidToken,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<UserError> for VerifyEmailError {
+    fn from(err: UserError) -> Self {
+        match err {
+            UserError::NotFound => Self::InvalidToken,
+            _ => Self::Internal,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChangeUsernameError {
+    #[error("username is empty")]
+    EmptyUsername,
+    #[error("user not found")]
+    NotFound,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<UserError> for ChangeUsernameError {
+    fn from(err: UserError) -> Self {
+        match err {
+            UserError::NotFound => Self::NotFound,
+            _ => Self::Internal,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChangeEmailError {
+    #[error("invalid email address")]
+    InvalidEmail,
+    #[error("this is the current email address")]
+    SameEmail,
+    #[error("email address is taken")]
+    AlreadyExists,
+    #[error("invalid or expired token")]
+    InvalidToken,
+    #[error("user not found")]
+    NotFound,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<UserError> for ChangeEmailError {
+    fn from(err: UserError) -> Self {
+        match err {
+            UserError::AlreadyExists => Self::AlreadyExists,
+            UserError::NotFound => Self::NotFound,
+            _ => Self::Internal,
+        }
+    }
+}
+
+/// Email addresses are stored and looked up trimmed and lowercased, so that an account can't be
+/// registered twice, or missed, by typing the address differently.
+#[must_use]
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Recovery codes are accepted regardless of case, dashes and spaces.
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiTokenError {
+    #[error("token name is empty")]
+    EmptyName,
+    #[error("tokens have to be valid for at least a day")]
+    InvalidExpiry,
+    #[error("permission {0:?} isn't held by the user")]
+    PermissionNotHeld(Permission),
+    #[error("token not found")]
+    NotFound,
+    #[error("invalid or expired token")]
+    InvalidToken,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<UserError> for ApiTokenError {
+    fn from(err: UserError) -> Self {
+        match err {
+            UserError::NotFound => Self::NotFound,
+            _ => Self::Internal,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TotpError {
+    #[error("two-factor authentication isn't set up")]
+    NotEnrolled,
+    #[error("two-factor authentication is enabled already")]
+    AlreadyEnabled,
+    #[error("invalid code")]
+    InvalidCode,
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<UserError> for TotpError {
+    fn from(_err: UserError) -> Self {
+        Self::Internal
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResetPasswordError {
+    #[error("invalid or expired token")]
+    InvalidToken,
+    #[error("password requirements failed: {0:?}")]
+    PasswordRequirements(Vec<PasswordRequirement>),
+    #[error("internal error")]
+    Internal,
+}
+
+impl From<UserError> for ResetPasswordError {
+    fn from(err: UserError) -> Self {
+        match err {
+            UserError::NotFound => Self::InvalidToken,
+            _ => Self::Internal,
+        }
+    }
+}
+
+impl From<Vec<PasswordRequirement>> for ResetPasswordError {
+    fn from(requirements: Vec<PasswordRequirement>) -> Self {
+        Self::PasswordRequirements(requirements)
+    }
+}
+
 #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum PasswordRequirement {
     PasswordTooShort { min_length: usize },
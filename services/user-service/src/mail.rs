@@ -0,0 +1,19 @@
+/// The kinds of mail that users are sent, each kind has its own cooldown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MailKind {
+    VerifyEmail,
+    AlreadyRegistered,
+    ResetPassword,
+    ChangeEmail,
+}
+
+impl MailKind {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::VerifyEmail => "verify-email",
+            Self::AlreadyRegistered => "already-registered",
+            Self::ResetPassword => "reset-password",
+            Self::ChangeEmail => "change-email",
+        }
+    }
+}
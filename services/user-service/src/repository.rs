@@ -1,6 +1,12 @@
 use sqlx::SqlitePool;
 
-use crate::User;
+use crate::token::TokenPurpose;
+use crate::{ApiToken, Permission, Role, User, UserSession};
+
+pub(crate) struct TotpRecord {
+    pub secret: String,
+    pub confirmed: bool,
+}
 
 #[derive(Clone)]
 pub(crate) struct UserRepository {
@@ -24,12 +30,14 @@ impl UserRepository {
         Self { pool }
     }
 
+    /// Add a user, the first user becomes an admin and everyone after an editor.
     pub async fn add_user(
         &self,
         username: &str,
         email: &str,
         password_hash: &str,
     ) -> Result<i64, UserError> {
+        let mut tx = self.pool.begin().await?;
         let result = sqlx::query!(
             r#"
             INSERT INTO user (username, email, password_hash)
@@ -39,15 +47,118 @@ impl UserRepository {
             email,
             password_hash,
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await;
-        match result {
-            Ok(query_result) => Ok(query_result.last_insert_rowid()),
+        let user_id = match result {
+            Ok(query_result) => query_result.last_insert_rowid(),
             Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
-                Err(UserError::AlreadyExists)
+                return Err(UserError::AlreadyExists);
             }
-            Err(err) => Err(err.into()),
+            Err(err) => return Err(err.into()),
+        };
+        sqlx::query!(
+            r#"
+            INSERT INTO user_role (user_id, role_id)
+            SELECT ?1, r.id
+            FROM role AS r
+            WHERE r.name = CASE
+                WHEN (SELECT COUNT(*) FROM user) = 1 THEN 'admin'
+                ELSE 'viewer'
+            END;
+            "#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(user_id)
+    }
+
+    pub async fn roles(&self, user_id: i64) -> Result<Vec<Role>, UserError> {
+        sqlx::query!(
+            r#"
+            SELECT r.name
+            FROM
+                user_role AS ur
+            JOIN
+                role AS r
+                ON ur.role_id = r.id
+            WHERE
+                ur.user_id = ?
+            ORDER BY
+                r.id;
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| Role::try_from(record.name.as_str()))
+        .collect()
+    }
+
+    pub async fn set_roles(&self, user_id: i64, roles: &[Role]) -> Result<(), UserError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            SELECT id
+            FROM user
+            WHERE id = ?;
+            "#,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(UserError::NotFound)?;
+        sqlx::query!(
+            r#"
+            DELETE FROM user_role
+            WHERE user_id = ?;
+            "#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for role in roles {
+            let role = role.as_str();
+            sqlx::query!(
+                r#"
+                INSERT OR IGNORE INTO user_role (user_id, role_id)
+                SELECT ?, id
+                FROM role
+                WHERE name = ?;
+                "#,
+                user_id,
+                role
+            )
+            .execute(&mut *tx)
+            .await?;
         }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn permissions(&self, user_id: i64) -> Result<Vec<Permission>, UserError> {
+        sqlx::query!(
+            r#"
+            SELECT DISTINCT rp.permission
+            FROM
+                user_role AS ur
+            JOIN
+                role_permission AS rp
+                ON ur.role_id = rp.role_id
+            WHERE
+                ur.user_id = ?
+            ORDER BY
+                rp.permission;
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| Permission::try_from(record.permission.as_str()))
+        .collect()
     }
 
     pub async fn delete_user(&self, email: &str) -> Result<(), UserError> {
@@ -67,6 +178,17 @@ impl UserRepository {
         }
     }
 
+    pub async fn has_users(&self) -> Result<bool, UserError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM user) AS "has_users!: bool";
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(r.has_users)
+    }
+
     pub async fn user_by_email(&self, email: &str) -> Result<User, UserError> {
         let user = sqlx::query_as!(
             User,
@@ -99,6 +221,660 @@ impl UserRepository {
         Ok(user)
     }
 
+    /// Store a token for a user, replacing their other tokens for the same purpose.
+    pub async fn add_token(
+        &self,
+        user_id: i64,
+        token_hash: &str,
+        purpose: TokenPurpose,
+        valid_for_hours: i64,
+    ) -> Result<(), UserError> {
+        let purpose = purpose.as_str();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM user_token
+            WHERE user_id = ? AND purpose = ?;
+            "#,
+            user_id,
+            purpose
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO user_token (token_hash, user_id, purpose, expires_at)
+            VALUES (?, ?, ?, datetime('now', '+' || ? || ' hours'));
+            "#,
+            token_hash,
+            user_id,
+            purpose,
+            valid_for_hours
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Record a mail of `kind` as sent to `email`, unless one was sent less than
+    /// `cooldown_minutes` ago. Returns whether it was recorded.
+    pub async fn claim_mail(
+        &self,
+        email: &str,
+        kind: &str,
+        cooldown_minutes: i64,
+    ) -> Result<bool, UserError> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO mail_sent (email, kind, sent_at)
+            VALUES (?1, ?2, datetime('now'))
+            ON CONFLICT (email, kind) DO UPDATE SET
+                sent_at = excluded.sent_at
+            WHERE mail_sent.sent_at <= datetime('now', '-' || ?3 || ' minutes');
+            "#,
+            email,
+            kind,
+            cooldown_minutes
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Use up a token that hasn't expired, returning the user it belongs to.
+    pub async fn take_token(
+        &self,
+        token_hash: &str,
+        purpose: TokenPurpose,
+    ) -> Result<i64, UserError> {
+        let purpose = purpose.as_str();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM user_token
+            WHERE expires_at <= datetime('now');
+            "#
+        )
+        .execute(&mut *tx)
+        .await?;
+        let record = sqlx::query!(
+            r#"
+            DELETE FROM user_token
+            WHERE token_hash = ? AND purpose = ?
+            RETURNING user_id;
+            "#,
+            token_hash,
+            purpose
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(UserError::NotFound)?;
+        tx.commit().await?;
+        Ok(record.user_id)
+    }
+
+    pub async fn delete_tokens(
+        &self,
+        user_id: i64,
+        purpose: TokenPurpose,
+    ) -> Result<(), UserError> {
+        let purpose = purpose.as_str();
+        sqlx::query!(
+            r#"
+            DELETE FROM user_token
+            WHERE user_id = ? AND purpose = ?;
+            "#,
+            user_id,
+            purpose
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn add_session(
+        &self,
+        session_id: &str,
+        user_id: i64,
+        user_agent: Option<&str>,
+        ip: Option<&str>,
+    ) -> Result<(), UserError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_session (session_id, user_id, user_agent, ip)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (session_id) DO UPDATE SET
+                user_id = excluded.user_id,
+                created_at = datetime('now'),
+                last_seen_at = datetime('now'),
+                user_agent = excluded.user_agent,
+                ip = excluded.ip;
+            "#,
+            session_id,
+            user_id,
+            user_agent,
+            ip
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Whether a session is logged in as a user, and was used within `idle_days`.
+    pub async fn has_session(
+        &self,
+        session_id: &str,
+        user_id: i64,
+        idle_days: i64,
+    ) -> Result<bool, UserError> {
+        let r = sqlx::query!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM user_session
+                WHERE
+                    session_id = ?1
+                    AND user_id = ?2
+                    AND last_seen_at > datetime('now', '-' || ?3 || ' days')
+            ) AS "has_session!: bool";
+            "#,
+            session_id,
+            user_id,
+            idle_days
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(r.has_session)
+    }
+
+    pub async fn delete_session(&self, session_id: &str) -> Result<(), UserError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM user_session
+            WHERE session_id = ?;
+            "#,
+            session_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record that a session was used, at most once a minute to spare the database a write for
+    /// every request.
+    pub async fn touch_session(&self, session_id: &str, ip: Option<&str>) -> Result<(), UserError> {
+        sqlx::query!(
+            r#"
+            UPDATE user_session SET
+                last_seen_at = datetime('now'),
+                ip = COALESCE(?2, ip)
+            WHERE
+                session_id = ?1
+                AND last_seen_at < datetime('now', '-1 minute');
+            "#,
+            session_id,
+            ip
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn sessions(&self, user_id: i64) -> Result<Vec<UserSession>, UserError> {
+        let sessions = sqlx::query_as!(
+            UserSession,
+            r#"
+            SELECT id, created_at, last_seen_at, user_agent, ip, session_id
+            FROM user_session
+            WHERE user_id = ?
+            ORDER BY last_seen_at DESC, id DESC;
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(sessions)
+    }
+
+    /// Delete a session of a user by its id, returning its session id.
+    pub async fn delete_session_by_id(&self, user_id: i64, id: i64) -> Result<String, UserError> {
+        let record = sqlx::query!(
+            r#"
+            DELETE FROM user_session
+            WHERE id = ? AND user_id = ?
+            RETURNING session_id;
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(UserError::NotFound)?;
+        Ok(record.session_id)
+    }
+
+    pub async fn delete_other_sessions(
+        &self,
+        user_id: i64,
+        session_id: &str,
+    ) -> Result<(), UserError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM user_session
+            WHERE user_id = ? AND session_id != ?;
+            "#,
+            user_id,
+            session_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_idle_sessions(
+        &self,
+        user_id: i64,
+        idle_days: i64,
+    ) -> Result<(), UserError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM user_session
+            WHERE user_id = ?1 AND last_seen_at <= datetime('now', '-' || ?2 || ' days');
+            "#,
+            user_id,
+            idle_days
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_sessions(&self, user_id: i64) -> Result<(), UserError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM user_session
+            WHERE user_id = ?;
+            "#,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn totp(&self, user_id: i64) -> Result<Option<TotpRecord>, UserError> {
+        let record = sqlx::query_as!(
+            TotpRecord,
+            r#"
+            SELECT secret, confirmed
+            FROM user_totp
+            WHERE user_id = ?;
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(record)
+    }
+
+    /// Store a secret that still has to be confirmed, replacing an unconfirmed one.
+    pub async fn set_pending_totp(&self, user_id: i64, secret: &str) -> Result<(), UserError> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO user_totp (user_id, secret)
+            VALUES (?, ?)
+            ON CONFLICT (user_id) DO UPDATE SET
+                secret = excluded.secret,
+                last_used_step = NULL
+            WHERE confirmed = FALSE;
+            "#,
+            user_id,
+            secret
+        )
+        .execute(&self.pool)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(err)) if err.is_foreign_key_violation() => {
+                Err(UserError::NotFound)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Confirm the secret of a user with the step of their first code, replacing their recovery
+    /// codes.
+    pub async fn confirm_totp(
+        &self,
+        user_id: i64,
+        step: i64,
+        recovery_code_hashes: &[String],
+    ) -> Result<(), UserError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE user_totp SET confirmed = TRUE, last_used_step = ?
+            WHERE user_id = ?;
+            "#,
+            step,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM user_recovery_code
+            WHERE user_id = ?;
+            "#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for code_hash in recovery_code_hashes {
+            sqlx::query!(
+                r#"
+                INSERT INTO user_recovery_code (code_hash, user_id)
+                VALUES (?, ?);
+                "#,
+                code_hash,
+                user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Use the code of a step, which only works for steps after the last used one.
+    pub async fn use_totp_step(&self, user_id: i64, step: i64) -> Result<bool, UserError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user_totp SET last_used_step = ?1
+            WHERE
+                user_id = ?2
+                AND confirmed = TRUE
+                AND (last_used_step IS NULL OR last_used_step < ?1);
+            "#,
+            step,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn take_recovery_code(
+        &self,
+        user_id: i64,
+        code_hash: &str,
+    ) -> Result<bool, UserError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM user_recovery_code
+            WHERE user_id = ? AND code_hash = ?;
+            "#,
+            user_id,
+            code_hash
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn delete_totp(&self, user_id: i64) -> Result<(), UserError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM user_totp
+            WHERE user_id = ?;
+            "#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM user_recovery_code
+            WHERE user_id = ?;
+            "#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn add_api_token(
+        &self,
+        user_id: i64,
+        name: &str,
+        token_hash: &str,
+        permissions: &[Permission],
+        valid_for_days: Option<i64>,
+    ) -> Result<i64, UserError> {
+        let mut tx = self.pool.begin().await?;
+        let token_id = sqlx::query!(
+            r#"
+            INSERT INTO api_token (user_id, name, token_hash, expires_at)
+            VALUES (?1, ?2, ?3, CASE
+                WHEN ?4 IS NULL THEN NULL
+                ELSE datetime('now', '+' || ?4 || ' days')
+            END)
+            RETURNING id;
+            "#,
+            user_id,
+            name,
+            token_hash,
+            valid_for_days
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .id;
+        for permission in permissions {
+            let permission = permission.as_str();
+            sqlx::query!(
+                r#"
+                INSERT OR IGNORE INTO api_token_permission (token_id, permission)
+                VALUES (?, ?);
+                "#,
+                token_id,
+                permission
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(token_id)
+    }
+
+    pub async fn api_tokens(&self, user_id: i64) -> Result<Vec<ApiToken>, UserError> {
+        sqlx::query!(
+            r#"
+            SELECT
+                t.id,
+                t.name,
+                t.created_at,
+                t.last_used_at,
+                t.expires_at,
+                GROUP_CONCAT(p.permission) AS permissions
+            FROM
+                api_token AS t
+            LEFT JOIN
+                api_token_permission AS p
+                ON t.id = p.token_id
+            WHERE
+                t.user_id = ?
+            GROUP BY
+                t.id
+            ORDER BY
+                t.id;
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| {
+            let mut permissions = record
+                .permissions
+                .as_deref()
+                .map(|permissions| {
+                    permissions
+                        .split(',')
+                        .map(Permission::try_from)
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?
+                .unwrap_or_default();
+            permissions.sort_by_key(|permission| permission.as_str());
+            Ok(ApiToken {
+                id: record.id,
+                name: record.name,
+                permissions,
+                created_at: record.created_at,
+                last_used_at: record.last_used_at,
+                expires_at: record.expires_at,
+            })
+        })
+        .collect()
+    }
+
+    pub async fn delete_api_token(&self, user_id: i64, token_id: i64) -> Result<(), UserError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM api_token
+            WHERE id = ? AND user_id = ?;
+            "#,
+            token_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(UserError::NotFound),
+            1 => Ok(()),
+            _ => Err(UserError::ConsistencyError),
+        }
+    }
+
+    /// Mark a token that hasn't expired as used, returning its id and user.
+    pub async fn use_api_token(&self, token_hash: &str) -> Result<(i64, i64), UserError> {
+        let record = sqlx::query!(
+            r#"
+            UPDATE api_token SET last_used_at = datetime('now')
+            WHERE
+                token_hash = ?
+                AND (expires_at IS NULL OR expires_at > datetime('now'))
+            RETURNING id, user_id;
+            "#,
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(UserError::NotFound)?;
+        Ok((record.id, record.user_id))
+    }
+
+    pub async fn api_token_permissions(&self, token_id: i64) -> Result<Vec<Permission>, UserError> {
+        sqlx::query!(
+            r#"
+            SELECT permission
+            FROM api_token_permission
+            WHERE token_id = ?
+            ORDER BY permission;
+            "#,
+            token_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| Permission::try_from(record.permission.as_str()))
+        .collect()
+    }
+
+    pub async fn set_username(&self, user_id: i64, username: &str) -> Result<(), UserError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user SET username = ?
+            WHERE id = ?;
+            "#,
+            username,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(UserError::NotFound),
+            1 => Ok(()),
+            _ => Err(UserError::ConsistencyError),
+        }
+    }
+
+    pub async fn set_pending_email(&self, user_id: i64, email: &str) -> Result<(), UserError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user SET pending_email = ?
+            WHERE id = ?;
+            "#,
+            email,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(UserError::NotFound),
+            1 => Ok(()),
+            _ => Err(UserError::ConsistencyError),
+        }
+    }
+
+    /// Make the pending email address of a user their address, which is verified by now.
+    pub async fn apply_pending_email(&self, user_id: i64) -> Result<(), UserError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user SET
+                email = pending_email,
+                pending_email = NULL,
+                email_verified = TRUE
+            WHERE id = ? AND pending_email IS NOT NULL;
+            "#,
+            user_id
+        )
+        .execute(&self.pool)
+        .await;
+        let result = match result {
+            Ok(result) => result,
+            Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+                return Err(UserError::AlreadyExists);
+            }
+            Err(err) => return Err(err.into()),
+        };
+        match result.rows_affected() {
+            0 => Err(UserError::NotFound),
+            1 => Ok(()),
+            _ => Err(UserError::ConsistencyError),
+        }
+    }
+
+    pub async fn set_email_verified(&self, user_id: i64) -> Result<(), UserError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user SET email_verified = TRUE
+            WHERE id = ?;
+            "#,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+        match result.rows_affected() {
+            0 => Err(UserError::NotFound),
+            1 => Ok(()),
+            _ => Err(UserError::ConsistencyError),
+        }
+    }
+
     pub async fn set_user_password_hash(
         &self,
         email: &str,
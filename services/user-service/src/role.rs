@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use crate::UserError;
+
+/// A set of permissions that can be given to users.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    /// Everything, including managing the accounts of other users.
+    Admin,
+    /// Viewing and editing recipes.
+    Editor,
+    /// Only viewing recipes.
+    Viewer,
+}
+
+impl Role {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::Editor => "editor",
+            Self::Viewer => "viewer",
+        }
+    }
+}
+
+impl TryFrom<&str> for Role {
+    type Error = UserError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "admin" => Ok(Self::Admin),
+            "editor" => Ok(Self::Editor),
+            "viewer" => Ok(Self::Viewer),
+            _ => Err(UserError::ConsistencyError),
+        }
+    }
+}
+
+/// What a user is allowed to do, granted through their roles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Permission {
+    ManageUsers,
+    EditRecipes,
+    ViewRecipes,
+}
+
+impl Permission {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ManageUsers => "users:manage",
+            Self::EditRecipes => "recipes:edit",
+            Self::ViewRecipes => "recipes:view",
+        }
+    }
+}
+
+impl TryFrom<&str> for Permission {
+    type Error = UserError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "users:manage" => Ok(Self::ManageUsers),
+            "recipes:edit" => Ok(Self::EditRecipes),
+            "recipes:view" => Ok(Self::ViewRecipes),
+            _ => Err(UserError::ConsistencyError),
+        }
+    }
+}
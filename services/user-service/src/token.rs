@@ -0,0 +1,32 @@
+use sha2::{Digest, Sha256};
+
+/// What a token sent to a user can be used for, a token only works for its own purpose.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TokenPurpose {
+    VerifyEmail,
+    ResetPassword,
+    ChangeEmail,
+}
+
+impl TokenPurpose {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::VerifyEmail => "verify-email",
+            Self::ResetPassword => "reset-password",
+            Self::ChangeEmail => "change-email",
+        }
+    }
+}
+
+/// A new random token and the hash to store for it.
+pub(crate) fn generate() -> (String, String) {
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    let token_hash = hash(&token);
+    (token, token_hash)
+}
+
+/// Tokens are random and long enough that a fast unsalted hash is enough to keep a leaked
+/// database from giving away usable tokens.
+pub(crate) fn hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
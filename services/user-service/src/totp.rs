@@ -0,0 +1,84 @@
+//! Time-based one-time passwords as in RFC 6238, the variant authenticator apps support: SHA-1,
+//! six digits and a new code every 30 seconds.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+const STEP_SECONDS: u64 = 30;
+const DIGITS: u32 = 6;
+/// Codes of the step before and after are accepted too, for clocks that are a little off.
+const ALLOWED_DRIFT: i64 = 1;
+const ISSUER: &str = "food";
+
+/// A new random secret, base32 encoded like authenticator apps expect it.
+pub(crate) fn generate_secret() -> String {
+    BASE32_NOPAD.encode(&rand::random::<[u8; 20]>())
+}
+
+/// The URI that authenticator apps enrol an account with, usually shown as a QR code.
+pub(crate) fn otpauth_uri(secret: &str, account: &str) -> String {
+    let issuer = encode_uri_component(ISSUER);
+    let account = encode_uri_component(account);
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}\
+        &algorithm=SHA1&digits={DIGITS}&period={STEP_SECONDS}"
+    )
+}
+
+pub(crate) fn current_step() -> i64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    i64::try_from(now / STEP_SECONDS).unwrap_or(i64::MAX)
+}
+
+/// The step that `code` belongs to, if it is a valid code for a step around `step`.
+pub(crate) fn matching_step(secret: &str, code: &str, step: i64) -> Option<i64> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    (step - ALLOWED_DRIFT..=step + ALLOWED_DRIFT).find(|&step| code_at(&key, step) == Some(code))
+}
+
+/// The code for a base32 encoded secret at a unix time.
+#[must_use]
+pub fn totp_code(secret: &str, unix_time: u64) -> Option<String> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let step = i64::try_from(unix_time / STEP_SECONDS).ok()?;
+    let code = code_at(&key, step)?;
+    Some(format!("{code:0width$}", width = DIGITS as usize))
+}
+
+fn code_at(key: &[u8], step: i64) -> Option<u32> {
+    let counter = u64::try_from(step).ok()?;
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).ok()?;
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    // Dynamic truncation: the last nibble picks where to read 31 bits from.
+    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+    let bytes = [
+        digest[offset],
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ];
+    let binary = u32::from_be_bytes(bytes) & 0x7fff_ffff;
+    Some(binary % 10_u32.pow(DIGITS))
+}
+
+fn encode_uri_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
@@ -1,6 +1,64 @@
+use crate::Permission;
+
 pub struct User {
     pub id: i64,
     pub username: String,
     pub email: String,
     pub password_hash: String,
+    pub email_verified: bool,
+    /// An address the user wants to change to, which they still have to verify.
+    pub pending_email: Option<String>,
+}
+
+/// A user that was just added, with the token they verify their email address with.
+#[derive(Debug)]
+pub struct NewUser {
+    pub id: i64,
+    pub verification_token: String,
+}
+
+/// A secret for two-factor authentication that still has to be confirmed.
+#[derive(Debug)]
+pub struct TotpEnrolment {
+    pub secret: String,
+    /// For authenticator apps, contains the secret.
+    pub otpauth_uri: String,
+}
+
+/// A session a user is logged in with, as they can see it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserSession {
+    /// Not the session id of the cookie, which must stay secret.
+    pub id: i64,
+    pub created_at: String,
+    pub last_seen_at: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub session_id: String,
+}
+
+/// A personal token for scripts and the CLI, without the secret itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    /// The most the token may do, it never gets more than its user has.
+    pub permissions: Vec<Permission>,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+/// A token that was just created, its secret is only known until then.
+#[derive(Debug)]
+pub struct NewApiToken {
+    pub api_token: ApiToken,
+    pub secret: String,
+}
+
+/// Who a request with an API token acts as, and what it may do.
+#[derive(Debug)]
+pub struct ApiTokenGrant {
+    pub user_id: i64,
+    pub permissions: Vec<Permission>,
 }
@@ -1,6 +1,12 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use sqlx::migrate::Migrator;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use user_service::{AddUserError, ChangePasswordError, PasswordRequirement, UserService};
+use user_service::{
+    AddUserError, ApiTokenError, ChangeEmailError, ChangePasswordError, ChangeUsernameError,
+    MailKind, PasswordRequirement, Permission, ResetPasswordError, Role, TotpError, UserError,
+    UserService, VerifyEmailError, totp_code,
+};
 
 static MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
 
@@ -52,6 +58,35 @@ async fn test_add_user_already_exists(
     assert!(matches!(result, Err(AddUserError::AlreadyExists)));
 }
 
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_email_case_insensitive(
+    pool_options: SqlitePoolOptions,
+    options: SqliteConnectOptions,
+) {
+    // GIVEN a user registered with a capitalized address
+    let service = setup_service(pool_options, options).await;
+    let user_id = service
+        .add_user("testuser", " Test@Example.com", "ValidPassword123!")
+        .await
+        .unwrap()
+        .id;
+
+    // WHEN adding another user with the address in lowercase
+    let result = service
+        .add_user("testuser2", "test@example.com", "ValidPassword123!")
+        .await;
+
+    // THEN it is taken, and the user is found however the address is typed
+    assert!(matches!(result, Err(AddUserError::AlreadyExists)));
+    let user = service.user_by_email("TEST@example.COM ").await.unwrap();
+    assert_eq!(user.id, user_id);
+    assert_eq!(user.email, "test@example.com");
+    let result = service
+        .request_email_change(user_id, "test@EXAMPLE.com")
+        .await;
+    assert!(matches!(result, Err(ChangeEmailError::SameEmail)));
+}
+
 #[sqlx::test(migrator = "MIGRATOR")]
 async fn test_add_user_password_requirements_too_short(
     pool_options: SqlitePoolOptions,
@@ -187,7 +222,8 @@ async fn test_get_user_by_id(pool_options: SqlitePoolOptions, options: SqliteCon
     let id = service
         .add_user("getuser", "get@example.com", "ValidPassword123!")
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     // WHEN getting user by id
     let user = service.user_by_id(id).await.unwrap();
@@ -237,6 +273,7 @@ async fn test_change_password_success(
             "changepw@example.com",
             "OldPassword123!",
             "NewValidPassword123!",
+            None,
         )
         .await
         .unwrap();
@@ -247,6 +284,7 @@ async fn test_change_password_success(
             "changepw@example.com",
             "NewValidPassword123!",
             "AnotherNewPassword123!",
+            None,
         )
         .await
         .unwrap();
@@ -270,6 +308,7 @@ async fn test_change_password_wrong_current(
             "user@example.com",
             "WrongOldPassword",
             "NewValidPassword123!",
+            None,
         )
         .await;
 
@@ -280,6 +319,60 @@ async fn test_change_password_wrong_current(
     ));
 }
 
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_set_password_without_current(
+    pool_options: SqlitePoolOptions,
+    options: SqliteConnectOptions,
+) {
+    // GIVEN an admin and another user
+    let service = setup_service(pool_options, options).await;
+    service
+        .add_user("admin", "admin@example.com", "AdminPassword123!")
+        .await
+        .unwrap();
+    let user_id = service
+        .add_user("username", "user@example.com", "OldPassword123!")
+        .await
+        .unwrap()
+        .id;
+
+    // WHEN the admin sets a new password for the other user
+    service
+        .set_password(user_id, "NewValidPassword123!")
+        .await
+        .unwrap();
+
+    // THEN the old password doesn't work anymore and the new one does
+    let change_result = service
+        .change_password(
+            "user@example.com",
+            "OldPassword123!",
+            "AnotherNewPassword123!",
+            None,
+        )
+        .await;
+    assert!(matches!(
+        change_result,
+        Err(ChangePasswordError::WrongCurrentPassword)
+    ));
+    service
+        .change_password(
+            "user@example.com",
+            "NewValidPassword123!",
+            "AnotherNewPassword123!",
+            None,
+        )
+        .await
+        .unwrap();
+
+    // WHEN setting a password that is too weak, THEN it's an error
+    let set_result = service.set_password(user_id, "weak").await;
+    assert!(matches!(
+        set_result,
+        Err(ChangePasswordError::PasswordRequirements(_))
+    ));
+}
+
 #[sqlx::test(migrator = "MIGRATOR")]
 async fn test_change_password_no_user(
     pool_options: SqlitePoolOptions,
@@ -294,6 +387,7 @@ async fn test_change_password_no_user(
             "nouser@example.com",
             "WrongOldPassword",
             "NewValidPassword123!",
+            None,
         )
         .await;
 
@@ -303,3 +397,483 @@ async fn test_change_password_no_user(
         Err(ChangePasswordError::UserNotFound)
     ));
 }
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_first_user_is_admin(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN no users
+    let service = setup_service(pool_options, options).await;
+    assert!(!service.has_users().await.unwrap());
+
+    // WHEN adding two users
+    service
+        .add_user("first", "first@example.com", "ValidPassword123!")
+        .await
+        .unwrap();
+    service
+        .add_user("second", "second@example.com", "ValidPassword123!")
+        .await
+        .unwrap();
+
+    // THEN only the first one is an admin, the second one a viewer
+    assert!(service.has_users().await.unwrap());
+    let first = service.user_by_email("first@example.com").await.unwrap();
+    let second = service.user_by_email("second@example.com").await.unwrap();
+    assert_eq!(service.roles(first.id).await.unwrap(), [Role::Admin]);
+    assert_eq!(service.roles(second.id).await.unwrap(), [Role::Viewer]);
+    let manage_users = Permission::ManageUsers;
+    assert!(
+        service
+            .has_permission(first.id, manage_users)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !service
+            .has_permission(second.id, manage_users)
+            .await
+            .unwrap()
+    );
+
+    let permissions = service.permissions(second.id).await.unwrap();
+    assert_eq!(permissions, [Permission::ViewRecipes]);
+
+    // WHEN they get several roles, THEN they have the permissions of all of them
+    service
+        .set_roles(second.id, &[Role::Viewer, Role::Editor])
+        .await
+        .unwrap();
+    let permissions = service.permissions(second.id).await.unwrap();
+    assert_eq!(
+        permissions,
+        [Permission::EditRecipes, Permission::ViewRecipes]
+    );
+    let result = service.set_roles(99, &[Role::Viewer]).await;
+    assert!(matches!(result, Err(user_service::UserError::NotFound)));
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_verify_email(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a new user
+    let service = setup_service(pool_options, options).await;
+    let new_user = service
+        .add_user("new", "new@example.com", "ValidPassword123!")
+        .await
+        .unwrap();
+
+    // THEN their email address isn't verified
+    let user = service.user_by_id(new_user.id).await.unwrap();
+    assert!(!user.email_verified);
+
+    // WHEN verifying with a token that wasn't sent, THEN it is rejected
+    let result = service.verify_email("not-a-token").await;
+    assert!(matches!(result, Err(VerifyEmailError::InvalidToken)));
+
+    // WHEN a new token is sent, THEN the earlier one stops working
+    let token = service
+        .verification_token("new@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    let result = service.verify_email(&new_user.verification_token).await;
+    assert!(matches!(result, Err(VerifyEmailError::InvalidToken)));
+
+    // WHEN verifying with the new token
+    let user = service.verify_email(&token).await.unwrap();
+
+    // THEN their email address is verified and the token can't be used again
+    assert_eq!(user.id, new_user.id);
+    assert!(user.email_verified);
+    let result = service.verify_email(&token).await;
+    assert!(matches!(result, Err(VerifyEmailError::InvalidToken)));
+
+    // WHEN asking for another token, THEN there is none since they are verified
+    let token = service.verification_token("new@example.com").await.unwrap();
+    assert!(token.is_none());
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_reset_password(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a user who is logged in with two sessions
+    let service = setup_service(pool_options, options).await;
+    let user_id = service
+        .add_user("forgetful", "forgetful@example.com", "OldPassword123!")
+        .await
+        .unwrap()
+        .id;
+    service
+        .start_session("laptop", user_id, None, None)
+        .await
+        .unwrap();
+    service
+        .start_session("phone", user_id, None, None)
+        .await
+        .unwrap();
+    assert!(service.is_session_active("laptop", user_id).await.unwrap());
+
+    // WHEN a new reset token is issued, THEN the earlier one stops working
+    let earlier = service.password_reset_token(user_id).await.unwrap();
+    let token = service.password_reset_token(user_id).await.unwrap();
+    let result = service.reset_password(&earlier, "NewPassword123!").await;
+    assert!(matches!(result, Err(ResetPasswordError::InvalidToken)));
+
+    // WHEN resetting to a password that fails the requirements, THEN it is rejected
+    let result = service.reset_password(&token, "weak").await;
+    assert!(matches!(
+        result,
+        Err(ResetPasswordError::PasswordRequirements(_))
+    ));
+
+    // WHEN resetting with the token and a good password
+    let user = service
+        .reset_password(&token, "NewPassword123!")
+        .await
+        .unwrap();
+
+    // THEN the new password works, the token is used up and the sessions are ended
+    assert_eq!(user.id, user_id);
+    assert!(password_auth::verify_password("NewPassword123!", &user.password_hash).is_ok());
+    let result = service.reset_password(&token, "OtherPassword123!").await;
+    assert!(matches!(result, Err(ResetPasswordError::InvalidToken)));
+    assert!(!service.is_session_active("laptop", user_id).await.unwrap());
+    assert!(!service.is_session_active("phone", user_id).await.unwrap());
+
+    // WHEN changing the password on the laptop while a reset token and two sessions exist
+    let token = service.password_reset_token(user_id).await.unwrap();
+    for session_id in ["laptop", "phone"] {
+        service
+            .start_session(session_id, user_id, None, None)
+            .await
+            .unwrap();
+    }
+    service
+        .change_password(
+            "forgetful@example.com",
+            "NewPassword123!",
+            "NewerPassword123!",
+            Some("laptop"),
+        )
+        .await
+        .unwrap();
+
+    // THEN the token and the phone session are invalidated, but the laptop stays logged in
+    let result = service.reset_password(&token, "OtherPassword123!").await;
+    assert!(matches!(result, Err(ResetPasswordError::InvalidToken)));
+    assert!(service.is_session_active("laptop", user_id).await.unwrap());
+    assert!(!service.is_session_active("phone", user_id).await.unwrap());
+}
+
+#[test]
+fn test_totp_code() {
+    // The SHA-1 test vectors of RFC 6238, cut to six digits.
+    let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    assert_eq!(totp_code(secret, 59).unwrap(), "287082");
+    assert_eq!(totp_code(secret, 1_111_111_109).unwrap(), "081804");
+    assert_eq!(totp_code(secret, 20_000_000_000).unwrap(), "353130");
+    assert!(totp_code("not base32!", 59).is_none());
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_totp(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a user
+    let service = setup_service(pool_options, options).await;
+    let user_id = service
+        .add_user("careful", "careful+2fa@example.com", "ValidPassword123!")
+        .await
+        .unwrap()
+        .id;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    // WHEN starting to enable two-factor authentication
+    let enrolment = service.begin_totp(user_id).await.unwrap();
+
+    // THEN there is a secret for authenticator apps, which doesn't count until confirmed
+    assert!(
+        enrolment
+            .otpauth_uri
+            .starts_with("otpauth://totp/food:careful%2B2fa%40example.com?")
+    );
+    assert!(enrolment.otpauth_uri.contains(&enrolment.secret));
+    assert!(!service.has_totp(user_id).await.unwrap());
+    let result = service.verify_second_factor(user_id, "000000").await;
+    assert!(matches!(result, Err(TotpError::NotEnrolled)));
+
+    // WHEN confirming with a wrong code, THEN it is rejected
+    let result = service.confirm_totp(user_id, "12345").await;
+    assert!(matches!(result, Err(TotpError::InvalidCode)));
+
+    // WHEN confirming with the current code
+    let code = totp_code(&enrolment.secret, now).unwrap();
+    let recovery_codes = service.confirm_totp(user_id, &code).await.unwrap();
+
+    // THEN it is enabled and there are recovery codes
+    assert!(service.has_totp(user_id).await.unwrap());
+    assert_eq!(recovery_codes.len(), 10);
+    let result = service.begin_totp(user_id).await;
+    assert!(matches!(result, Err(TotpError::AlreadyEnabled)));
+
+    // WHEN using the same code again, THEN it is rejected, but the next one works
+    let result = service.verify_second_factor(user_id, &code).await;
+    assert!(matches!(result, Err(TotpError::InvalidCode)));
+    let next_code = totp_code(&enrolment.secret, now + 30).unwrap();
+    service
+        .verify_second_factor(user_id, &next_code)
+        .await
+        .unwrap();
+
+    // WHEN using a recovery code, THEN it works once
+    let recovery_code = recovery_codes[0].to_uppercase();
+    service
+        .verify_second_factor(user_id, &recovery_code)
+        .await
+        .unwrap();
+    let result = service.verify_second_factor(user_id, &recovery_code).await;
+    assert!(matches!(result, Err(TotpError::InvalidCode)));
+
+    // WHEN disabling it with another recovery code, THEN it is disabled
+    service
+        .disable_totp(user_id, &recovery_codes[1])
+        .await
+        .unwrap();
+    assert!(!service.has_totp(user_id).await.unwrap());
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_api_tokens(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN an admin and an editor
+    let service = setup_service(pool_options, options).await;
+    let admin_id = service
+        .add_user("admin", "admin@example.com", "ValidPassword123!")
+        .await
+        .unwrap()
+        .id;
+    let editor_id = service
+        .add_user("editor", "editor@example.com", "ValidPassword123!")
+        .await
+        .unwrap()
+        .id;
+
+    // WHEN the editor asks for a token to manage users, THEN they don't get it
+    let result = service
+        .create_api_token(editor_id, "sneaky", &[Permission::ManageUsers], None)
+        .await;
+    assert!(matches!(
+        result,
+        Err(ApiTokenError::PermissionNotHeld(Permission::ManageUsers))
+    ));
+    let result = service.create_api_token(editor_id, " ", &[], None).await;
+    assert!(matches!(result, Err(ApiTokenError::EmptyName)));
+
+    // WHEN the admin creates a token for managing users and editing recipes
+    let new_token = service
+        .create_api_token(
+            admin_id,
+            "cli",
+            &[Permission::ManageUsers, Permission::EditRecipes],
+            Some(30),
+        )
+        .await
+        .unwrap();
+
+    // THEN it is listed without its secret, and it acts as the admin with those permissions
+    assert!(new_token.secret.starts_with("food_"));
+    let tokens = service.api_tokens(admin_id).await.unwrap();
+    assert_eq!(tokens, std::slice::from_ref(&new_token.api_token));
+    assert_eq!(
+        tokens[0].permissions,
+        [Permission::EditRecipes, Permission::ManageUsers]
+    );
+    assert!(tokens[0].last_used_at.is_none());
+    assert!(tokens[0].expires_at.is_some());
+    let grant = service
+        .authenticate_api_token(&new_token.secret)
+        .await
+        .unwrap();
+    assert_eq!(grant.user_id, admin_id);
+    assert_eq!(
+        grant.permissions,
+        [Permission::EditRecipes, Permission::ManageUsers]
+    );
+    let tokens = service.api_tokens(admin_id).await.unwrap();
+    assert!(tokens[0].last_used_at.is_some());
+
+    // WHEN the admin becomes a viewer, THEN the token loses what they can't do anymore
+    service.set_roles(admin_id, &[Role::Viewer]).await.unwrap();
+    let grant = service
+        .authenticate_api_token(&new_token.secret)
+        .await
+        .unwrap();
+    assert!(grant.permissions.is_empty());
+
+    // WHEN someone else revokes it, THEN it isn't found, but its user can revoke it
+    let token_id = new_token.api_token.id;
+    let result = service.revoke_api_token(editor_id, token_id).await;
+    assert!(matches!(result, Err(ApiTokenError::NotFound)));
+    service.revoke_api_token(admin_id, token_id).await.unwrap();
+
+    // THEN it stops working
+    let result = service.authenticate_api_token(&new_token.secret).await;
+    assert!(matches!(result, Err(ApiTokenError::InvalidToken)));
+    assert!(service.api_tokens(admin_id).await.unwrap().is_empty());
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_sessions(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN a user logged in on a laptop and a phone, and another user
+    let service = setup_service(pool_options, options).await;
+    let user_id = service
+        .add_user("user", "user@example.com", "ValidPassword123!")
+        .await
+        .unwrap()
+        .id;
+    let other_id = service
+        .add_user("other", "other@example.com", "ValidPassword123!")
+        .await
+        .unwrap()
+        .id;
+    let ip = "192.0.2.1".parse().unwrap();
+    service
+        .start_session("laptop", user_id, Some("Firefox"), Some(ip))
+        .await
+        .unwrap();
+    service
+        .start_session("phone", user_id, None, None)
+        .await
+        .unwrap();
+    service
+        .start_session("tablet", other_id, None, None)
+        .await
+        .unwrap();
+
+    // WHEN listing the sessions of the user, THEN both are there with where they are from
+    let sessions = service.sessions(user_id).await.unwrap();
+    assert_eq!(sessions.len(), 2);
+    let laptop = sessions
+        .iter()
+        .find(|session| session.session_id == "laptop")
+        .unwrap();
+    assert_eq!(laptop.user_agent.as_deref(), Some("Firefox"));
+    assert_eq!(laptop.ip.as_deref(), Some("192.0.2.1"));
+
+    // WHEN the other user ends a session of the user, THEN it isn't found
+    let result = service.end_session_by_id(other_id, laptop.id).await;
+    assert!(matches!(result, Err(UserError::NotFound)));
+
+    // WHEN the user ends the laptop session, THEN only the phone is logged in
+    let session_id = service.end_session_by_id(user_id, laptop.id).await.unwrap();
+    assert_eq!(session_id, "laptop");
+    assert!(!service.is_session_active("laptop", user_id).await.unwrap());
+    assert!(service.is_session_active("phone", user_id).await.unwrap());
+
+    // WHEN the user logs out all other sessions from the laptop again
+    service
+        .start_session("laptop", user_id, None, None)
+        .await
+        .unwrap();
+    service.end_other_sessions(user_id, "laptop").await.unwrap();
+
+    // THEN only the laptop is logged in, and the other user is unaffected
+    assert!(service.is_session_active("laptop", user_id).await.unwrap());
+    assert!(!service.is_session_active("phone", user_id).await.unwrap());
+    assert!(service.is_session_active("tablet", other_id).await.unwrap());
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_change_profile(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN two users
+    let service = setup_service(pool_options, options).await;
+    let user_id = service
+        .add_user("user", "user@example.com", "ValidPassword123!")
+        .await
+        .unwrap()
+        .id;
+    service
+        .add_user("other", "other@example.com", "ValidPassword123!")
+        .await
+        .unwrap();
+
+    // WHEN the user changes their name, THEN it changes unless it is empty
+    let user = service.change_username(user_id, " cook ").await.unwrap();
+    assert_eq!(user.username, "cook");
+    let result = service.change_username(user_id, "  ").await;
+    assert!(matches!(result, Err(ChangeUsernameError::EmptyUsername)));
+
+    // WHEN the user asks for an address that is taken, or their own, THEN they are refused
+    let result = service
+        .request_email_change(user_id, "other@example.com")
+        .await;
+    assert!(matches!(result, Err(ChangeEmailError::AlreadyExists)));
+    let result = service
+        .request_email_change(user_id, "user@example.com")
+        .await;
+    assert!(matches!(result, Err(ChangeEmailError::SameEmail)));
+
+    // WHEN the user asks for a new address
+    let token = service
+        .request_email_change(user_id, "new@example.com")
+        .await
+        .unwrap();
+
+    // THEN it is pending, and the old address stays until the new one is confirmed
+    let user = service.user_by_id(user_id).await.unwrap();
+    assert_eq!(user.email, "user@example.com");
+    assert_eq!(user.pending_email.as_deref(), Some("new@example.com"));
+    let result = service.confirm_email_change("wrong").await;
+    assert!(matches!(result, Err(ChangeEmailError::InvalidToken)));
+    let user = service.confirm_email_change(&token).await.unwrap();
+    assert_eq!(user.email, "new@example.com");
+    assert!(user.pending_email.is_none());
+    assert!(user.email_verified);
+    assert!(service.user_by_email("user@example.com").await.is_err());
+
+    // WHEN another user takes the address before a change to it is confirmed
+    let token = service
+        .request_email_change(user_id, "late@example.com")
+        .await
+        .unwrap();
+    service
+        .add_user("quick", "late@example.com", "ValidPassword123!")
+        .await
+        .unwrap();
+
+    // THEN the change fails and the user keeps their address
+    let result = service.confirm_email_change(&token).await;
+    assert!(matches!(result, Err(ChangeEmailError::AlreadyExists)));
+    let user = service.user_by_id(user_id).await.unwrap();
+    assert_eq!(user.email, "new@example.com");
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn test_mail_cooldown(pool_options: SqlitePoolOptions, options: SqliteConnectOptions) {
+    // GIVEN an address that was just sent a password reset mail
+    let service = setup_service(pool_options, options).await;
+    assert!(
+        service
+            .claim_mail("user@example.com", MailKind::ResetPassword)
+            .await
+            .unwrap()
+    );
+
+    // WHEN another one is asked for right away
+    let result = service
+        .claim_mail("user@example.com", MailKind::ResetPassword)
+        .await
+        .unwrap();
+
+    // THEN it isn't sent, but other mails and addresses aren't held up
+    assert!(!result);
+    assert!(
+        service
+            .claim_mail("user@example.com", MailKind::VerifyEmail)
+            .await
+            .unwrap()
+    );
+    assert!(
+        service
+            .claim_mail("other@example.com", MailKind::ResetPassword)
+            .await
+            .unwrap()
+    );
+}
@@ -1,2 +1,11 @@
+pub mod collection;
+pub mod equipment;
+pub mod household;
+pub mod meal_plan;
+pub mod pantry;
+pub mod price;
 pub mod recipe;
+pub mod revision;
+pub mod shopping_list;
+pub mod substitution;
 pub mod user;
@@ -0,0 +1,226 @@
+use dioxus::prelude::*;
+use dioxus_fullstack::AsStatusCode;
+#[cfg(feature = "server")]
+use dioxus_fullstack::extract::State;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
+use crate::models;
+
+#[get("/api/collections", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn listing() -> Result<Vec<models::CollectionListing>, CollectionError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let collections = server_state.recipe.collections(viewer).await?;
+    Ok(collections.into_iter().map(Into::into).collect())
+}
+
+#[get("/api/collection", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn by_id(collection_id: i64) -> Result<models::Collection, CollectionError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let collection = server_state
+        .recipe
+        .collection(collection_id, viewer)
+        .await?;
+    Ok(collection.into())
+}
+
+#[post("/api/collection/create", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn create(name: String, description: String) -> Result<i64, CollectionError> {
+    let user = auth::require_writer(&auth).await?;
+    let id = server_state
+        .recipe
+        .create_collection(user.id, &name, &description)
+        .await?;
+    Ok(id)
+}
+
+#[post("/api/collection/update", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn update(
+    collection_id: i64,
+    changes: models::CollectionUpdate,
+) -> Result<(), CollectionError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .update_collection(user.id, collection_id, &changes.into())
+        .await?;
+    Ok(())
+}
+
+#[post("/api/collection/delete", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn delete(collection_id: i64) -> Result<(), CollectionError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .delete_collection(user.id, collection_id)
+        .await?;
+    Ok(())
+}
+
+#[post("/api/collection/add-recipe", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn add_recipe(collection_id: i64, recipe_id: i64) -> Result<(), CollectionError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .add_to_collection(user.id, collection_id, recipe_id)
+        .await?;
+    Ok(())
+}
+
+#[post("/api/collection/remove-recipe", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn remove_recipe(collection_id: i64, recipe_id: i64) -> Result<(), CollectionError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .remove_from_collection(user.id, collection_id, recipe_id)
+        .await?;
+    Ok(())
+}
+
+#[post("/api/collection/reorder", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn reorder(collection_id: i64, recipe_ids: Vec<i64>) -> Result<(), CollectionError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .reorder_collection(user.id, collection_id, &recipe_ids)
+        .await?;
+    Ok(())
+}
+
+#[get("/api/collection/export", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn export(
+    collection_id: i64,
+    format: models::RecipeFormat,
+) -> Result<String, CollectionError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let exported = server_state
+        .recipe
+        .export_collection(collection_id, viewer, format.into())
+        .await?;
+    Ok(exported)
+}
+
+pub use error::*;
+mod error {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum CollectionError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("only the owner can change a collection")]
+        Forbidden,
+        #[error("collection not found")]
+        NotFound,
+        #[error("a collection needs a name")]
+        EmptyName,
+        #[error("the new order must contain every recipe in the collection")]
+        InvalidOrder,
+        #[error("a household is required")]
+        MissingHousehold,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for CollectionError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::EmptyName | Self::InvalidOrder | Self::MissingHousehold => {
+                    StatusCode::BAD_REQUEST
+                }
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+
+    impl From<recipe_service::CollectionListing> for models::CollectionListing {
+        fn from(collection: recipe_service::CollectionListing) -> Self {
+            Self {
+                id: collection.id,
+                name: collection.name,
+                description: collection.description,
+                owner_username: collection.owner_username,
+                is_own: collection.is_own,
+            }
+        }
+    }
+
+    impl From<recipe_service::Collection> for models::Collection {
+        fn from(collection: recipe_service::Collection) -> Self {
+            Self {
+                id: collection.id,
+                name: collection.name,
+                description: collection.description,
+                owner_username: collection.owner_username,
+                visibility: collection.visibility.into(),
+                is_own: collection.is_own,
+                recipes: collection.recipes.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<models::CollectionUpdate> for recipe_service::CollectionUpdate {
+        fn from(update: models::CollectionUpdate) -> Self {
+            Self {
+                name: update.name,
+                description: update.description,
+                visibility: update.visibility.into(),
+                household_id: update.household_id,
+            }
+        }
+    }
+
+    impl From<models::RecipeFormat> for recipe_service::RecipeFormat {
+        fn from(format: models::RecipeFormat) -> Self {
+            match format {
+                models::RecipeFormat::Markdown => Self::Markdown,
+                models::RecipeFormat::Json => Self::Json,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for CollectionError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for CollectionError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<recipe_service::CollectionError> for CollectionError {
+        fn from(err: recipe_service::CollectionError) -> Self {
+            use recipe_service::CollectionError as ServiceError;
+            match err {
+                ServiceError::NotFound => Self::NotFound,
+                ServiceError::NotOwner | ServiceError::NotHouseholdMember => Self::Forbidden,
+                ServiceError::EmptyName => Self::EmptyName,
+                ServiceError::InvalidOrder => Self::InvalidOrder,
+                ServiceError::MissingHousehold => Self::MissingHousehold,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use dioxus::prelude::*;
+use dioxus_fullstack::AsStatusCode;
+#[cfg(feature = "server")]
+use dioxus_fullstack::extract::State;
+
+#[cfg(feature = "server")]
+use user_service::Permission;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
+use crate::models;
+
+/// All the equipment recipes can need, and whether the user owns it.
+#[get("/api/equipment", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn listing() -> Result<Vec<models::Equipment>, EquipmentError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let equipment = server_state.recipe.equipment(viewer).await?;
+    Ok(equipment.into_iter().map(Into::into).collect())
+}
+
+#[post("/api/equipment/owned", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn set_owned(equipment_id: i64, owned: bool) -> Result<(), EquipmentError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .set_equipment_owned(user.id, equipment_id, owned)
+        .await?;
+    Ok(())
+}
+
+/// Replace the equipment a recipe needs.
+#[post("/api/recipe/equipment", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn set_for_recipe(recipe_id: i64, equipment: Vec<String>) -> Result<(), EquipmentError> {
+    let user = auth::require(&auth, &[Permission::EditRecipes]).await?;
+    server_state
+        .recipe
+        .set_recipe_equipment(recipe_id, user.id, &equipment)
+        .await?;
+    Ok(())
+}
+
+pub use error::*;
+mod error {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum EquipmentError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to change the equipment of this recipe")]
+        Forbidden,
+        #[error("not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for EquipmentError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+
+    impl From<recipe_service::Equipment> for models::Equipment {
+        fn from(equipment: recipe_service::Equipment) -> Self {
+            Self {
+                id: equipment.id,
+                name: equipment.name,
+                owned: equipment.owned,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for EquipmentError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for EquipmentError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<recipe_service::EquipmentError> for EquipmentError {
+        fn from(err: recipe_service::EquipmentError) -> Self {
+            use recipe_service::EquipmentError as ServiceError;
+            match err {
+                ServiceError::NotFound => Self::NotFound,
+                ServiceError::NotOwner => Self::Forbidden,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use dioxus::prelude::*;
+use dioxus_fullstack::AsStatusCode;
+#[cfg(feature = "server")]
+use dioxus_fullstack::extract::State;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
+use crate::models;
+
+/// The households of the logged in user.
+#[get("/api/households", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn listing() -> Result<Vec<models::Household>, HouseholdError> {
+    let user = auth::require_user(&auth)?;
+    let households = server_state.recipe.households(user.id).await?;
+    Ok(households.into_iter().map(Into::into).collect())
+}
+
+#[post("/api/household/create", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn create(name: String) -> Result<models::Household, HouseholdError> {
+    let user = auth::require_writer(&auth).await?;
+    let household = server_state.recipe.create_household(user.id, &name).await?;
+    Ok(household.into())
+}
+
+#[post("/api/household/join", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn join(invite_code: String) -> Result<models::Household, HouseholdError> {
+    let user = auth::require_writer(&auth).await?;
+    let household = server_state
+        .recipe
+        .join_household(user.id, &invite_code)
+        .await?;
+    Ok(household.into())
+}
+
+/// Leave a household, what the user shared with it becomes private.
+#[post("/api/household/leave", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn leave(household_id: i64) -> Result<(), HouseholdError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .leave_household(user.id, household_id)
+        .await?;
+    Ok(())
+}
+
+pub use error::*;
+mod error {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum HouseholdError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed")]
+        Forbidden,
+        #[error("a household needs a name")]
+        EmptyName,
+        #[error("no household has this invite code")]
+        InvalidInviteCode,
+        #[error("not a member of the household")]
+        NotMember,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for HouseholdError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden | Self::NotMember => StatusCode::FORBIDDEN,
+                Self::EmptyName | Self::InvalidInviteCode => StatusCode::BAD_REQUEST,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+
+    impl From<recipe_service::Household> for models::Household {
+        fn from(household: recipe_service::Household) -> Self {
+            Self {
+                id: household.id,
+                name: household.name,
+                invite_code: household.invite_code,
+                members: household.members,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for HouseholdError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for HouseholdError {
+        fn from(_: recipe_service::RecipeError) -> Self {
+            Self::Internal
+        }
+    }
+
+    impl From<recipe_service::HouseholdError> for HouseholdError {
+        fn from(err: recipe_service::HouseholdError) -> Self {
+            use recipe_service::HouseholdError as ServiceError;
+            match err {
+                ServiceError::EmptyName => Self::EmptyName,
+                ServiceError::InvalidInviteCode => Self::InvalidInviteCode,
+                ServiceError::NotMember => Self::NotMember,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use chrono::NaiveDate;
+use dioxus::prelude::*;
+use dioxus_fullstack::AsStatusCode;
+#[cfg(feature = "server")]
+use dioxus_fullstack::extract::State;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
+use crate::models;
+
+/// The planned meals of the session user between `from` and `to`, both inclusive.
+#[get("/api/plan", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn plan(from: NaiveDate, to: NaiveDate) -> Result<Vec<models::MealPlanEntry>, PlanError> {
+    let user = auth::require_user(&auth)?;
+    let entries = server_state.meal_plan.plan(user.id, from, to).await?;
+    Ok(entries.into_iter().map(Into::into).collect())
+}
+
+#[post("/api/plan/add", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn add_entry(
+    recipe_id: i64,
+    date: NaiveDate,
+    slot: models::MealSlot,
+    servings: u32,
+) -> Result<i64, PlanError> {
+    let user = auth::require_writer(&auth).await?;
+    let id = server_state
+        .meal_plan
+        .add_entry(user.id, recipe_id, date, slot.into(), servings)
+        .await?;
+    Ok(id)
+}
+
+#[post("/api/plan/move", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn move_entry(
+    entry_id: i64,
+    date: NaiveDate,
+    slot: models::MealSlot,
+) -> Result<(), PlanError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .meal_plan
+        .move_entry(user.id, entry_id, date, slot.into())
+        .await?;
+    Ok(())
+}
+
+#[post("/api/plan/servings", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn set_servings(entry_id: i64, servings: u32) -> Result<(), PlanError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .meal_plan
+        .set_servings(user.id, entry_id, servings)
+        .await?;
+    Ok(())
+}
+
+#[post("/api/plan/remove", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn remove_entry(entry_id: i64) -> Result<(), PlanError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .meal_plan
+        .remove_entry(user.id, entry_id)
+        .await?;
+    Ok(())
+}
+
+pub use error::*;
+mod error {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum PlanError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("the API token isn't allowed to change anything")]
+        Forbidden,
+        #[error("meal plan entry not found")]
+        NotFound,
+        #[error("recipe not found")]
+        RecipeNotFound,
+        #[error("at least one serving must be planned")]
+        InvalidServings,
+        #[error("invalid date range")]
+        InvalidRange,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for PlanError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound | Self::RecipeNotFound => StatusCode::NOT_FOUND,
+                Self::InvalidServings | Self::InvalidRange => StatusCode::BAD_REQUEST,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+
+    impl From<meal_plan_service::MealPlanEntry> for models::MealPlanEntry {
+        fn from(entry: meal_plan_service::MealPlanEntry) -> Self {
+            Self {
+                id: entry.id,
+                recipe_id: entry.recipe_id,
+                recipe_title: entry.recipe_title,
+                date: entry.date,
+                slot: entry.slot.into(),
+                servings: entry.servings,
+            }
+        }
+    }
+
+    impl From<meal_plan_service::MealSlot> for models::MealSlot {
+        fn from(slot: meal_plan_service::MealSlot) -> Self {
+            match slot {
+                meal_plan_service::MealSlot::Breakfast => Self::Breakfast,
+                meal_plan_service::MealSlot::Lunch => Self::Lunch,
+                meal_plan_service::MealSlot::Dinner => Self::Dinner,
+            }
+        }
+    }
+
+    impl From<models::MealSlot> for meal_plan_service::MealSlot {
+        fn from(slot: models::MealSlot) -> Self {
+            match slot {
+                models::MealSlot::Breakfast => Self::Breakfast,
+                models::MealSlot::Lunch => Self::Lunch,
+                models::MealSlot::Dinner => Self::Dinner,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for PlanError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<meal_plan_service::PlanError> for PlanError {
+        fn from(err: meal_plan_service::PlanError) -> Self {
+            use meal_plan_service::PlanError as ServiceError;
+            match err {
+                ServiceError::NotFound => Self::NotFound,
+                ServiceError::RecipeNotFound => Self::RecipeNotFound,
+                ServiceError::InvalidServings => Self::InvalidServings,
+                ServiceError::InvalidRange => Self::InvalidRange,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+}
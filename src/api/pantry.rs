@@ -0,0 +1,150 @@
+use chrono::NaiveDate;
+use dioxus::prelude::*;
+use dioxus_fullstack::AsStatusCode;
+#[cfg(feature = "server")]
+use dioxus_fullstack::extract::State;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
+use crate::models;
+
+#[get("/api/pantry", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn listing() -> Result<Vec<models::PantryItem>, PantryError> {
+    let user = auth::require_user(&auth)?;
+    let pantry = server_state.recipe.pantry(user.id).await?;
+    Ok(pantry.into_iter().map(Into::into).collect())
+}
+
+/// Add an ingredient to the pantry, or update how much is left of it.
+#[post("/api/pantry/set", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn set_item(
+    ingredient: String,
+    quantity: Option<(f64, String)>,
+    expires_on: Option<NaiveDate>,
+) -> Result<i64, PantryError> {
+    let user = auth::require_writer(&auth).await?;
+    let quantity = quantity
+        .as_ref()
+        .map(|(quantity, unit)| (*quantity, unit.as_str()));
+    let id = server_state
+        .recipe
+        .set_pantry_item(user.id, &ingredient, quantity, expires_on)
+        .await?;
+    Ok(id)
+}
+
+#[post("/api/pantry/remove", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn remove_item(item_id: i64) -> Result<(), PantryError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .remove_pantry_item(user.id, item_id)
+        .await?;
+    Ok(())
+}
+
+/// The recipes that can be cooked with what's in the pantry, best matches first.
+#[get("/api/pantry/recipes", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn what_can_i_cook() -> Result<Vec<models::PantryMatch>, PantryError> {
+    let user = auth::require_user(&auth)?;
+    let matches = server_state.recipe.what_can_i_cook(user.id).await?;
+    Ok(matches.into_iter().map(Into::into).collect())
+}
+
+pub use error::*;
+mod error {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum PantryError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("the API token isn't allowed to change anything")]
+        Forbidden,
+        #[error("pantry item not found")]
+        NotFound,
+        #[error("an ingredient is required")]
+        EmptyIngredient,
+        #[error("a quantity must be positive and have a unit")]
+        InvalidQuantity,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for PantryError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::EmptyIngredient | Self::InvalidQuantity => StatusCode::BAD_REQUEST,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+
+    impl From<recipe_service::PantryItem> for models::PantryItem {
+        fn from(item: recipe_service::PantryItem) -> Self {
+            Self {
+                id: item.id,
+                ingredient: item.ingredient,
+                quantity: item.quantity,
+                unit: item.unit,
+                expires_on: item.expires_on,
+                is_expired: item.is_expired,
+            }
+        }
+    }
+
+    impl From<recipe_service::PantryMatch> for models::PantryMatch {
+        fn from(pantry_match: recipe_service::PantryMatch) -> Self {
+            Self {
+                recipe_id: pantry_match.recipe_id,
+                title: pantry_match.title,
+                available: pantry_match.available,
+                total: pantry_match.total,
+                missing: pantry_match.missing,
+                substitutions: pantry_match.substitutions,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for PantryError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for PantryError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<recipe_service::PantryError> for PantryError {
+        fn from(err: recipe_service::PantryError) -> Self {
+            use recipe_service::PantryError as ServiceError;
+            match err {
+                ServiceError::EmptyIngredient => Self::EmptyIngredient,
+                ServiceError::InvalidQuantity => Self::InvalidQuantity,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+}
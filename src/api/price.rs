@@ -0,0 +1,132 @@
+use chrono::NaiveDate;
+use dioxus::prelude::*;
+use dioxus_fullstack::AsStatusCode;
+#[cfg(feature = "server")]
+use dioxus_fullstack::extract::State;
+
+#[cfg(feature = "server")]
+use user_service::Permission;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
+use crate::models;
+
+/// The prices that apply today, one per ingredient and store.
+#[get("/api/prices", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn current() -> Result<Vec<models::Price>, PriceError> {
+    auth::require_user(&auth)?;
+    let prices = server_state.recipe.current_prices().await?;
+    Ok(prices.into_iter().map(Into::into).collect())
+}
+
+/// Set the price of one `unit` of an ingredient in a store, from `effective_from` on.
+#[post("/api/prices/set", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn set(
+    ingredient: String,
+    store: String,
+    price: f64,
+    unit: String,
+    effective_from: NaiveDate,
+) -> Result<i64, PriceError> {
+    auth::require(&auth, &[Permission::EditRecipes]).await?;
+    let id = server_state
+        .recipe
+        .set_price(&ingredient, &store, price, &unit, effective_from)
+        .await?;
+    Ok(id)
+}
+
+pub use error::*;
+mod error {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum PriceError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to change prices")]
+        Forbidden,
+        #[error("an ingredient is required")]
+        EmptyIngredient,
+        #[error("a store is required")]
+        EmptyStore,
+        #[error("a price can't be negative")]
+        InvalidPrice,
+        #[error("unknown unit")]
+        UnknownUnit,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for PriceError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::EmptyIngredient
+                | Self::EmptyStore
+                | Self::InvalidPrice
+                | Self::UnknownUnit => StatusCode::BAD_REQUEST,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+
+    impl From<recipe_service::Price> for models::Price {
+        fn from(price: recipe_service::Price) -> Self {
+            Self {
+                id: price.id,
+                ingredient: price.ingredient,
+                store: price.store,
+                price: price.price,
+                unit: price.unit,
+                effective_from: price.effective_from,
+            }
+        }
+    }
+
+    impl From<recipe_service::CostEstimate> for models::CostEstimate {
+        fn from(estimate: recipe_service::CostEstimate) -> Self {
+            Self {
+                total: estimate.total,
+                unpriced: estimate.unpriced,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for PriceError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for PriceError {
+        fn from(_: recipe_service::RecipeError) -> Self {
+            Self::Internal
+        }
+    }
+
+    impl From<recipe_service::PriceError> for PriceError {
+        fn from(err: recipe_service::PriceError) -> Self {
+            use recipe_service::PriceError as ServiceError;
+            match err {
+                ServiceError::EmptyIngredient => Self::EmptyIngredient,
+                ServiceError::EmptyStore => Self::EmptyStore,
+                ServiceError::InvalidPrice => Self::InvalidPrice,
+                ServiceError::UnknownUnit => Self::UnknownUnit,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+}
@@ -4,19 +4,219 @@ use dioxus_fullstack::AsStatusCode;
 use dioxus_fullstack::extract::State;
 
 #[cfg(feature = "server")]
-use crate::backend::ServerState;
+use user_service::Permission;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
 use crate::models;
 
-#[get("/api/recipes", server_state: State<ServerState>)]
-pub async fn listing() -> Result<Vec<models::RecipeListing>, RecipeListingError> {
-    let recipe_listings = server_state.recipe.recipe_listing().await?;
+#[get("/api/recipes", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn listing(
+    query: models::ListingQuery,
+) -> Result<Vec<models::RecipeListing>, RecipeListingError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    if query.favorites_only && viewer.is_none() {
+        return Err(RecipeListingError::Unauthorized);
+    }
+    let recipe_listings = server_state
+        .recipe
+        .recipe_listing(viewer, &query.into())
+        .await?;
     Ok(recipe_listings.into_iter().map(Into::into).collect())
 }
 
-#[get("/api/recipe", server_state: State<ServerState>)]
+#[get("/api/recipe", auth: auth::Session, server_state: State<ServerState>)]
 pub async fn by_id(recipe_id: i64) -> Result<models::Recipe, RecipeByIdError> {
-    let recipe = server_state.recipe.recipe(recipe_id).await?;
-    Ok(recipe.into())
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let recipe = server_state.recipe.recipe(recipe_id, viewer).await?;
+    let is_own = viewer.is_some() && recipe.owner_id == viewer;
+    Ok(models::Recipe {
+        is_own,
+        ..recipe.into()
+    })
+}
+
+/// The calories and macronutrients of a recipe, in total and per serving.
+#[get("/api/recipe/nutrition", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn nutrition(recipe_id: i64) -> Result<models::RecipeNutrition, RecipeByIdError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let nutrition = server_state.recipe.nutrition(recipe_id, viewer).await?;
+    Ok(nutrition.into())
+}
+
+/// What a recipe costs with today's prices, in total and per serving.
+#[get("/api/recipe/cost", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn cost(recipe_id: i64) -> Result<models::RecipeCost, RecipeByIdError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let cost = server_state.recipe.recipe_cost(recipe_id, viewer).await?;
+    Ok(cost.into())
+}
+
+/// The variations of a recipe, by title.
+#[get("/api/recipe/variations", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn variations(recipe_id: i64) -> Result<Vec<models::RecipeListing>, RecipeByIdError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let recipe_listings = server_state.recipe.variations(recipe_id, viewer).await?;
+    Ok(recipe_listings.into_iter().map(Into::into).collect())
+}
+
+/// Copy a recipe into a private variation of the current user, returning the id of the copy.
+#[post("/api/recipe/fork", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip(auth, server_state), err)]
+pub async fn fork(recipe_id: i64) -> Result<i64, ForkError> {
+    let user = auth::require(&auth, &[Permission::EditRecipes]).await?;
+    let id = server_state.recipe.fork_recipe(recipe_id, user.id).await?;
+    Ok(id)
+}
+
+#[get("/api/recipes/favorites", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn favorites() -> Result<Vec<models::RecipeListing>, FavoriteError> {
+    let user = auth::require_user(&auth)?;
+    let recipe_listings = server_state.recipe.favorites(user.id).await?;
+    Ok(recipe_listings.into_iter().map(Into::into).collect())
+}
+
+#[get("/api/recipe/favorite", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn is_favorite(recipe_id: i64) -> Result<bool, FavoriteError> {
+    let user = auth::require_user(&auth)?;
+    Ok(server_state.recipe.is_favorite(user.id, recipe_id).await?)
+}
+
+#[post("/api/recipe/favorite/add", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn add_favorite(recipe_id: i64) -> Result<(), FavoriteError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state.recipe.add_favorite(user.id, recipe_id).await?;
+    Ok(())
+}
+
+#[post("/api/recipe/favorite/remove", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn remove_favorite(recipe_id: i64) -> Result<(), FavoriteError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .remove_favorite(user.id, recipe_id)
+        .await?;
+    Ok(())
+}
+
+#[get("/api/recipe/reviews", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn reviews(recipe_id: i64) -> Result<Vec<models::Review>, ReviewError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let reviews = server_state.recipe.reviews(recipe_id, viewer).await?;
+    Ok(reviews.into_iter().map(Into::into).collect())
+}
+
+#[post("/api/recipe/review", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn set_review(
+    recipe_id: i64,
+    rating: u8,
+    text: Option<String>,
+) -> Result<(), ReviewError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .set_review(user.id, recipe_id, rating, text.as_deref())
+        .await?;
+    Ok(())
+}
+
+#[post("/api/recipe/review/delete", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn delete_review(recipe_id: i64) -> Result<(), ReviewError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .delete_review(user.id, recipe_id)
+        .await?;
+    Ok(())
+}
+
+#[get("/api/recipe/cooking-log", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn cooking_log(recipe_id: i64) -> Result<Vec<models::CookingLogEntry>, CookingLogError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let cooking_log = server_state.recipe.cooking_log(recipe_id, viewer).await?;
+    Ok(cooking_log.into_iter().map(Into::into).collect())
+}
+
+#[post("/api/recipe/cooking-log", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn log_cooking(
+    recipe_id: i64,
+    cooked_on: chrono::NaiveDate,
+    servings: u32,
+    notes: Option<String>,
+) -> Result<(), CookingLogError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .log_cooking(user.id, recipe_id, cooked_on, servings, notes.as_deref())
+        .await?;
+    Ok(())
+}
+
+#[post("/api/recipe/cooking-log/delete", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn delete_cooking_log_entry(entry_id: i64) -> Result<(), CookingLogError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .delete_cooking_log_entry(user.id, entry_id)
+        .await?;
+    Ok(())
+}
+
+/// How many recipes to show in the lists of cooked recipes.
+#[cfg(feature = "server")]
+const COOKED_RECIPES_LIMIT: u32 = 5;
+
+#[get("/api/recipes/recently-cooked", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn recently_cooked() -> Result<Vec<models::CookedRecipe>, CookingLogError> {
+    let user = auth::require_user(&auth)?;
+    let recipes = server_state
+        .recipe
+        .recently_cooked(user.id, COOKED_RECIPES_LIMIT)
+        .await?;
+    Ok(recipes.into_iter().map(Into::into).collect())
+}
+
+#[get("/api/recipes/not-cooked-lately", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn not_cooked_lately() -> Result<Vec<models::CookedRecipe>, CookingLogError> {
+    let user = auth::require_user(&auth)?;
+    let recipes = server_state
+        .recipe
+        .not_cooked_lately(user.id, COOKED_RECIPES_LIMIT)
+        .await?;
+    Ok(recipes.into_iter().map(Into::into).collect())
+}
+
+#[post("/api/recipe/visibility", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip(auth, server_state), err)]
+pub async fn set_visibility(
+    recipe_id: i64,
+    visibility: models::Visibility,
+    household_id: Option<i64>,
+) -> Result<(), SetVisibilityError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .recipe
+        .set_visibility(recipe_id, user.id, visibility.into(), household_id)
+        .await?;
+    Ok(())
+}
+
+/// Hand a recipe to the user with `owner_email`, mostly for recipes from before recipes had
+/// owners. Only user managers can do this.
+#[post("/api/recipe/owner", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip(auth, server_state), err)]
+pub async fn set_owner(recipe_id: i64, owner_email: String) -> Result<(), SetOwnerError> {
+    auth::require(&auth, &[Permission::ManageUsers]).await?;
+    let owner = server_state
+        .user
+        .user_by_email(&owner_email)
+        .await
+        .map_err(|err| match err {
+            user_service::UserError::NotFound => SetOwnerError::UnknownUser,
+            _ => SetOwnerError::Internal,
+        })?;
+    server_state.recipe.set_owner(recipe_id, owner.id).await?;
+    Ok(())
 }
 
 pub use error::*;
@@ -25,6 +225,8 @@ mod error {
 
     #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
     pub enum RecipeListingError {
+        #[error("not logged in")]
+        Unauthorized,
         #[error("internal error")]
         Internal,
         #[error("internal server error")]
@@ -34,6 +236,7 @@ mod error {
     impl AsStatusCode for RecipeListingError {
         fn as_status_code(&self) -> StatusCode {
             match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
                 Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
                 Self::ServerFnError(err) => err.as_status_code(),
             }
@@ -59,6 +262,177 @@ mod error {
             }
         }
     }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum FavoriteError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("the API token isn't allowed to change anything")]
+        Forbidden,
+        #[error("recipe not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for FavoriteError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum ForkError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to create recipes")]
+        Forbidden,
+        #[error("recipe not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for ForkError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum ReviewError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("the API token isn't allowed to change anything")]
+        Forbidden,
+        #[error("a rating must be between 1 and 5")]
+        InvalidRating,
+        #[error("recipe not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for ReviewError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::InvalidRating => StatusCode::BAD_REQUEST,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum CookingLogError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("the API token isn't allowed to change anything")]
+        Forbidden,
+        #[error("at least one serving must be cooked")]
+        InvalidServings,
+        #[error("not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for CookingLogError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::InvalidServings => StatusCode::BAD_REQUEST,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum SetVisibilityError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to change the visibility of this recipe")]
+        Forbidden,
+        #[error("a household is required")]
+        MissingHousehold,
+        #[error("recipe not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for SetVisibilityError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::MissingHousehold => StatusCode::BAD_REQUEST,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum SetOwnerError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to change the owner of a recipe")]
+        Forbidden,
+        #[error("recipe not found")]
+        NotFound,
+        #[error("no user has this e-mail address")]
+        UnknownUser,
+        #[error("the new owner already has a recipe with this title")]
+        DuplicateTitle,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for SetOwnerError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::UnknownUser => StatusCode::BAD_REQUEST,
+                Self::DuplicateTitle => StatusCode::CONFLICT,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
 }
 
 #[cfg(feature = "server")]
@@ -70,6 +444,35 @@ mod server {
             Self {
                 id: recipe_listing.id,
                 title: recipe_listing.title,
+                average_rating: recipe_listing.average_rating,
+                rating_count: recipe_listing.rating_count,
+            }
+        }
+    }
+
+    impl From<models::ListingQuery> for recipe_service::ListingQuery {
+        fn from(query: models::ListingQuery) -> Self {
+            Self {
+                favorites_only: query.favorites_only,
+                variations_of: None,
+                owned_equipment_only: query.owned_equipment_only,
+                sort: match query.sort {
+                    models::ListingSort::Title => recipe_service::ListingSort::Title,
+                    models::ListingSort::Rating => recipe_service::ListingSort::Rating,
+                },
+            }
+        }
+    }
+
+    impl From<recipe_service::Review> for models::Review {
+        fn from(review: recipe_service::Review) -> Self {
+            Self {
+                id: review.id,
+                username: review.username,
+                rating: review.rating,
+                text: review.text,
+                creation_date: review.creation_date,
+                is_own: review.is_own,
             }
         }
     }
@@ -80,6 +483,18 @@ mod server {
                 quantity: ingredient.quantity,
                 unit: ingredient.unit,
                 name: ingredient.name,
+                sub_recipe_id: ingredient.sub_recipe_id,
+            }
+        }
+    }
+
+    impl From<models::Ingredient> for recipe_service::Ingredient {
+        fn from(ingredient: models::Ingredient) -> Self {
+            Self {
+                quantity: ingredient.quantity,
+                unit: ingredient.unit,
+                name: ingredient.name,
+                sub_recipe_id: ingredient.sub_recipe_id,
             }
         }
     }
@@ -92,9 +507,66 @@ mod server {
                 meal_type: recipe.meal_type,
                 source_name: recipe.source_name,
                 source_url: recipe.source_url,
+                servings: recipe.servings,
                 ingredients: recipe.ingredients.into_iter().map(Into::into).collect(),
                 instructions: recipe.instructions,
+                equipment: recipe.equipment,
                 creation_date: recipe.creation_date,
+                visibility: recipe.visibility.into(),
+                household_id: recipe.household_id,
+                forked_from: recipe.forked_from,
+                is_own: false,
+            }
+        }
+    }
+
+    impl From<recipe_service::Nutrients> for models::Nutrients {
+        fn from(nutrients: recipe_service::Nutrients) -> Self {
+            Self {
+                kcal: nutrients.kcal,
+                protein: nutrients.protein,
+                fat: nutrients.fat,
+                carbohydrates: nutrients.carbohydrates,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeNutrition> for models::RecipeNutrition {
+        fn from(nutrition: recipe_service::RecipeNutrition) -> Self {
+            Self {
+                total: nutrition.total.into(),
+                per_serving: nutrition.per_serving.into(),
+                unresolved: nutrition.unresolved,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeCost> for models::RecipeCost {
+        fn from(cost: recipe_service::RecipeCost) -> Self {
+            Self {
+                total: cost.total,
+                per_serving: cost.per_serving,
+                unpriced: cost.unpriced,
+            }
+        }
+    }
+
+    impl From<recipe_service::Visibility> for models::Visibility {
+        fn from(visibility: recipe_service::Visibility) -> Self {
+            match visibility {
+                recipe_service::Visibility::Private => Self::Private,
+                recipe_service::Visibility::Household => Self::Household,
+                recipe_service::Visibility::Public => Self::Public,
+            }
+        }
+    }
+
+    impl From<models::Visibility> for recipe_service::Visibility {
+        fn from(visibility: models::Visibility) -> Self {
+            match visibility {
+                models::Visibility::Private => Self::Private,
+                models::Visibility::Household => Self::Household,
+                models::Visibility::Public => Self::Public,
             }
         }
     }
@@ -105,6 +577,7 @@ mod server {
                 recipe_service::RecipeError::NotFound => Self::Internal, // Should not happen for listing
                 recipe_service::RecipeError::Database(_) => Self::Internal,
                 recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
             }
         }
     }
@@ -115,6 +588,173 @@ mod server {
                 recipe_service::RecipeError::NotFound => Self::NotFound,
                 recipe_service::RecipeError::Database(_) => Self::Internal,
                 recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for FavoriteError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for FavoriteError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for ForkError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for ForkError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for ReviewError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for ReviewError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<recipe_service::ReviewError> for ReviewError {
+        fn from(err: recipe_service::ReviewError) -> Self {
+            use recipe_service::ReviewError as ServiceError;
+            match err {
+                ServiceError::NotFound => Self::NotFound,
+                ServiceError::InvalidRating => Self::InvalidRating,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    impl From<recipe_service::CookingLogEntry> for models::CookingLogEntry {
+        fn from(entry: recipe_service::CookingLogEntry) -> Self {
+            Self {
+                id: entry.id,
+                username: entry.username,
+                cooked_on: entry.cooked_on,
+                servings: entry.servings,
+                notes: entry.notes,
+                is_own: entry.is_own,
+            }
+        }
+    }
+
+    impl From<recipe_service::CookedRecipe> for models::CookedRecipe {
+        fn from(recipe: recipe_service::CookedRecipe) -> Self {
+            Self {
+                id: recipe.id,
+                title: recipe.title,
+                last_cooked: recipe.last_cooked,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for CookingLogError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for CookingLogError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<recipe_service::CookingLogError> for CookingLogError {
+        fn from(err: recipe_service::CookingLogError) -> Self {
+            use recipe_service::CookingLogError as ServiceError;
+            match err {
+                ServiceError::NotFound => Self::NotFound,
+                ServiceError::InvalidServings => Self::InvalidServings,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for SetVisibilityError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::SetVisibilityError> for SetVisibilityError {
+        fn from(err: recipe_service::SetVisibilityError) -> Self {
+            use recipe_service::SetVisibilityError as ServiceError;
+            match err {
+                ServiceError::NotFound => Self::NotFound,
+                ServiceError::NotOwner | ServiceError::NotHouseholdMember => Self::Forbidden,
+                ServiceError::MissingHousehold => Self::MissingHousehold,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for SetOwnerError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for SetOwnerError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::DuplicateTitle,
             }
         }
     }
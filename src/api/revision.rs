@@ -0,0 +1,253 @@
+use dioxus::prelude::*;
+use dioxus_fullstack::AsStatusCode;
+#[cfg(feature = "server")]
+use dioxus_fullstack::extract::State;
+
+#[cfg(feature = "server")]
+use user_service::Permission;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
+use crate::models;
+
+/// Edit a recipe, keeping the edit as a new revision. Returns the number of the revision.
+#[post("/api/recipe/update", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip(auth, server_state), err)]
+pub async fn update(recipe_id: i64, content: models::RecipeContent) -> Result<u32, RevisionError> {
+    let user = auth::require(&auth, &[Permission::EditRecipes]).await?;
+    let number = server_state
+        .recipe
+        .update_recipe(recipe_id, user.id, &content.into())
+        .await?;
+    Ok(number)
+}
+
+/// The revisions of a recipe, newest first.
+#[get("/api/recipe/revisions", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn listing(recipe_id: i64) -> Result<Vec<models::RevisionListing>, RevisionError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let revisions = server_state.recipe.revisions(recipe_id, viewer).await?;
+    Ok(revisions.into_iter().map(Into::into).collect())
+}
+
+#[get("/api/recipe/revision", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn by_number(recipe_id: i64, number: u32) -> Result<models::Revision, RevisionError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let revision = server_state
+        .recipe
+        .revision(recipe_id, number, viewer)
+        .await?;
+    Ok(revision.into())
+}
+
+/// What changed from revision `from` to revision `to` of a recipe.
+#[get("/api/recipe/revision/diff", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn diff(
+    recipe_id: i64,
+    from: u32,
+    to: u32,
+) -> Result<models::RevisionDiff, RevisionError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let diff = server_state
+        .recipe
+        .revision_diff(recipe_id, from, to, viewer)
+        .await?;
+    Ok(diff.into())
+}
+
+/// Bring a recipe back to an older revision. Returns the number of the new revision.
+#[post("/api/recipe/revert", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip(auth, server_state), err)]
+pub async fn revert(recipe_id: i64, number: u32) -> Result<u32, RevisionError> {
+    let user = auth::require(&auth, &[Permission::EditRecipes]).await?;
+    let number = server_state
+        .recipe
+        .revert_recipe(recipe_id, user.id, number)
+        .await?;
+    Ok(number)
+}
+
+pub use error::*;
+mod error {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum RevisionError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to edit this recipe")]
+        Forbidden,
+        #[error("not found")]
+        NotFound,
+        #[error("a recipe needs a title")]
+        EmptyTitle,
+        #[error("you already have a recipe with this title")]
+        DuplicateTitle,
+        #[error("a recipe must serve at least one")]
+        InvalidServings,
+        #[error("an ingredient needs a name, a unit and a positive quantity")]
+        InvalidIngredient,
+        #[error("sub-recipe not found")]
+        SubRecipeNotFound,
+        #[error("a recipe can't use itself, not even through other recipes")]
+        Cycle,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for RevisionError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::EmptyTitle
+                | Self::InvalidServings
+                | Self::InvalidIngredient
+                | Self::SubRecipeNotFound
+                | Self::Cycle => StatusCode::BAD_REQUEST,
+                Self::DuplicateTitle => StatusCode::CONFLICT,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+
+    impl From<models::RecipeContent> for recipe_service::RecipeContent {
+        fn from(content: models::RecipeContent) -> Self {
+            Self {
+                title: content.title,
+                description: content.description,
+                servings: content.servings,
+                ingredients: content.ingredients.into_iter().map(Into::into).collect(),
+                instructions: content.instructions,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeContent> for models::RecipeContent {
+        fn from(content: recipe_service::RecipeContent) -> Self {
+            Self {
+                title: content.title,
+                description: content.description,
+                servings: content.servings,
+                ingredients: content.ingredients.into_iter().map(Into::into).collect(),
+                instructions: content.instructions,
+            }
+        }
+    }
+
+    impl From<recipe_service::RevisionListing> for models::RevisionListing {
+        fn from(revision: recipe_service::RevisionListing) -> Self {
+            Self {
+                number: revision.number,
+                author: revision.author,
+                created_at: revision.created_at,
+            }
+        }
+    }
+
+    impl From<recipe_service::Revision> for models::Revision {
+        fn from(revision: recipe_service::Revision) -> Self {
+            Self {
+                number: revision.number,
+                author: revision.author,
+                created_at: revision.created_at,
+                content: revision.content.into(),
+            }
+        }
+    }
+
+    impl From<recipe_service::RevisionDiff> for models::RevisionDiff {
+        fn from(diff: recipe_service::RevisionDiff) -> Self {
+            Self {
+                fields: diff.fields.into_iter().map(Into::into).collect(),
+                ingredients: diff.ingredients.into_iter().map(Into::into).collect(),
+                instructions: diff.instructions.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<recipe_service::FieldChange> for models::FieldChange {
+        fn from(change: recipe_service::FieldChange) -> Self {
+            Self {
+                field: match change.field {
+                    recipe_service::RecipeField::Title => models::RecipeField::Title,
+                    recipe_service::RecipeField::Description => models::RecipeField::Description,
+                    recipe_service::RecipeField::Servings => models::RecipeField::Servings,
+                },
+                old: change.old,
+                new: change.new,
+            }
+        }
+    }
+
+    impl From<recipe_service::IngredientChange> for models::IngredientChange {
+        fn from(change: recipe_service::IngredientChange) -> Self {
+            use recipe_service::IngredientChange as ServiceChange;
+            match change {
+                ServiceChange::Added(ingredient) => Self::Added(ingredient.into()),
+                ServiceChange::Removed(ingredient) => Self::Removed(ingredient.into()),
+                ServiceChange::Changed { old, new } => Self::Changed {
+                    old: old.into(),
+                    new: new.into(),
+                },
+            }
+        }
+    }
+
+    impl From<recipe_service::InstructionChange> for models::InstructionChange {
+        fn from(change: recipe_service::InstructionChange) -> Self {
+            use recipe_service::InstructionChange as ServiceChange;
+            match change {
+                ServiceChange::Added { step, text } => Self::Added { step, text },
+                ServiceChange::Removed { step, text } => Self::Removed { step, text },
+                ServiceChange::Edited { step, old, new } => Self::Edited { step, old, new },
+            }
+        }
+    }
+
+    impl From<auth::Denied> for RevisionError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for RevisionError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<recipe_service::EditError> for RevisionError {
+        fn from(err: recipe_service::EditError) -> Self {
+            use recipe_service::EditError as ServiceError;
+            match err {
+                ServiceError::NotFound => Self::NotFound,
+                ServiceError::NotOwner => Self::Forbidden,
+                ServiceError::EmptyTitle => Self::EmptyTitle,
+                ServiceError::DuplicateTitle => Self::DuplicateTitle,
+                ServiceError::InvalidServings => Self::InvalidServings,
+                ServiceError::InvalidIngredient => Self::InvalidIngredient,
+                ServiceError::SubRecipeNotFound => Self::SubRecipeNotFound,
+                ServiceError::Cycle => Self::Cycle,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+}
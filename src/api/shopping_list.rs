@@ -0,0 +1,204 @@
+use chrono::NaiveDate;
+use dioxus::prelude::*;
+use dioxus_fullstack::AsStatusCode;
+#[cfg(feature = "server")]
+use dioxus_fullstack::extract::State;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
+use crate::models;
+
+#[get("/api/shopping-lists", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn listing() -> Result<Vec<models::ShoppingListListing>, ShoppingListError> {
+    let user = auth::require_user(&auth)?;
+    let lists = server_state.shopping_list.lists(user.id).await?;
+    Ok(lists.into_iter().map(Into::into).collect())
+}
+
+#[get("/api/shopping-list", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn by_id(list_id: i64) -> Result<models::ShoppingList, ShoppingListError> {
+    let user = auth::require_user(&auth)?;
+    let list = server_state.shopping_list.list(user.id, list_id).await?;
+    Ok(list.into())
+}
+
+/// What the items that aren't checked off yet cost with today's prices.
+#[get("/api/shopping-list/cost", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn cost(list_id: i64) -> Result<models::CostEstimate, ShoppingListError> {
+    let user = auth::require_user(&auth)?;
+    let estimate = server_state.shopping_list.cost(user.id, list_id).await?;
+    Ok(estimate.into())
+}
+
+/// Create a shopping list for the given recipes, scaled to the given servings.
+#[post("/api/shopping-list/from-recipes", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn create_from_recipes(
+    name: String,
+    recipes: Vec<models::RecipeSelection>,
+) -> Result<i64, ShoppingListError> {
+    let user = auth::require_writer(&auth).await?;
+    let recipes: Vec<_> = recipes.into_iter().map(Into::into).collect();
+    let id = server_state
+        .shopping_list
+        .create_from_recipes(user.id, &name, &recipes)
+        .await?;
+    Ok(id)
+}
+
+/// Create a shopping list for the meals planned between `from` and `to`, both inclusive.
+#[post("/api/shopping-list/from-plan", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn create_from_plan(
+    name: String,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<i64, ShoppingListError> {
+    let user = auth::require_writer(&auth).await?;
+    let id = server_state
+        .shopping_list
+        .create_from_plan(user.id, &name, from, to)
+        .await?;
+    Ok(id)
+}
+
+#[post("/api/shopping-list/check", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn set_checked(item_id: i64, checked: bool) -> Result<(), ShoppingListError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .shopping_list
+        .set_checked(user.id, item_id, checked)
+        .await?;
+    Ok(())
+}
+
+#[post("/api/shopping-list/delete", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn delete(list_id: i64) -> Result<(), ShoppingListError> {
+    let user = auth::require_writer(&auth).await?;
+    server_state
+        .shopping_list
+        .delete_list(user.id, list_id)
+        .await?;
+    Ok(())
+}
+
+pub use error::*;
+mod error {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum ShoppingListError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("the API token isn't allowed to change anything")]
+        Forbidden,
+        #[error("shopping list not found")]
+        NotFound,
+        #[error("recipe not found")]
+        RecipeNotFound,
+        #[error("a shopping list needs a name")]
+        EmptyName,
+        #[error("at least one serving must be shopped for")]
+        InvalidServings,
+        #[error("invalid date range")]
+        InvalidRange,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for ShoppingListError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound | Self::RecipeNotFound => StatusCode::NOT_FOUND,
+                Self::EmptyName | Self::InvalidServings | Self::InvalidRange => {
+                    StatusCode::BAD_REQUEST
+                }
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+
+    impl From<shopping_list_service::ShoppingListListing> for models::ShoppingListListing {
+        fn from(list: shopping_list_service::ShoppingListListing) -> Self {
+            Self {
+                id: list.id,
+                name: list.name,
+                creation_date: list.creation_date,
+                item_count: list.item_count,
+                checked_count: list.checked_count,
+            }
+        }
+    }
+
+    impl From<shopping_list_service::ShoppingList> for models::ShoppingList {
+        fn from(list: shopping_list_service::ShoppingList) -> Self {
+            Self {
+                id: list.id,
+                name: list.name,
+                creation_date: list.creation_date,
+                items: list.items.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl From<shopping_list_service::ShoppingListItem> for models::ShoppingListItem {
+        fn from(item: shopping_list_service::ShoppingListItem) -> Self {
+            Self {
+                id: item.id,
+                name: item.name,
+                quantity: item.quantity,
+                unit: item.unit,
+                checked: item.checked,
+            }
+        }
+    }
+
+    impl From<models::RecipeSelection> for shopping_list_service::RecipeSelection {
+        fn from(selection: models::RecipeSelection) -> Self {
+            Self {
+                recipe_id: selection.recipe_id,
+                servings: selection.servings,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for ShoppingListError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<shopping_list_service::ShoppingListError> for ShoppingListError {
+        fn from(err: shopping_list_service::ShoppingListError) -> Self {
+            match err {
+                shopping_list_service::ShoppingListError::NotFound => Self::NotFound,
+                shopping_list_service::ShoppingListError::Database(_) => Self::Internal,
+                shopping_list_service::ShoppingListError::ConsistencyError => Self::Internal,
+            }
+        }
+    }
+
+    impl From<shopping_list_service::GenerateError> for ShoppingListError {
+        fn from(err: shopping_list_service::GenerateError) -> Self {
+            use shopping_list_service::GenerateError as ServiceError;
+            match err {
+                ServiceError::RecipeNotFound => Self::RecipeNotFound,
+                ServiceError::EmptyName => Self::EmptyName,
+                ServiceError::InvalidServings => Self::InvalidServings,
+                ServiceError::InvalidRange => Self::InvalidRange,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use dioxus::prelude::*;
+use dioxus_fullstack::AsStatusCode;
+#[cfg(feature = "server")]
+use dioxus_fullstack::extract::State;
+
+#[cfg(feature = "server")]
+use user_service::Permission;
+
+#[cfg(feature = "server")]
+use crate::backend::{ServerState, auth};
+use crate::models;
+
+/// All the known substitutions, by ingredient.
+#[get("/api/substitutions", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn listing() -> Result<Vec<models::Substitution>, SubstitutionError> {
+    auth::require_user(&auth)?;
+    let substitutions = server_state.recipe.substitutions().await?;
+    Ok(substitutions.into_iter().map(Into::into).collect())
+}
+
+/// The substitutions for the ingredients of a recipe.
+#[get("/api/recipe/substitutions", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn for_recipe(recipe_id: i64) -> Result<Vec<models::Substitution>, SubstitutionError> {
+    let viewer = auth.current_user.as_ref().map(|user| user.id);
+    let substitutions = server_state
+        .recipe
+        .recipe_substitutions(recipe_id, viewer)
+        .await?;
+    Ok(substitutions.into_iter().map(Into::into).collect())
+}
+
+/// Add a substitution, or update the ratio and note of an existing one.
+#[post("/api/substitutions/set", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn set(
+    ingredient: String,
+    replacement: String,
+    ratio: f64,
+    note: String,
+) -> Result<i64, SubstitutionError> {
+    auth::require(&auth, &[Permission::EditRecipes]).await?;
+    let id = server_state
+        .recipe
+        .set_substitution(&ingredient, &replacement, ratio, &note)
+        .await?;
+    Ok(id)
+}
+
+#[post("/api/substitutions/remove", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn remove(substitution_id: i64) -> Result<(), SubstitutionError> {
+    auth::require(&auth, &[Permission::EditRecipes]).await?;
+    server_state
+        .recipe
+        .remove_substitution(substitution_id)
+        .await?;
+    Ok(())
+}
+
+pub use error::*;
+mod error {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum SubstitutionError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to change substitutions")]
+        Forbidden,
+        #[error("not found")]
+        NotFound,
+        #[error("an ingredient and its replacement are required")]
+        EmptyIngredient,
+        #[error("an ingredient can't replace itself")]
+        SameIngredient,
+        #[error("the ratio must be positive")]
+        InvalidRatio,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for SubstitutionError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::EmptyIngredient | Self::SameIngredient | Self::InvalidRatio => {
+                    StatusCode::BAD_REQUEST
+                }
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+
+    impl From<recipe_service::Substitution> for models::Substitution {
+        fn from(substitution: recipe_service::Substitution) -> Self {
+            Self {
+                id: substitution.id,
+                ingredient: substitution.ingredient,
+                replacement: substitution.replacement,
+                ratio: substitution.ratio,
+                note: substitution.note,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for SubstitutionError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<recipe_service::RecipeError> for SubstitutionError {
+        fn from(err: recipe_service::RecipeError) -> Self {
+            match err {
+                recipe_service::RecipeError::NotFound => Self::NotFound,
+                recipe_service::RecipeError::Database(_) => Self::Internal,
+                recipe_service::RecipeError::ConsistencyError => Self::Internal,
+                recipe_service::RecipeError::DuplicateTitle => Self::Internal,
+            }
+        }
+    }
+
+    impl From<recipe_service::SubstitutionError> for SubstitutionError {
+        fn from(err: recipe_service::SubstitutionError) -> Self {
+            use recipe_service::SubstitutionError as ServiceError;
+            match err {
+                ServiceError::EmptyIngredient => Self::EmptyIngredient,
+                ServiceError::SameIngredient => Self::SameIngredient,
+                ServiceError::InvalidRatio => Self::InvalidRatio,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+}
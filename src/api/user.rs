@@ -1,6 +1,8 @@
 mod login_form;
+mod register_form;
 
 pub use login_form::LoginForm;
+pub use register_form::RegisterForm;
 
 #[cfg(feature = "server")]
 use dioxus::fullstack::axum::extract::State;
@@ -8,7 +10,15 @@ use dioxus::fullstack::{AsStatusCode, Form};
 use dioxus::prelude::*;
 
 #[cfg(feature = "server")]
-use crate::backend::{ServerState, auth};
+use user_service::{MailKind, Permission};
+
+#[cfg(feature = "server")]
+use self::server::{
+    PendingLogin, finish_login, send_already_registered_mail, send_email_change_mails,
+    send_password_reset_mail, send_verification_mail,
+};
+#[cfg(feature = "server")]
+use crate::backend::{ClientIp, ServerState, UserAgent, auth};
 use crate::models;
 
 #[get("/api/user", auth: auth::Session, server_state: State<ServerState>)]
@@ -22,58 +32,488 @@ pub async fn current_user() -> Result<Option<models::User>> {
     }
 }
 
-#[post("/api/user/login", auth: auth::Session, server_state: State<ServerState>)]
+/// Log in with a password. Users with two-factor authentication continue with
+/// `login_second_factor` in the same session.
+#[post("/api/user/login", auth: auth::Session, server_state: State<ServerState>, client_ip: ClientIp, user_agent: UserAgent)]
 #[tracing::instrument(skip_all, fields(email = form.email), err)]
-pub async fn login(form: Form<LoginForm>) -> Result<models::User, LoginError> {
-    let user = server_state.auth.login(&form.email, &form.password).await?;
-    auth.login_user(user.id);
-    auth.remember_user(form.stay_signed_in);
-    tracing::info!("logged in user {}", user.id);
+pub async fn login(form: Form<LoginForm>) -> Result<models::LoginStep, LoginError> {
+    let ClientIp(client_ip) = client_ip;
+    let step = server_state
+        .auth
+        .login(&form.email, &form.password, client_ip)
+        .await?;
+    match step {
+        auth_service::LoginStep::LoggedIn(user) => {
+            finish_login(
+                &auth,
+                &server_state,
+                user.id,
+                form.stay_signed_in,
+                &user_agent,
+                client_ip,
+            )
+            .await?;
+            Ok(models::LoginStep::LoggedIn(user.into()))
+        }
+        auth_service::LoginStep::SecondFactorRequired(user) => {
+            PendingLogin::start(&auth, user.id, form.stay_signed_in);
+            Ok(models::LoginStep::SecondFactorRequired)
+        }
+    }
+}
+
+/// Finish logging in with a code from an authenticator app or a recovery code, after `login`
+/// asked for it.
+#[post("/api/user/login/second-factor", auth: auth::Session, server_state: State<ServerState>, client_ip: ClientIp, user_agent: UserAgent)]
+#[tracing::instrument(skip_all, err)]
+pub async fn login_second_factor(code: String) -> Result<models::User, LoginError> {
+    let ClientIp(client_ip) = client_ip;
+    let pending = PendingLogin::get(&auth).ok_or(LoginError::NoPendingLogin)?;
+    let user = server_state
+        .auth
+        .login_second_factor(pending.user_id, &code, client_ip)
+        .await?;
+    PendingLogin::clear(&auth);
+    finish_login(
+        &auth,
+        &server_state,
+        user.id,
+        pending.stay_signed_in,
+        &user_agent,
+        client_ip,
+    )
+    .await?;
     Ok(user.into())
 }
 
-#[post("/api/user/logout", auth: auth::Session)]
+#[post("/api/user/logout", auth: auth::Session, server_state: State<ServerState>)]
 pub async fn logout() -> Result<()> {
+    server_state
+        .user
+        .end_session(&auth::session_id(&auth))
+        .await?;
     auth.logout_user();
     Ok(())
 }
 
-#[post("/api/user/add", server_state: State<ServerState>)]
+/// Add a user, once there are users this takes the permission to manage them.
+#[post("/api/user/add", auth: auth::Session, server_state: State<ServerState>)]
 #[tracing::instrument(skip_all, fields(email = email), err)]
 pub async fn add_user(
     username: String,
     email: String,
     password: String,
 ) -> Result<(), AddUserError> {
-    server_state
+    // Without users there is nobody to add them, anyone can add the first user who becomes the
+    // admin.
+    let has_users = server_state
+        .user
+        .has_users()
+        .await
+        .map_err(|_| AddUserError::Internal)?;
+    if has_users {
+        auth::require(&auth, &[Permission::ManageUsers]).await?;
+    }
+    let new_user = server_state
         .user
         .add_user(&username, &email, &password)
         .await?;
+    send_verification_mail(
+        &server_state,
+        &username,
+        &email,
+        &new_user.verification_token,
+    )
+    .await
+    .map_err(|_| AddUserError::Internal)?;
     Ok(())
 }
 
-#[post("/api/user/delete", server_state: State<ServerState>)]
-#[tracing::instrument(skip(server_state), err)]
-pub async fn delete_user(email: String) -> ServerFnResult<()> {
-    server_state
+/// Create an account for yourself, it can be used to log in once its email address is verified.
+/// Registering an address that has an account already looks the same, so that it doesn't reveal
+/// who has one, the owner of the address gets a mail about it instead. The same address gets the
+/// same mail at most once every few minutes.
+#[post("/api/user/register", server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, fields(email = form.email), err)]
+pub async fn register(form: Form<RegisterForm>) -> Result<(), RegisterError> {
+    let new_user = match server_state
         .user
-        .delete_user(&email)
+        .add_user(&form.username, &form.email, &form.password)
         .await
-        .or_internal_server_error("internal error")?;
+    {
+        Ok(new_user) => new_user,
+        Err(user_service::AddUserError::AlreadyExists) => {
+            send_already_registered_mail(&server_state, &form.email)
+                .await
+                .map_err(|_| RegisterError::Internal)?;
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    // A new account can't have been mailed, but a deleted one with the same address may have.
+    let may_mail = server_state
+        .user
+        .claim_mail(&form.email, MailKind::VerifyEmail)
+        .await
+        .map_err(|_| RegisterError::Internal)?;
+    if may_mail {
+        send_verification_mail(
+            &server_state,
+            &form.username,
+            &form.email,
+            &new_user.verification_token,
+        )
+        .await
+        .map_err(|_| RegisterError::Internal)?;
+    }
+    tracing::info!("registered user {}", new_user.id);
     Ok(())
 }
 
-#[patch("/api/user/change-password", server_state: State<ServerState>)]
+/// Verify an email address with the token that was mailed to it.
+#[post("/api/user/verify-email", server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, err)]
+pub async fn verify_email(token: String) -> Result<(), VerifyEmailError> {
+    let user = server_state.user.verify_email(&token).await?;
+    tracing::info!("verified email of user {}", user.id);
+    Ok(())
+}
+
+/// Mail a new verification link to an account that isn't verified yet, at most once every few
+/// minutes. Whether there is such an account isn't revealed.
+#[post("/api/user/resend-verification", server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, fields(email = email), err)]
+pub async fn resend_verification(email: String) -> Result<()> {
+    let Ok(user) = server_state.user.user_by_email(&email).await else {
+        return Ok(());
+    };
+    if user.email_verified
+        || !server_state
+            .user
+            .claim_mail(&email, MailKind::VerifyEmail)
+            .await?
+    {
+        return Ok(());
+    }
+    if let Some(token) = server_state.user.verification_token(&email).await? {
+        send_verification_mail(&server_state, &user.username, &email, &token).await?;
+    }
+    Ok(())
+}
+
+/// Delete a user, users can delete themselves and user managers anyone.
+#[post("/api/user/delete", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, fields(email = email), err)]
+pub async fn delete_user(email: String) -> Result<(), DeleteUserError> {
+    let email = user_service::normalize_email(&email);
+    let is_self = auth::require_self_or(&auth, &email, Permission::ManageUsers)
+        .await?
+        .email
+        == email;
+    server_state.user.delete_user(&email).await?;
+    if is_self {
+        auth.logout_user();
+    }
+    Ok(())
+}
+
+/// Replace the roles of a user, which decide what they are allowed to do.
+#[post("/api/user/roles", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip(auth, server_state), err)]
+pub async fn set_roles(email: String, roles: Vec<models::Role>) -> Result<(), SetRolesError> {
+    auth::require(&auth, &[Permission::ManageUsers]).await?;
+    let user = server_state.user.user_by_email(&email).await?;
+    let roles: Vec<_> = roles.into_iter().map(Into::into).collect();
+    server_state.user.set_roles(user.id, &roles).await?;
+    Ok(())
+}
+
+/// Change the password of a user, users can change their own with their current password and user
+/// managers anyone else's without it. This logs the user out everywhere, except in this session
+/// when they changed their own.
+#[patch("/api/user/change-password", auth: auth::Session, server_state: State<ServerState>)]
 #[tracing::instrument(skip_all, fields(email = email), err)]
 pub async fn change_password(
     email: String,
     current_password: String,
     new_password: String,
 ) -> Result<(), ChangePasswordError> {
+    let email = user_service::normalize_email(&email);
+    let current_user = auth::require_self_or(&auth, &email, Permission::ManageUsers).await?;
+    if current_user.email != email {
+        let user = server_state
+            .user
+            .user_by_email(&email)
+            .await
+            .map_err(|_| ChangePasswordError::Internal)?;
+        server_state
+            .user
+            .set_password(user.id, &new_password)
+            .await?;
+        return Ok(());
+    }
+    // Requests with an API token have no session among the user's to keep.
+    server_state
+        .user
+        .change_password(
+            &email,
+            &current_password,
+            &new_password,
+            Some(&auth::session_id(&auth)),
+        )
+        .await?;
+    Ok(())
+}
+
+/// The logged in user as they can edit themselves.
+#[get("/api/user/profile", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn profile() -> Result<models::Profile, ProfileError> {
+    let user = auth::require_user(&auth)?;
+    let user = server_state
+        .user
+        .user_by_id(user.id)
+        .await
+        .map_err(|_| ProfileError::Internal)?;
+    Ok(models::Profile {
+        username: user.username,
+        email: user.email,
+        pending_email: user.pending_email,
+    })
+}
+
+#[post("/api/user/profile/username", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, fields(username = username), err)]
+pub async fn change_username(username: String) -> Result<models::User, ProfileError> {
+    let user = auth::require_account_access(&auth)?;
+    let user = server_state
+        .user
+        .change_username(user.id, &username)
+        .await?;
+    Ok(user.into())
+}
+
+/// Mail a link to a new email address, which becomes the address of the logged in user once it
+/// is followed. The current address is told about it. This can be done once every few minutes.
+#[post("/api/user/profile/email", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, fields(new_email = new_email), err)]
+pub async fn change_email(new_email: String) -> Result<(), ProfileError> {
+    let user = auth::require_account_access(&auth)?;
+    let may_mail = server_state
+        .user
+        .claim_mail(&user.email, MailKind::ChangeEmail)
+        .await
+        .map_err(|_| ProfileError::Internal)?;
+    if !may_mail {
+        return Err(ProfileError::TooManyRequests);
+    }
+    let token = server_state
+        .user
+        .request_email_change(user.id, &new_email)
+        .await?;
+    send_email_change_mails(
+        &server_state,
+        &user.username,
+        &user.email,
+        new_email.trim(),
+        &token,
+    )
+    .await
+    .map_err(|_| ProfileError::Internal)?;
+    Ok(())
+}
+
+/// Change an email address with the token mailed to the new address.
+#[post("/api/user/confirm-email", server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, err)]
+pub async fn confirm_email_change(token: String) -> Result<(), ProfileError> {
+    let user = server_state.user.confirm_email_change(&token).await?;
+    tracing::info!("changed email address of user {}", user.id);
+    Ok(())
+}
+
+#[get("/api/user/two-factor", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn two_factor_enabled() -> Result<bool, TwoFactorError> {
+    let user = auth::require_account_access(&auth)?;
+    let enabled = server_state
+        .user
+        .has_totp(user.id)
+        .await
+        .map_err(|_| TwoFactorError::Internal)?;
+    Ok(enabled)
+}
+
+/// Start enabling two-factor authentication, it is enabled once `confirm_two_factor` gets a code
+/// for the returned secret.
+#[post("/api/user/two-factor/begin", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, err)]
+pub async fn begin_two_factor() -> Result<models::TotpEnrolment, TwoFactorError> {
+    let user = auth::require_account_access(&auth)?;
+    let enrolment = server_state.user.begin_totp(user.id).await?;
+    Ok(models::TotpEnrolment {
+        secret: enrolment.secret,
+        otpauth_uri: enrolment.otpauth_uri,
+    })
+}
+
+/// Enable two-factor authentication with a first code, returning the recovery codes.
+#[post("/api/user/two-factor/confirm", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, err)]
+pub async fn confirm_two_factor(code: String) -> Result<Vec<String>, TwoFactorError> {
+    let user = auth::require_account_access(&auth)?;
+    let recovery_codes = server_state.user.confirm_totp(user.id, &code).await?;
+    tracing::info!("enabled two-factor authentication for user {}", user.id);
+    Ok(recovery_codes)
+}
+
+/// Disable two-factor authentication, which takes a code like logging in does.
+#[post("/api/user/two-factor/disable", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, err)]
+pub async fn disable_two_factor(code: String) -> Result<(), TwoFactorError> {
+    let user = auth::require_account_access(&auth)?;
+    server_state.user.disable_totp(user.id, &code).await?;
+    tracing::info!("disabled two-factor authentication for user {}", user.id);
+    Ok(())
+}
+
+/// What the logged in user may do, which is as much as they can give their API tokens.
+#[get("/api/user/permissions", auth: auth::Session)]
+pub async fn own_permissions() -> Result<Vec<models::Permission>, ApiTokenError> {
+    let user = auth::require_user(&auth)?;
+    Ok(user.permissions.iter().copied().map(Into::into).collect())
+}
+
+#[get("/api/user/api-tokens", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn api_tokens() -> Result<Vec<models::ApiToken>, ApiTokenError> {
+    let user = auth::require_account_access(&auth)?;
+    let api_tokens = server_state
+        .user
+        .api_tokens(user.id)
+        .await
+        .map_err(|_| ApiTokenError::Internal)?;
+    Ok(api_tokens.into_iter().map(Into::into).collect())
+}
+
+/// Create a personal API token with some of the permissions of the logged in user, optionally
+/// expiring after a number of days. Its secret is only returned here.
+#[post("/api/user/api-tokens/create", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, fields(name = name), err)]
+pub async fn create_api_token(
+    name: String,
+    permissions: Vec<models::Permission>,
+    valid_for_days: Option<i64>,
+) -> Result<models::NewApiToken, ApiTokenError> {
+    let user = auth::require_account_access(&auth)?;
+    let permissions: Vec<Permission> = permissions.into_iter().map(Into::into).collect();
+    // Nobody can give a token more than they may do themselves.
+    if let Some(&permission) = permissions
+        .iter()
+        .find(|permission| !user.permissions.contains(permission))
+    {
+        return Err(ApiTokenError::PermissionNotHeld(permission.into()));
+    }
+    let new_token = server_state
+        .user
+        .create_api_token(user.id, &name, &permissions, valid_for_days)
+        .await?;
+    tracing::info!(
+        "created API token {} for user {}",
+        new_token.api_token.id,
+        user.id
+    );
+    Ok(models::NewApiToken {
+        api_token: new_token.api_token.into(),
+        secret: new_token.secret,
+    })
+}
+
+#[post("/api/user/api-tokens/revoke", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, fields(id = id), err)]
+pub async fn revoke_api_token(id: i64) -> Result<(), ApiTokenError> {
+    let user = auth::require_account_access(&auth)?;
+    server_state.user.revoke_api_token(user.id, id).await?;
+    tracing::info!("revoked API token {id} of user {}", user.id);
+    Ok(())
+}
+
+/// The sessions the logged in user is logged in with, most recently used first.
+#[get("/api/user/sessions", auth: auth::Session, server_state: State<ServerState>)]
+pub async fn sessions() -> Result<Vec<models::Session>, SessionError> {
+    let user = auth::require_account_access(&auth)?;
+    let current_session_id = auth::session_id(&auth);
+    let sessions = server_state
+        .user
+        .sessions(user.id)
+        .await
+        .map_err(|_| SessionError::Internal)?;
+    Ok(sessions
+        .into_iter()
+        .map(|session| models::Session {
+            id: session.id,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            user_agent: session.user_agent,
+            ip: session.ip,
+            is_current: session.session_id == current_session_id,
+        })
+        .collect())
+}
+
+/// Log out one of the sessions of the logged in user, which may be this one.
+#[post("/api/user/sessions/log-out", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, fields(id = id), err)]
+pub async fn log_out_session(id: i64) -> Result<(), SessionError> {
+    let user = auth::require_account_access(&auth)?;
+    let session_id = server_state.user.end_session_by_id(user.id, id).await?;
+    if session_id == auth::session_id(&auth) {
+        auth.logout_user();
+    }
+    tracing::info!("logged out session {id} of user {}", user.id);
+    Ok(())
+}
+
+/// Log out every session of the logged in user except this one.
+#[post("/api/user/sessions/log-out-others", auth: auth::Session, server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, err)]
+pub async fn log_out_other_sessions() -> Result<(), SessionError> {
+    let user = auth::require_account_access(&auth)?;
     server_state
         .user
-        .change_password(&email, &current_password, &new_password)
+        .end_other_sessions(user.id, &auth::session_id(&auth))
+        .await
+        .map_err(|_| SessionError::Internal)?;
+    tracing::info!("logged out other sessions of user {}", user.id);
+    Ok(())
+}
+
+/// Mail a link to reset the password of an account, at most once every few minutes. Whether there
+/// is an account for the address isn't revealed.
+#[post("/api/user/forgot-password", server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, fields(email = email), err)]
+pub async fn forgot_password(email: String) -> Result<()> {
+    let Ok(user) = server_state.user.user_by_email(&email).await else {
+        return Ok(());
+    };
+    if !server_state
+        .user
+        .claim_mail(&email, MailKind::ResetPassword)
+        .await?
+    {
+        return Ok(());
+    }
+    let token = server_state.user.password_reset_token(user.id).await?;
+    send_password_reset_mail(&server_state, &user.username, &email, &token).await?;
+    Ok(())
+}
+
+/// Set a new password with the token from a reset mail, which logs the user out everywhere.
+#[post("/api/user/reset-password", server_state: State<ServerState>)]
+#[tracing::instrument(skip_all, err)]
+pub async fn reset_password(token: String, new_password: String) -> Result<(), ResetPasswordError> {
+    let user = server_state
+        .user
+        .reset_password(&token, &new_password)
         .await?;
+    tracing::info!("reset password of user {}", user.id);
     Ok(())
 }
 
@@ -85,6 +525,16 @@ mod error {
     pub enum LoginError {
         #[error("invalid credentials")]
         InvalidCredentials,
+        #[error("email address not verified")]
+        EmailNotVerified,
+        #[error("invalid code")]
+        InvalidSecondFactor,
+        /// There is no login waiting for a second factor, or it took too long.
+        #[error("no login waiting for a second factor")]
+        NoPendingLogin,
+        /// Logins are refused for `retry_after` seconds after too many failed ones.
+        #[error("too many failed logins, retry after {retry_after} seconds")]
+        TooManyAttempts { retry_after: u64 },
         #[error("internal error")]
         Internal,
         #[error("internal server error")]
@@ -95,6 +545,10 @@ mod error {
         fn as_status_code(&self) -> StatusCode {
             match self {
                 Self::InvalidCredentials => StatusCode::UNAUTHORIZED,
+                Self::EmailNotVerified => StatusCode::FORBIDDEN,
+                Self::InvalidSecondFactor => StatusCode::UNAUTHORIZED,
+                Self::NoPendingLogin => StatusCode::UNAUTHORIZED,
+                Self::TooManyAttempts { .. } => StatusCode::TOO_MANY_REQUESTS,
                 Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
                 Self::ServerFnError(err) => err.as_status_code(),
             }
@@ -103,6 +557,10 @@ mod error {
 
     #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
     pub enum AddUserError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to add users")]
+        Forbidden,
         #[error("password requirements failed: {0:?}")]
         PasswordRequirement(Vec<PasswordRequirement>),
         #[error("internal error")]
@@ -112,6 +570,28 @@ mod error {
     }
 
     impl AsStatusCode for AddUserError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::PasswordRequirement(_) => StatusCode::BAD_REQUEST,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum RegisterError {
+        #[error("password requirements failed: {0:?}")]
+        PasswordRequirement(Vec<PasswordRequirement>),
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for RegisterError {
         fn as_status_code(&self) -> StatusCode {
             match self {
                 Self::PasswordRequirement(_) => StatusCode::BAD_REQUEST,
@@ -121,8 +601,84 @@ mod error {
         }
     }
 
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum VerifyEmailError {
+        #[error("invalid or expired token")]
+        InvalidToken,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for VerifyEmailError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::InvalidToken => StatusCode::BAD_REQUEST,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum DeleteUserError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to delete other users")]
+        Forbidden,
+        #[error("user not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for DeleteUserError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum SetRolesError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to change roles")]
+        Forbidden,
+        #[error("user not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for SetRolesError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
     #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
     pub enum ChangePasswordError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("not allowed to change the password of other users")]
+        Forbidden,
         #[error("password requirements failed: {0:?}")]
         PasswordRequirements(Vec<PasswordRequirement>),
         #[error("wrong current password")]
@@ -136,6 +692,8 @@ mod error {
     impl AsStatusCode for ChangePasswordError {
         fn as_status_code(&self) -> StatusCode {
             match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
                 Self::PasswordRequirements(_) => StatusCode::BAD_REQUEST,
                 Self::WrongCurrentPassword => StatusCode::UNAUTHORIZED,
                 Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
@@ -144,6 +702,163 @@ mod error {
         }
     }
 
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum ProfileError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("API tokens can't manage the account")]
+        Forbidden,
+        #[error("username is empty")]
+        EmptyUsername,
+        #[error("invalid email address")]
+        InvalidEmail,
+        #[error("this is the current email address")]
+        SameEmail,
+        #[error("email address is taken")]
+        AlreadyExists,
+        #[error("invalid or expired token")]
+        InvalidToken,
+        #[error("an email change was requested a moment ago")]
+        TooManyRequests,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for ProfileError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::EmptyUsername => StatusCode::BAD_REQUEST,
+                Self::InvalidEmail => StatusCode::BAD_REQUEST,
+                Self::SameEmail => StatusCode::BAD_REQUEST,
+                Self::AlreadyExists => StatusCode::CONFLICT,
+                Self::InvalidToken => StatusCode::BAD_REQUEST,
+                Self::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum TwoFactorError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("API tokens can't manage the account")]
+        Forbidden,
+        #[error("two-factor authentication isn't set up")]
+        NotEnrolled,
+        #[error("two-factor authentication is enabled already")]
+        AlreadyEnabled,
+        #[error("invalid code")]
+        InvalidCode,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for TwoFactorError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotEnrolled => StatusCode::BAD_REQUEST,
+                Self::AlreadyEnabled => StatusCode::CONFLICT,
+                Self::InvalidCode => StatusCode::BAD_REQUEST,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum ApiTokenError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("API tokens can't manage the account")]
+        Forbidden,
+        #[error("token name is empty")]
+        EmptyName,
+        #[error("tokens have to be valid for at least a day")]
+        InvalidExpiry,
+        #[error("permission {0:?} isn't held")]
+        PermissionNotHeld(models::Permission),
+        #[error("token not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for ApiTokenError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::EmptyName => StatusCode::BAD_REQUEST,
+                Self::InvalidExpiry => StatusCode::BAD_REQUEST,
+                Self::PermissionNotHeld(_) => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum SessionError {
+        #[error("not logged in")]
+        Unauthorized,
+        #[error("API tokens can't manage the account")]
+        Forbidden,
+        #[error("session not found")]
+        NotFound,
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for SessionError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::Unauthorized => StatusCode::UNAUTHORIZED,
+                Self::Forbidden => StatusCode::FORBIDDEN,
+                Self::NotFound => StatusCode::NOT_FOUND,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
+    #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
+    pub enum ResetPasswordError {
+        #[error("invalid or expired token")]
+        InvalidToken,
+        #[error("password requirements failed: {0:?}")]
+        PasswordRequirements(Vec<PasswordRequirement>),
+        #[error("internal error")]
+        Internal,
+        #[error("internal server error")]
+        ServerFnError(#[from] ServerFnError),
+    }
+
+    impl AsStatusCode for ResetPasswordError {
+        fn as_status_code(&self) -> StatusCode {
+            match self {
+                Self::InvalidToken => StatusCode::BAD_REQUEST,
+                Self::PasswordRequirements(_) => StatusCode::BAD_REQUEST,
+                Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                Self::ServerFnError(err) => err.as_status_code(),
+            }
+        }
+    }
+
     #[derive(Debug, serde::Serialize, serde::Deserialize)]
     pub enum PasswordRequirement {
         PasswordTooShort { min_length: usize },
@@ -157,6 +872,8 @@ mod error {
 
 #[cfg(feature = "server")]
 mod server {
+    use std::net::IpAddr;
+
     use super::*;
 
     // Allowed to contstruct models::User on the server, not elsewhere.
@@ -173,6 +890,13 @@ mod server {
         fn from(err: auth_service::LoginError) -> Self {
             match err {
                 auth_service::LoginError::InvalidCredentials => Self::InvalidCredentials,
+                auth_service::LoginError::EmailNotVerified => Self::EmailNotVerified,
+                auth_service::LoginError::InvalidSecondFactor => Self::InvalidSecondFactor,
+                auth_service::LoginError::TooManyAttempts { retry_after } => {
+                    Self::TooManyAttempts {
+                        retry_after: retry_after.as_secs(),
+                    }
+                }
                 auth_service::LoginError::Internal => Self::Internal,
             }
         }
@@ -209,6 +933,225 @@ mod server {
         }
     }
 
+    impl From<user_service::AddUserError> for RegisterError {
+        fn from(err: user_service::AddUserError) -> Self {
+            use user_service::AddUserError as ServiceError;
+            match err {
+                // `register` handles existing accounts itself.
+                ServiceError::AlreadyExists | ServiceError::Internal => Self::Internal,
+                ServiceError::PasswordRequirement(reqs) => {
+                    Self::PasswordRequirement(reqs.into_iter().map(Into::into).collect())
+                }
+            }
+        }
+    }
+
+    impl From<user_service::VerifyEmailError> for VerifyEmailError {
+        fn from(err: user_service::VerifyEmailError) -> Self {
+            use user_service::VerifyEmailError as ServiceError;
+            match err {
+                ServiceError::InvalidToken => Self::InvalidToken,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    impl From<user_service::DeleteUserError> for DeleteUserError {
+        fn from(err: user_service::DeleteUserError) -> Self {
+            use user_service::DeleteUserError as ServiceError;
+            match err {
+                ServiceError::NotFound => Self::NotFound,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for AddUserError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<user_service::ChangeUsernameError> for ProfileError {
+        fn from(err: user_service::ChangeUsernameError) -> Self {
+            use user_service::ChangeUsernameError as ServiceError;
+            match err {
+                ServiceError::EmptyUsername => Self::EmptyUsername,
+                // The logged in user was deleted meanwhile.
+                ServiceError::NotFound => Self::Unauthorized,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    impl From<user_service::ChangeEmailError> for ProfileError {
+        fn from(err: user_service::ChangeEmailError) -> Self {
+            use user_service::ChangeEmailError as ServiceError;
+            match err {
+                ServiceError::InvalidEmail => Self::InvalidEmail,
+                ServiceError::SameEmail => Self::SameEmail,
+                ServiceError::AlreadyExists => Self::AlreadyExists,
+                ServiceError::InvalidToken => Self::InvalidToken,
+                ServiceError::NotFound => Self::Unauthorized,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for ProfileError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<user_service::TotpError> for TwoFactorError {
+        fn from(err: user_service::TotpError) -> Self {
+            use user_service::TotpError as ServiceError;
+            match err {
+                ServiceError::NotEnrolled => Self::NotEnrolled,
+                ServiceError::AlreadyEnabled => Self::AlreadyEnabled,
+                ServiceError::InvalidCode => Self::InvalidCode,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for TwoFactorError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<models::Role> for user_service::Role {
+        fn from(role: models::Role) -> Self {
+            match role {
+                models::Role::Admin => Self::Admin,
+                models::Role::Editor => Self::Editor,
+                models::Role::Viewer => Self::Viewer,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for SetRolesError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<user_service::UserError> for SetRolesError {
+        fn from(err: user_service::UserError) -> Self {
+            match err {
+                user_service::UserError::NotFound => Self::NotFound,
+                _ => Self::Internal,
+            }
+        }
+    }
+
+    impl From<user_service::Permission> for models::Permission {
+        fn from(permission: user_service::Permission) -> Self {
+            match permission {
+                Permission::ManageUsers => Self::ManageUsers,
+                Permission::EditRecipes => Self::EditRecipes,
+                Permission::ViewRecipes => Self::ViewRecipes,
+            }
+        }
+    }
+
+    impl From<models::Permission> for user_service::Permission {
+        fn from(permission: models::Permission) -> Self {
+            match permission {
+                models::Permission::ManageUsers => Self::ManageUsers,
+                models::Permission::EditRecipes => Self::EditRecipes,
+                models::Permission::ViewRecipes => Self::ViewRecipes,
+            }
+        }
+    }
+
+    impl From<user_service::ApiToken> for models::ApiToken {
+        fn from(api_token: user_service::ApiToken) -> Self {
+            Self {
+                id: api_token.id,
+                name: api_token.name,
+                permissions: api_token.permissions.into_iter().map(Into::into).collect(),
+                created_at: api_token.created_at,
+                last_used_at: api_token.last_used_at,
+                expires_at: api_token.expires_at,
+            }
+        }
+    }
+
+    impl From<user_service::ApiTokenError> for ApiTokenError {
+        fn from(err: user_service::ApiTokenError) -> Self {
+            use user_service::ApiTokenError as ServiceError;
+            match err {
+                ServiceError::EmptyName => Self::EmptyName,
+                ServiceError::InvalidExpiry => Self::InvalidExpiry,
+                ServiceError::PermissionNotHeld(permission) => {
+                    Self::PermissionNotHeld(permission.into())
+                }
+                ServiceError::NotFound | ServiceError::InvalidToken => Self::NotFound,
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for ApiTokenError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<user_service::UserError> for SessionError {
+        fn from(err: user_service::UserError) -> Self {
+            match err {
+                user_service::UserError::NotFound => Self::NotFound,
+                _ => Self::Internal,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for SessionError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for DeleteUserError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
+    impl From<auth::Denied> for ChangePasswordError {
+        fn from(denied: auth::Denied) -> Self {
+            match denied {
+                auth::Denied::Unauthorized => Self::Unauthorized,
+                auth::Denied::Forbidden => Self::Forbidden,
+            }
+        }
+    }
+
     impl From<user_service::ChangePasswordError> for ChangePasswordError {
         fn from(err: user_service::ChangePasswordError) -> Self {
             use user_service::ChangePasswordError as ServiceError;
@@ -222,4 +1165,198 @@ mod server {
             }
         }
     }
+
+    impl From<user_service::ResetPasswordError> for ResetPasswordError {
+        fn from(err: user_service::ResetPasswordError) -> Self {
+            use user_service::ResetPasswordError as ServiceError;
+            match err {
+                ServiceError::InvalidToken => Self::InvalidToken,
+                ServiceError::PasswordRequirements(requirements) => {
+                    Self::PasswordRequirements(requirements.into_iter().map(Into::into).collect())
+                }
+                ServiceError::Internal => Self::Internal,
+            }
+        }
+    }
+
+    /// A login that checked the password and waits for the second factor, kept in the session.
+    #[derive(serde::Serialize, serde::Deserialize)]
+    pub(super) struct PendingLogin {
+        pub user_id: i64,
+        pub stay_signed_in: bool,
+        /// Unix timestamp in seconds.
+        expires_at: i64,
+    }
+
+    impl PendingLogin {
+        const SESSION_KEY: &str = "pending_login";
+        /// How long there is to enter the second factor.
+        const SECONDS: i64 = 5 * 60;
+
+        pub(super) fn start(session: &auth::Session, user_id: i64, stay_signed_in: bool) {
+            let pending = Self {
+                user_id,
+                stay_signed_in,
+                expires_at: chrono::Utc::now().timestamp() + Self::SECONDS,
+            };
+            session.session.set(Self::SESSION_KEY, pending);
+        }
+
+        pub(super) fn get(session: &auth::Session) -> Option<Self> {
+            session
+                .session
+                .get::<Self>(Self::SESSION_KEY)
+                .filter(|pending| pending.expires_at > chrono::Utc::now().timestamp())
+        }
+
+        pub(super) fn clear(session: &auth::Session) {
+            session.session.remove(Self::SESSION_KEY);
+        }
+    }
+
+    pub(super) async fn finish_login(
+        session: &auth::Session,
+        server_state: &ServerState,
+        user_id: i64,
+        stay_signed_in: bool,
+        user_agent: &UserAgent,
+        client_ip: Option<IpAddr>,
+    ) -> Result<(), LoginError> {
+        let UserAgent(user_agent) = user_agent;
+        server_state
+            .user
+            .start_session(
+                &auth::session_id(session),
+                user_id,
+                user_agent.as_deref(),
+                client_ip,
+            )
+            .await
+            .map_err(|_| LoginError::Internal)?;
+        session.login_user(user_id);
+        session.remember_user(stay_signed_in);
+        tracing::info!("logged in user {user_id}");
+        Ok(())
+    }
+
+    pub(super) async fn send_verification_mail(
+        server_state: &ServerState,
+        username: &str,
+        email: &str,
+        token: &str,
+    ) -> Result<(), mail_service::MailError> {
+        let app_url = server_state.app_url.trim_end_matches('/');
+        let body = format!(
+            "Hello {username},\n\n\
+            Open this link to verify your e-mail address:\n\
+            {app_url}/verify-email/{token}\n\n\
+            The link works for a day.\n"
+        );
+        send_mail(server_state, email, "Verify your e-mail address", body).await
+    }
+
+    /// Tell the owner of an account that someone tried to register their address again. Accounts
+    /// that aren't verified yet get a new verification link, as the owner likely lost the first.
+    pub(super) async fn send_already_registered_mail(
+        server_state: &ServerState,
+        email: &str,
+    ) -> Result<()> {
+        let user = server_state.user.user_by_email(email).await?;
+        if !user.email_verified {
+            if server_state
+                .user
+                .claim_mail(email, MailKind::VerifyEmail)
+                .await?
+                && let Some(token) = server_state.user.verification_token(email).await?
+            {
+                send_verification_mail(server_state, &user.username, email, &token).await?;
+            }
+            return Ok(());
+        }
+        if !server_state
+            .user
+            .claim_mail(email, MailKind::AlreadyRegistered)
+            .await?
+        {
+            return Ok(());
+        }
+        let app_url = server_state.app_url.trim_end_matches('/');
+        let username = user.username;
+        let body = format!(
+            "Hello {username},\n\n\
+            Someone tried to create an account with this e-mail address, but you have one already.\n\
+            If it was you, log in or reset your password here:\n\
+            {app_url}/forgot-password\n\n\
+            If it wasn't you, you can ignore this mail.\n"
+        );
+        send_mail(server_state, email, "You already have an account", body).await?;
+        Ok(())
+    }
+
+    /// Mail the link to confirm a new email address to that address, and tell the current address
+    /// about it so that the owner notices when it wasn't them.
+    pub(super) async fn send_email_change_mails(
+        server_state: &ServerState,
+        username: &str,
+        email: &str,
+        new_email: &str,
+        token: &str,
+    ) -> Result<(), mail_service::MailError> {
+        let app_url = server_state.app_url.trim_end_matches('/');
+        let body = format!(
+            "Hello {username},\n\n\
+            Open this link to make this your e-mail address:\n\
+            {app_url}/confirm-email/{token}\n\n\
+            The link works for a day.\n"
+        );
+        send_mail(
+            server_state,
+            new_email,
+            "Confirm your new e-mail address",
+            body,
+        )
+        .await?;
+        let body = format!(
+            "Hello {username},\n\n\
+            You asked to change your e-mail address to {new_email}. It changes once the link \
+            mailed there is opened.\n\n\
+            If this wasn't you, change your password.\n"
+        );
+        send_mail(server_state, email, "Your e-mail address is changing", body).await
+    }
+
+    pub(super) async fn send_password_reset_mail(
+        server_state: &ServerState,
+        username: &str,
+        email: &str,
+        token: &str,
+    ) -> Result<(), mail_service::MailError> {
+        let app_url = server_state.app_url.trim_end_matches('/');
+        let body = format!(
+            "Hello {username},\n\n\
+            Open this link to choose a new password:\n\
+            {app_url}/reset-password/{token}\n\n\
+            The link works for an hour. If you didn't ask for it, you can ignore this mail.\n"
+        );
+        send_mail(server_state, email, "Reset your password", body).await
+    }
+
+    /// Failures are logged here since callers only report an internal error.
+    async fn send_mail(
+        server_state: &ServerState,
+        to: &str,
+        subject: &str,
+        body: String,
+    ) -> Result<(), mail_service::MailError> {
+        let mail = mail_service::Mail {
+            to: to.to_string(),
+            subject: subject.to_string(),
+            body,
+        };
+        let result = server_state.mail.send(&mail).await;
+        if let Err(err) = &result {
+            tracing::error!("failed to send mail: {err}");
+        }
+        result
+    }
 }
@@ -1,7 +1,11 @@
 pub mod auth;
+mod client_ip;
 mod server_state;
+mod user_agent;
 
+pub use client_ip::ClientIp;
 pub use server_state::ServerState;
+pub use user_agent::UserAgent;
 
 use sqlx::sqlite::SqliteConnectOptions;
 
@@ -1,9 +1,16 @@
 mod user;
 
 use anyhow::anyhow;
+use axum_session_auth::{Auth, Authentication as _, HasPermission, Rights};
+use dioxus::fullstack::axum::extract::Request;
+use dioxus::fullstack::axum::http::header::AUTHORIZATION;
+use dioxus::fullstack::axum::http::{Method, StatusCode};
+use dioxus::fullstack::axum::middleware::Next;
+use dioxus::fullstack::axum::response::{IntoResponse as _, Response};
 use sqlx::SqlitePool;
-use user_service::UserService;
+use user_service::{Permission, UserService};
 
+use super::{ClientIp, ServerState};
 use user::User;
 
 type SessionPool = axum_session_sqlx::SessionSqlitePool;
@@ -14,6 +21,176 @@ pub type AuthLayer = axum_session_auth::AuthSessionLayer<User, Id, SessionPool,
 
 pub type Id = i64;
 
+/// Why a session isn't allowed to call a server function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Denied {
+    /// Nobody is logged in.
+    Unauthorized,
+    /// The logged in user lacks the rights.
+    Forbidden,
+}
+
+/// The logged in user of a session.
+pub(crate) fn require_user(session: &Session) -> Result<&User, Denied> {
+    session.current_user.as_ref().ok_or(Denied::Unauthorized)
+}
+
+/// The logged in user of a session, who must have all of `permissions`. This is how server
+/// functions declare the permissions they require.
+pub(crate) async fn require<'a>(
+    session: &'a Session,
+    permissions: &[Permission],
+) -> Result<&'a User, Denied> {
+    let user = require_user(session)?;
+    let rights = Rights::all(
+        permissions
+            .iter()
+            .map(|permission| Rights::permission(permission.as_str())),
+    );
+    // Server functions are routed by name rather than by method, so only the rights matter.
+    let allowed = Auth::<User, Id, Pool>::build([Method::POST], true)
+        .requires(rights)
+        .validate(user, &Method::POST, None)
+        .await;
+    if !allowed {
+        return Err(Denied::Forbidden);
+    }
+    Ok(user)
+}
+
+/// The logged in user of a session, who must either be the user with `email` or have
+/// `permission`. Requests with an API token need `permission` either way.
+pub(crate) async fn require_self_or<'a>(
+    session: &'a Session,
+    email: &str,
+    permission: Permission,
+) -> Result<&'a User, Denied> {
+    let user = require_user(session)?;
+    if user.email == user_service::normalize_email(email) && !user.via_api_token {
+        return Ok(user);
+    }
+    require(session, &[permission]).await
+}
+
+/// The logged in user of a session, to manage their own account. API tokens can't do that, or a
+/// leaked token could take over the account.
+pub(crate) fn require_account_access(session: &Session) -> Result<&User, Denied> {
+    let user = require_user(session)?;
+    if user.via_api_token {
+        return Err(Denied::Forbidden);
+    }
+    Ok(user)
+}
+
+/// The logged in user of a session, to change what is theirs, like reviews or the pantry. API
+/// tokens need the permission to edit recipes for that, others only read.
+pub(crate) async fn require_writer(session: &Session) -> Result<&User, Denied> {
+    let user = require_user(session)?;
+    if user.via_api_token {
+        return require(session, &[Permission::EditRecipes]).await;
+    }
+    Ok(user)
+}
+
+/// The id of a session, which identifies it among the sessions of its user.
+pub(crate) fn session_id(session: &Session) -> String {
+    session.session.get_session_id()
+}
+
+/// Logs out sessions that were ended on the server, like all sessions of a user whose password
+/// changed, or whose account can't be used, and records when and where the others are used. This
+/// has to run inside the auth layer, which loads the user of a session.
+pub async fn end_revoked_sessions(mut request: Request, next: Next) -> Response {
+    let server_state = request.extensions().get::<ServerState>().cloned();
+    let ClientIp(client_ip) = ClientIp::of(&request);
+    if let Some(server_state) = server_state
+        && let Some(session) = request.extensions_mut().get_mut::<Session>()
+        && let Some(user_id) = session.current_user.as_ref().map(|user| user.id)
+    {
+        let session_id = session_id(session);
+        match server_state
+            .user
+            .is_session_active(&session_id, user_id)
+            .await
+        {
+            Ok(true) if session.is_active() => {
+                if let Err(err) = server_state
+                    .user
+                    .touch_session(&session_id, client_ip)
+                    .await
+                {
+                    tracing::warn!("failed to record activity of user {user_id}: {err}");
+                }
+            }
+            Ok(_) => {
+                session.logout_user();
+                session.current_user = None;
+            }
+            Err(err) => {
+                // Only this request goes without the user, the session stays logged in.
+                tracing::error!("failed to check session of user {user_id}: {err}");
+                session.current_user = None;
+            }
+        }
+    }
+    next.run(request).await
+}
+
+/// Lets scripts and the CLI act as a user with a personal API token in an `Authorization: Bearer`
+/// header instead of logging in. The request only gets the permissions of the token, and nothing
+/// is kept in its session. This has to run inside the check for revoked sessions, which would
+/// otherwise log the user out again.
+pub async fn authenticate_bearer_token(mut request: Request, next: Next) -> Response {
+    let Some(authorization) = request.headers().get(AUTHORIZATION) else {
+        return next.run(request).await;
+    };
+    let Some(secret) = authorization
+        .to_str()
+        .ok()
+        .and_then(|authorization| authorization.strip_prefix("Bearer "))
+        .map(|secret| secret.trim().to_string())
+    else {
+        return (StatusCode::UNAUTHORIZED, "unsupported authorization").into_response();
+    };
+    let Some(server_state) = request.extensions().get::<ServerState>().cloned() else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    let grant = match server_state.user.authenticate_api_token(&secret).await {
+        Ok(grant) => grant,
+        Err(user_service::ApiTokenError::InvalidToken) => {
+            return (StatusCode::UNAUTHORIZED, "invalid or expired API token").into_response();
+        }
+        Err(err) => {
+            tracing::error!("failed to check API token: {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let user = match server_state.user.user_by_id(grant.user_id).await {
+        Ok(user) => user,
+        Err(err) => {
+            tracing::error!("failed to load user {} of API token: {err}", grant.user_id);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let user = User {
+        permissions: grant.permissions,
+        via_api_token: true,
+        ..user.into()
+    };
+    if !user.is_active() {
+        return (
+            StatusCode::UNAUTHORIZED,
+            "the account of this API token can't be used",
+        )
+            .into_response();
+    }
+    if let Some(session) = request.extensions_mut().get_mut::<Session>() {
+        session.current_user = Some(user);
+    }
+    next.run(request).await
+}
+
 #[async_trait::async_trait]
 impl axum_session_auth::Authentication<User, Id, Pool> for User {
     #[tracing::instrument(err, skip(pool))]
@@ -21,20 +198,35 @@ impl axum_session_auth::Authentication<User, Id, Pool> for User {
         let pool = pool.ok_or_else(|| anyhow!("cannot get user information without a database"))?;
         let user_service = UserService::new(pool.to_owned());
         let user = user_service.user_by_id(userid).await?;
-        Ok(user.into())
+        let permissions = user_service.permissions(userid).await?;
+        Ok(User {
+            permissions,
+            ..user.into()
+        })
     }
 
-    /// Is logged in.
+    /// Is logged in. Every loaded user is, visitors who aren't logged in have no user at all
+    /// rather than an anonymous one.
     fn is_authenticated(&self) -> bool {
         true
     }
 
-    /// Has been active recently in time.
+    /// Whether the account can be used. Sessions that were ended or went idle are checked by
+    /// `end_revoked_sessions` against the database instead, which this can't reach.
     fn is_active(&self) -> bool {
-        true
+        self.email_verified
     }
 
     fn is_anonymous(&self) -> bool {
         false
     }
 }
+
+#[async_trait::async_trait]
+impl HasPermission<Pool> for User {
+    async fn has(&self, perm: &str, _pool: &Option<&Pool>) -> bool {
+        self.permissions
+            .iter()
+            .any(|permission| permission.as_str() == perm)
+    }
+}
@@ -6,6 +6,13 @@ pub struct User {
     pub username: String,
     pub email: String,
     pub password_hash: String,
+    /// Whether the user verified their email address, until then the account can't be used.
+    pub email_verified: bool,
+    /// What the user may do through their roles, loaded along with the user.
+    pub permissions: Vec<user_service::Permission>,
+    /// Whether the request came with an API token rather than a logged in session.
+    #[serde(default)]
+    pub via_api_token: bool,
 }
 
 impl From<user_service::User> for User {
@@ -15,6 +22,9 @@ impl From<user_service::User> for User {
             username: user.username,
             email: user.email,
             password_hash: user.password_hash,
+            email_verified: user.email_verified,
+            permissions: Vec::new(),
+            via_api_token: false,
         }
     }
 }
@@ -0,0 +1,49 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+use dioxus::fullstack::axum::extract::{ConnectInfo, FromRequestParts, Request};
+use dioxus::fullstack::axum::http::request::Parts;
+use dioxus::fullstack::axum::http::{Extensions, HeaderMap};
+
+use super::ServerState;
+
+/// The address of the client a request came from, if it is known.
+///
+/// Behind a proxy, the client address is the last one in `X-Forwarded-For`, the one the proxy
+/// added. Anyone can send that header, so it is only trusted when the server is configured to be
+/// behind a proxy. Otherwise the client is who connected, which the server isn't always told.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl ClientIp {
+    /// The client address of a request in a middleware, where there is no extractor.
+    pub fn of(request: &Request) -> Self {
+        Self::from_parts(request.extensions(), request.headers())
+    }
+
+    fn from_parts(extensions: &Extensions, headers: &HeaderMap) -> Self {
+        let behind_proxy = extensions
+            .get::<ServerState>()
+            .is_some_and(|server_state| server_state.behind_proxy);
+        if behind_proxy {
+            let forwarded = headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit(',').next())
+                .and_then(|ip| ip.trim().parse().ok());
+            return Self(forwarded);
+        }
+        let connected = extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        Self(connected)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(&parts.extensions, &parts.headers))
+    }
+}
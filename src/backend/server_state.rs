@@ -1,6 +1,11 @@
+use std::sync::Arc;
+
 use auth_service::AuthService;
 use dioxus::fullstack::{FullstackContext, extract::FromRef};
+use mail_service::MailTransport;
+use meal_plan_service::MealPlanService;
 use recipe_service::RecipeService;
+use shopping_list_service::ShoppingListService;
 use sqlx::SqlitePool;
 use user_service::UserService;
 
@@ -9,14 +14,41 @@ pub struct ServerState {
     pub user: UserService,
     pub auth: AuthService,
     pub recipe: RecipeService,
+    pub meal_plan: MealPlanService,
+    pub shopping_list: ShoppingListService,
+    pub mail: Arc<dyn MailTransport>,
+    /// Where the app is reachable for users, to link back to it from mail.
+    pub app_url: String,
+    /// Whether requests come through a proxy that sets `X-Forwarded-For`, which anyone could set
+    /// otherwise.
+    pub behind_proxy: bool,
 }
 
 impl ServerState {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: SqlitePool, mail: Arc<dyn MailTransport>, app_url: String) -> Self {
         let user = UserService::new(pool.clone());
         let auth = AuthService::new(pool.clone());
-        let recipe = RecipeService::new(pool);
-        Self { user, auth, recipe }
+        let recipe = RecipeService::new(pool.clone());
+        let meal_plan = MealPlanService::new(pool.clone());
+        let shopping_list = ShoppingListService::new(pool);
+        Self {
+            user,
+            auth,
+            recipe,
+            meal_plan,
+            shopping_list,
+            mail,
+            app_url,
+            behind_proxy: false,
+        }
+    }
+
+    /// Trust the client addresses in `X-Forwarded-For`, for servers behind a proxy that sets it.
+    pub fn with_behind_proxy(self, behind_proxy: bool) -> Self {
+        Self {
+            behind_proxy,
+            ..self
+        }
     }
 }
 
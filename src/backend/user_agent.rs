@@ -0,0 +1,22 @@
+use std::convert::Infallible;
+
+use dioxus::fullstack::axum::extract::FromRequestParts;
+use dioxus::fullstack::axum::http::header::USER_AGENT;
+use dioxus::fullstack::axum::http::request::Parts;
+
+/// The `User-Agent` of a request, which tells users what their sessions are logged in with.
+#[derive(Clone, Debug)]
+pub struct UserAgent(pub Option<String>);
+
+impl<S: Send + Sync> FromRequestParts<S> for UserAgent {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+        Ok(Self(user_agent))
+    }
+}
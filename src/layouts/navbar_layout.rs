@@ -9,6 +9,12 @@ pub(crate) fn NavbarLayout() -> Element {
         div { id: "navbar",
             Link { to: Route::Home, "Home" }
             Link { to: Route::RecipeList, "Recipes" }
+            Link { to: Route::CollectionList, "Collections" }
+            Link { to: Route::Plan, "Plan" }
+            Link { to: Route::ShoppingListList, "Shopping" }
+            Link { to: Route::Pantry, "Pantry" }
+            Link { to: Route::Prices, "Prices" }
+            Link { to: Route::Substitutions, "Substitutions" }
             Profile {}
         }
         Outlet::<Route> {}
@@ -52,11 +52,27 @@ fn main() {
             SessionLayer::new(session_store)
         };
 
-        let server_state = food::backend::ServerState::new(pool);
+        let server_state = {
+            let outbox = std::env::var("MAIL_OUTBOX").unwrap_or_else(|_| "outbox".to_string());
+            tracing::debug!("Writing mail to the outbox in {outbox}");
+            let mail = std::sync::Arc::new(mail_service::OutboxTransport::new(outbox));
+            let app_url =
+                std::env::var("APP_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+            // Only a proxy in front of the server can vouch for the client addresses it forwards.
+            let behind_proxy = std::env::var("BEHIND_PROXY").is_ok_and(|value| value == "true");
+            food::backend::ServerState::new(pool, mail, app_url).with_behind_proxy(behind_proxy)
+        };
 
         let router = dioxus::server::router(App)
             // Important that the auth layer gets added before the session layer for
-            // the middleware to apply in the right order.
+            // the middleware to apply in the right order, and the revocation check before both.
+            // API tokens are checked last, their requests have no session to revoke.
+            .layer(dioxus::server::axum::middleware::from_fn(
+                food::backend::auth::authenticate_bearer_token,
+            ))
+            .layer(dioxus::server::axum::middleware::from_fn(
+                food::backend::auth::end_revoked_sessions,
+            ))
             .layer(auth_layer)
             .layer(session_layer)
             .layer(Extension(server_state));
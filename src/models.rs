@@ -7,11 +7,103 @@ pub struct User {
     pub email: String,
 }
 
+/// The user as they can edit themselves.
 #[must_use]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Profile {
+    pub username: String,
+    pub email: String,
+    /// The address the user is changing to, until they confirm it.
+    pub pending_email: Option<String>,
+}
+
+/// How far logging in with a password got.
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum LoginStep {
+    LoggedIn(User),
+    /// The user has two-factor authentication and still has to enter a code.
+    SecondFactorRequired,
+}
+
+/// A secret to add to an authenticator app, which still has to be confirmed with a code.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TotpEnrolment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// A session a user is logged in with.
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Session {
+    pub id: i64,
+    pub created_at: String,
+    pub last_seen_at: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    /// Whether this is the session that asked for it.
+    pub is_current: bool,
+}
+
+/// What a user is allowed to do, an API token can be limited to some of it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Permission {
+    ManageUsers,
+    EditRecipes,
+    ViewRecipes,
+}
+
+/// A set of permissions users are given by user managers.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Role {
+    Admin,
+    Editor,
+    Viewer,
+}
+
+/// A personal token for scripts and the CLI, without its secret.
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    pub permissions: Vec<Permission>,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+/// A token that was just created, its secret can't be shown again later.
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NewApiToken {
+    pub api_token: ApiToken,
+    pub secret: String,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct RecipeListing {
     pub id: i64,
     pub title: String,
+    pub average_rating: Option<f64>,
+    pub rating_count: i64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ListingQuery {
+    pub favorites_only: bool,
+    /// Leave out the recipes that need equipment the user doesn't own.
+    pub owned_equipment_only: bool,
+    pub sort: ListingSort,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum ListingSort {
+    #[default]
+    Title,
+    Rating,
 }
 
 #[must_use]
@@ -22,15 +114,326 @@ pub struct Recipe {
     pub meal_type: String,
     pub source_name: String,
     pub source_url: Option<String>,
+    pub servings: u32,
     pub ingredients: Vec<Ingredient>,
     pub instructions: Vec<String>,
+    pub equipment: Vec<String>,
     pub creation_date: chrono::NaiveDate,
+    pub visibility: Visibility,
+    pub household_id: Option<i64>,
+    /// The recipe this recipe is a variation of.
+    pub forked_from: Option<i64>,
+    /// Whether the recipe is owned by the user that asked for it.
+    pub is_own: bool,
 }
 
 #[must_use]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Review {
+    pub id: i64,
+    pub username: String,
+    pub rating: u8,
+    pub text: Option<String>,
+    pub creation_date: chrono::NaiveDate,
+    pub is_own: bool,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CookingLogEntry {
+    pub id: i64,
+    pub username: String,
+    pub cooked_on: chrono::NaiveDate,
+    pub servings: u32,
+    pub notes: Option<String>,
+    pub is_own: bool,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CookedRecipe {
+    pub id: i64,
+    pub title: String,
+    pub last_cooked: chrono::NaiveDate,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CollectionListing {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub owner_username: String,
+    pub is_own: bool,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub owner_username: String,
+    pub visibility: Visibility,
+    pub is_own: bool,
+    pub recipes: Vec<RecipeListing>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CollectionUpdate {
+    pub name: String,
+    pub description: String,
+    pub visibility: Visibility,
+    pub household_id: Option<i64>,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MealPlanEntry {
+    pub id: i64,
+    pub recipe_id: i64,
+    pub recipe_title: String,
+    pub date: chrono::NaiveDate,
+    pub slot: MealSlot,
+    pub servings: u32,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MealSlot {
+    Breakfast,
+    Lunch,
+    Dinner,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RecipeSelection {
+    pub recipe_id: i64,
+    pub servings: u32,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ShoppingListListing {
+    pub id: i64,
+    pub name: String,
+    pub creation_date: chrono::NaiveDate,
+    pub item_count: i64,
+    pub checked_count: i64,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ShoppingList {
+    pub id: i64,
+    pub name: String,
+    pub creation_date: chrono::NaiveDate,
+    pub items: Vec<ShoppingListItem>,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ShoppingListItem {
+    pub id: i64,
+    pub name: String,
+    pub quantity: f64,
+    pub unit: String,
+    pub checked: bool,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PantryItem {
+    pub id: i64,
+    pub ingredient: String,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub expires_on: Option<chrono::NaiveDate>,
+    pub is_expired: bool,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PantryMatch {
+    pub recipe_id: i64,
+    pub title: String,
+    pub available: u32,
+    pub total: u32,
+    pub missing: Vec<String>,
+    /// Missing ingredients with the replacement from the pantry that stands in for them.
+    pub substitutions: Vec<(String, String)>,
+}
+
+/// Use `ratio` of the replacement for every one of the ingredient.
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Substitution {
+    pub id: i64,
+    pub ingredient: String,
+    pub replacement: String,
+    pub ratio: f64,
+    pub note: String,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Equipment {
+    pub id: i64,
+    pub name: String,
+    pub owned: bool,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum RecipeFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Visibility {
+    Private,
+    Household,
+    Public,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Household {
+    pub id: i64,
+    pub name: String,
+    pub invite_code: String,
+    pub members: Vec<String>,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RecipeNutrition {
+    pub total: Nutrients,
+    pub per_serving: Nutrients,
+    /// The ingredients that were left out of the totals.
+    pub unresolved: Vec<String>,
+}
+
+/// Energy in kcal and macronutrients in grams.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Nutrients {
+    pub kcal: f64,
+    pub protein: f64,
+    pub fat: f64,
+    pub carbohydrates: f64,
+}
+
+/// What a recipe costs with today's prices.
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RecipeCost {
+    pub total: f64,
+    pub per_serving: f64,
+    /// The ingredients that were left out of the totals.
+    pub unpriced: Vec<String>,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CostEstimate {
+    pub total: f64,
+    /// The ingredients that were left out of the total.
+    pub unpriced: Vec<String>,
+}
+
+/// The price of one `unit` of an ingredient in a store.
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Price {
+    pub id: i64,
+    pub ingredient: String,
+    pub store: String,
+    pub price: f64,
+    pub unit: String,
+    pub effective_from: chrono::NaiveDate,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Ingredient {
     pub quantity: f64,
     pub unit: String,
     pub name: String,
+    /// Set when the ingredient is another recipe, the quantity is then in servings of that
+    /// recipe and its name is the title of that recipe.
+    #[serde(default)]
+    pub sub_recipe_id: Option<i64>,
+}
+
+/// The parts of a recipe that can be edited.
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RecipeContent {
+    pub title: String,
+    pub description: String,
+    pub servings: u32,
+    pub ingredients: Vec<Ingredient>,
+    pub instructions: Vec<String>,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RevisionListing {
+    pub number: u32,
+    pub author: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Revision {
+    pub number: u32,
+    pub author: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub content: RecipeContent,
+}
+
+#[must_use]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct RevisionDiff {
+    pub fields: Vec<FieldChange>,
+    pub ingredients: Vec<IngredientChange>,
+    pub instructions: Vec<InstructionChange>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum RecipeField {
+    Title,
+    Description,
+    Servings,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FieldChange {
+    pub field: RecipeField,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum IngredientChange {
+    Added(Ingredient),
+    Removed(Ingredient),
+    Changed { old: Ingredient, new: Ingredient },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum InstructionChange {
+    Added {
+        step: usize,
+        text: String,
+    },
+    Removed {
+        step: usize,
+        text: String,
+    },
+    Edited {
+        step: usize,
+        old: String,
+        new: String,
+    },
 }
@@ -19,8 +19,42 @@ pub(super) enum Route {
                 Recipe { recipe: String },
             #[end_layout]
         #[end_nest]
+        #[nest("/collections")]
+            #[layout(views::Collections)]
+                #[route("/", views::CollectionList)]
+                CollectionList,
+                #[route("/:collection", views::Collection)]
+                Collection { collection: i64 },
+            #[end_layout]
+        #[end_nest]
+        #[nest("/shopping-lists")]
+            #[layout(views::ShoppingLists)]
+                #[route("/", views::ShoppingListList)]
+                ShoppingListList,
+                #[route("/:list", views::ShoppingList)]
+                ShoppingList { list: i64 },
+            #[end_layout]
+        #[end_nest]
+        #[route("/plan", views::Plan)]
+        Plan,
+        #[route("/pantry", views::Pantry)]
+        Pantry,
+        #[route("/prices", views::Prices)]
+        Prices,
+        #[route("/substitutions", views::Substitutions)]
+        Substitutions,
         #[route("/user", views::User)]
         UserPage,
+        #[route("/register", views::Register)]
+        Register,
+        #[route("/verify-email/:token", views::VerifyEmail)]
+        VerifyEmail { token: String },
+        #[route("/confirm-email/:token", views::ConfirmEmail)]
+        ConfirmEmail { token: String },
+        #[route("/forgot-password", views::ForgotPassword)]
+        ForgotPassword,
+        #[route("/reset-password/:token", views::ResetPassword)]
+        ResetPassword { token: String },
         #[route("/:..route", views::NotFound)]
         NotFound { route: Vec<String> },
 }
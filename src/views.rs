@@ -7,5 +7,44 @@ pub(crate) use user::User;
 mod not_found;
 pub(crate) use not_found::NotFound;
 
+mod api_tokens;
+
+mod collections;
+pub(crate) use collections::{Collection, CollectionList, Collections};
+
+mod equipment;
+
+mod households;
+
+mod pantry;
+pub(crate) use pantry::Pantry;
+
+mod password_reset;
+pub(crate) use password_reset::{ForgotPassword, ResetPassword};
+
+mod plan;
+
+mod profile;
+pub(crate) use plan::Plan;
+pub(crate) use profile::ConfirmEmail;
+
+mod prices;
+pub(crate) use prices::Prices;
+
 mod recipes;
-pub(crate) use recipes::{Recipe, RecipeList, Recipes};
+pub(crate) use recipes::{Recipe, RecipeList, Recipes, recipe_slug};
+
+mod register;
+pub(crate) use register::{Register, VerifyEmail};
+
+mod revisions;
+
+mod sessions;
+
+mod shopping_lists;
+pub(crate) use shopping_lists::{ShoppingList, ShoppingListList, ShoppingLists};
+
+mod substitutions;
+pub(crate) use substitutions::Substitutions;
+
+mod two_factor;
@@ -0,0 +1,161 @@
+use dioxus::prelude::*;
+use food::{api, models};
+
+/// The personal API tokens of the logged in user, for scripts and the CLI.
+#[component]
+pub(super) fn ApiTokenSettings() -> Element {
+    let mut api_tokens = use_resource(api::user::api_tokens);
+    let own_permissions = use_resource(api::user::own_permissions);
+    let mut name = use_signal(String::new);
+    let mut permissions = use_signal(Vec::<models::Permission>::new);
+    let mut valid_for_days = use_signal(|| Some(90_i64));
+    let mut new_token = use_signal(|| None::<models::NewApiToken>);
+    let mut fail_message = use_signal(|| None::<String>);
+
+    let api_tokens_read = api_tokens.read();
+    let tokens = match api_tokens_read.as_ref() {
+        Some(Ok(tokens)) => tokens.as_slice(),
+        _ => &[][..],
+    };
+    let own_permissions = match own_permissions.read().as_ref() {
+        Some(Ok(own_permissions)) => own_permissions.clone(),
+        _ => Vec::new(),
+    };
+
+    rsx! {
+        div { class: "api-tokens",
+            h3 { "API tokens" }
+            p { "Tokens let scripts and the command line act as you, with only the permissions you pick." }
+            p { "They can't manage your account, and only change things with the permission to edit recipes." }
+            if let Some(new_token) = new_token() {
+                p { "Copy the token for \"{new_token.api_token.name}\" now, it won't be shown again:" }
+                p { class: "api-token-secret", "{new_token.secret}" }
+            }
+            if tokens.is_empty() {
+                p { "You have no API tokens." }
+            }
+            ul {
+                for token in tokens {
+                    li { key: "{token.id}",
+                        ApiTokenSummary { api_token: token.clone() }
+                        button {
+                            onclick: {
+                                let token_id = token.id;
+                                move |_| async move {
+                                    match api::user::revoke_api_token(token_id).await {
+                                        Ok(()) => {
+                                            fail_message.set(None);
+                                            api_tokens.restart();
+                                        }
+                                        Err(err) => fail_message.set(Some(api_token_error_message(err))),
+                                    }
+                                }
+                            },
+                            "Revoke"
+                        }
+                    }
+                }
+            }
+            form {
+                onsubmit: move |evt: FormEvent| async move {
+                    evt.prevent_default();
+                    match api::user::create_api_token(name(), permissions(), valid_for_days()).await {
+                        Ok(created) => {
+                            name.set(String::new());
+                            permissions.set(Vec::new());
+                            fail_message.set(None);
+                            new_token.set(Some(created));
+                            api_tokens.restart();
+                        }
+                        Err(err) => fail_message.set(Some(api_token_error_message(err))),
+                    }
+                },
+                input {
+                    name: "name",
+                    placeholder: "Token name",
+                    required: true,
+                    value: "{name}",
+                    oninput: move |evt| name.set(evt.value()),
+                }
+                for permission in own_permissions {
+                    label { key: "{permission_label(permission)}",
+                        input {
+                            r#type: "checkbox",
+                            checked: permissions.read().contains(&permission),
+                            onchange: move |evt| {
+                                permissions.with_mut(|permissions| {
+                                    permissions.retain(|p| *p != permission);
+                                    if evt.checked() {
+                                        permissions.push(permission);
+                                    }
+                                });
+                            },
+                        }
+                        "{permission_label(permission)}"
+                    }
+                }
+                select {
+                    name: "valid_for_days",
+                    onchange: move |evt| valid_for_days.set(evt.value().parse().ok()),
+                    option { value: "30", "Expires in 30 days" }
+                    option { value: "90", selected: true, "Expires in 90 days" }
+                    option { value: "365", "Expires in a year" }
+                    option { value: "never", "Never expires" }
+                }
+                button { r#type: "submit", "Create token" }
+            }
+            if let Some(message) = fail_message() {
+                p { class: "api-tokens-failed", "{message}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn ApiTokenSummary(api_token: models::ApiToken) -> Element {
+    let permissions = if api_token.permissions.is_empty() {
+        "no permissions".to_string()
+    } else {
+        api_token
+            .permissions
+            .iter()
+            .map(|permission| permission_label(*permission))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let last_used = api_token
+        .last_used_at
+        .map_or("never used".to_string(), |at| format!("last used {at}"));
+    let expires = api_token
+        .expires_at
+        .map_or("never expires".to_string(), |at| format!("expires {at}"));
+
+    rsx! {
+        span { class: "api-token",
+            strong { "{api_token.name}" }
+            " ({permissions}), created {api_token.created_at}, {last_used}, {expires} "
+        }
+    }
+}
+
+fn permission_label(permission: models::Permission) -> &'static str {
+    match permission {
+        models::Permission::ManageUsers => "Manage users",
+        models::Permission::EditRecipes => "Edit recipes",
+        models::Permission::ViewRecipes => "View recipes",
+    }
+}
+
+fn api_token_error_message(err: api::user::ApiTokenError) -> String {
+    use api::user::ApiTokenError;
+    match err {
+        ApiTokenError::Unauthorized => "Log in first",
+        ApiTokenError::Forbidden => "Log in with your password to do this",
+        ApiTokenError::EmptyName => "Give the token a name",
+        ApiTokenError::InvalidExpiry => "Tokens have to be valid for at least a day",
+        ApiTokenError::PermissionNotHeld(_) => "You can't give a token permissions you don't have",
+        ApiTokenError::NotFound => "The token doesn't exist anymore",
+        ApiTokenError::Internal | ApiTokenError::ServerFnError(_) => "Unexpected error",
+    }
+    .to_string()
+}
@@ -0,0 +1,342 @@
+use dioxus::prelude::*;
+use food::{api, models};
+
+use crate::layouts::UserContext;
+use crate::router::Route;
+use crate::views::recipe_slug;
+
+#[component]
+pub(crate) fn Collections() -> Element {
+    rsx! {
+        div { class: "content",
+            h1 { "Collections" }
+            Outlet::<Route> {}
+        }
+    }
+}
+
+#[component]
+pub(crate) fn CollectionList() -> Element {
+    let user_ctx = use_context::<UserContext>();
+    let mut response = use_server_future(api::collection::listing)?;
+    let response_read = response.read();
+    // SAFETY: If the future was still pending, it would have early returned
+    // with 'suspended' on the `?` above.
+    let Ok(collections) = response_read.as_ref().unwrap() else {
+        return HttpError::internal_server_error("failed to list collections")?;
+    };
+
+    rsx! {
+        if user_ctx.user.read().is_some() {
+            NewCollection { on_create: move |()| response.restart() }
+        }
+        ul { id: "collection-listings",
+            for collection in collections {
+                li { key: "{collection.id}", class: "collection-listing",
+                    Link {
+                        to: Route::Collection {
+                            collection: collection.id,
+                        },
+                        "{collection.name}"
+                    }
+                    if !collection.is_own {
+                        span { class: "collection-owner", " by {collection.owner_username}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn NewCollection(on_create: EventHandler) -> Element {
+    let mut name = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    rsx! {
+        form {
+            id: "new-collection",
+            class: "flex-row",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                match api::collection::create(name(), String::new()).await {
+                    Ok(_id) => {
+                        name.set(String::new());
+                        error.set(None);
+                        on_create.call(());
+                    }
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            },
+            input {
+                placeholder: "New collection",
+                value: "{name}",
+                required: true,
+                oninput: move |evt| name.set(evt.value()),
+            }
+            button { r#type: "submit", "Create" }
+        }
+        if let Some(error) = error() {
+            p { class: "collection-failed", "{error}" }
+        }
+    }
+}
+
+#[component]
+pub(crate) fn Collection(collection: i64) -> Element {
+    let collection_id = collection;
+    let mut response = use_server_future(move || api::collection::by_id(collection_id))?;
+    let response_read = response.read();
+    // SAFETY: If the future was still pending, it would have early returned
+    // with 'suspended' on the `?` above.
+    let Ok(c) = response_read.as_ref().unwrap() else {
+        return HttpError::not_found("no such collection")?;
+    };
+    let recipe_ids: Vec<i64> = c.recipes.iter().map(|recipe| recipe.id).collect();
+
+    rsx! {
+        div { id: "collection",
+            h2 { "{c.name}" }
+            p { class: "collection-owner", "By {c.owner_username}" }
+            if !c.description.is_empty() {
+                p { "{c.description}" }
+            }
+            ol { id: "collection-recipes",
+                for (index, recipe) in c.recipes.iter().enumerate() {
+                    li { key: "{recipe.id}",
+                        Link {
+                            to: Route::Recipe {
+                                recipe: recipe_slug(recipe.id, &recipe.title),
+                            },
+                            "{recipe.title}"
+                        }
+                        if c.is_own {
+                            CollectionRecipeControls {
+                                collection_id,
+                                recipe_ids: recipe_ids.clone(),
+                                index,
+                                on_change: move |()| response.restart(),
+                            }
+                        }
+                    }
+                }
+            }
+            if c.is_own {
+                CollectionSettings {
+                    collection: c.clone(),
+                    on_change: move |()| response.restart(),
+                }
+            }
+            CollectionExport { collection_id }
+        }
+    }
+}
+
+#[component]
+fn CollectionRecipeControls(
+    collection_id: i64,
+    recipe_ids: Vec<i64>,
+    index: usize,
+    on_change: EventHandler,
+) -> Element {
+    let recipe_id = recipe_ids[index];
+    let can_move_up = index > 0;
+    let can_move_down = index + 1 < recipe_ids.len();
+
+    let move_to = move |recipe_ids: Vec<i64>, to: usize| async move {
+        let mut recipe_ids = recipe_ids;
+        let recipe_id = recipe_ids.remove(index);
+        recipe_ids.insert(to, recipe_id);
+        if let Err(err) = api::collection::reorder(collection_id, recipe_ids).await {
+            tracing::warn!("failed to reorder collection: {err}");
+        }
+        on_change.call(());
+    };
+
+    rsx! {
+        span { class: "collection-recipe-controls",
+            if can_move_up {
+                button {
+                    title: "Move up",
+                    onclick: {
+                        let recipe_ids = recipe_ids.clone();
+                        move |_| move_to(recipe_ids.clone(), index - 1)
+                    },
+                    "↑"
+                }
+            }
+            if can_move_down {
+                button {
+                    title: "Move down",
+                    onclick: {
+                        let recipe_ids = recipe_ids.clone();
+                        move |_| move_to(recipe_ids.clone(), index + 1)
+                    },
+                    "↓"
+                }
+            }
+            button {
+                title: "Remove from collection",
+                onclick: move |_| async move {
+                    if let Err(err) = api::collection::remove_recipe(collection_id, recipe_id).await {
+                        tracing::warn!("failed to remove recipe from collection: {err}");
+                    }
+                    on_change.call(());
+                },
+                "✕"
+            }
+        }
+    }
+}
+
+#[component]
+fn CollectionSettings(collection: models::Collection, on_change: EventHandler) -> Element {
+    let nav = use_navigator();
+    let collection_id = collection.id;
+    let mut name = use_signal(|| collection.name.clone());
+    let mut description = use_signal(|| collection.description.clone());
+    let mut is_public = use_signal(|| collection.visibility == models::Visibility::Public);
+    let mut error = use_signal(|| None::<String>);
+
+    rsx! {
+        form {
+            id: "collection-settings",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                let visibility = if is_public() {
+                    models::Visibility::Public
+                } else {
+                    models::Visibility::Private
+                };
+                let update = models::CollectionUpdate {
+                    name: name(),
+                    description: description(),
+                    visibility,
+                    household_id: None,
+                };
+                match api::collection::update(collection_id, update).await {
+                    Ok(()) => {
+                        error.set(None);
+                        on_change.call(());
+                    }
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            },
+            h4 { "Settings" }
+            input {
+                value: "{name}",
+                required: true,
+                oninput: move |evt| name.set(evt.value()),
+            }
+            textarea {
+                value: "{description}",
+                placeholder: "What is this collection about?",
+                oninput: move |evt| description.set(evt.value()),
+            }
+            div { class: "flex-row",
+                label { class: "checkbox-container",
+                    input {
+                        id: "collection-public",
+                        r#type: "checkbox",
+                        checked: is_public(),
+                        onchange: move |evt| is_public.set(evt.checked()),
+                    }
+                    span { class: "checkmark" }
+                }
+                label {
+                    style: "margin-left: 5px;",
+                    r#for: "collection-public",
+                    "Anyone with the link can see it"
+                }
+            }
+            if let Some(error) = error() {
+                p { class: "collection-failed", "{error}" }
+            }
+            div { class: "flex-row",
+                button { r#type: "submit", "Save" }
+                button {
+                    r#type: "button",
+                    onclick: move |_| async move {
+                        match api::collection::delete(collection_id).await {
+                            Ok(()) => {
+                                nav.push(Route::CollectionList);
+                            }
+                            Err(err) => error.set(Some(err.to_string())),
+                        }
+                    },
+                    "Delete collection"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn CollectionExport(collection_id: i64) -> Element {
+    let mut exported = use_signal(|| None::<String>);
+
+    let export = move |format: models::RecipeFormat| async move {
+        match api::collection::export(collection_id, format).await {
+            Ok(text) => exported.set(Some(text)),
+            Err(err) => exported.set(Some(format!("Export failed: {err}"))),
+        }
+    };
+
+    rsx! {
+        div { id: "collection-export",
+            h4 { "Export" }
+            div { class: "flex-row",
+                button { onclick: move |_| export(models::RecipeFormat::Markdown), "Markdown" }
+                button { onclick: move |_| export(models::RecipeFormat::Json), "JSON" }
+            }
+            if let Some(text) = exported() {
+                pre { "{text}" }
+            }
+        }
+    }
+}
+
+/// Lets the current user add a recipe to one of their own collections.
+#[component]
+pub(crate) fn AddToCollection(recipe_id: i64) -> Element {
+    let collections = use_resource(api::collection::listing);
+    let mut selected = use_signal(|| None::<i64>);
+    let mut status = use_signal(|| None::<String>);
+
+    let own: Vec<models::CollectionListing> = match &*collections.read() {
+        Some(Ok(collections)) => collections
+            .iter()
+            .filter(|collection| collection.is_own)
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    };
+    if own.is_empty() {
+        return rsx! {};
+    }
+    let first_id = own[0].id;
+
+    rsx! {
+        div { id: "add-to-collection", class: "flex-row",
+            select {
+                onchange: move |evt| selected.set(evt.value().parse().ok()),
+                for collection in own {
+                    option { key: "{collection.id}", value: "{collection.id}", "{collection.name}" }
+                }
+            }
+            button {
+                onclick: move |_| async move {
+                    let collection_id = selected().unwrap_or(first_id);
+                    match api::collection::add_recipe(collection_id, recipe_id).await {
+                        Ok(()) => status.set(Some("Added".to_string())),
+                        Err(err) => status.set(Some(err.to_string())),
+                    }
+                },
+                "Add to collection"
+            }
+            if let Some(status) = status() {
+                span { class: "add-to-collection-status", " {status}" }
+            }
+        }
+    }
+}
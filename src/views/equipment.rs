@@ -0,0 +1,119 @@
+use dioxus::prelude::*;
+use food::api;
+
+/// The equipment a recipe needs, the owner can change it as a comma separated list.
+#[component]
+pub(crate) fn RecipeEquipment(
+    recipe_id: i64,
+    equipment: Vec<String>,
+    is_own: bool,
+    on_change: EventHandler,
+) -> Element {
+    let mut editing = use_signal(|| false);
+    let mut text = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    if equipment.is_empty() && !is_own {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { id: "equipment",
+            div { class: "flex-row",
+                h4 { "Equipment" }
+                if is_own && !editing() {
+                    button {
+                        onclick: {
+                            let equipment = equipment.join(", ");
+                            move |_| {
+                                text.set(equipment.clone());
+                                editing.set(true);
+                            }
+                        },
+                        "Edit"
+                    }
+                }
+            }
+            if editing() {
+                form {
+                    id: "equipment-form",
+                    onsubmit: move |evt: FormEvent| async move {
+                        evt.prevent_default();
+                        let equipment = text().split(',').map(str::to_string).collect();
+                        match api::equipment::set_for_recipe(recipe_id, equipment).await {
+                            Ok(()) => {
+                                error.set(None);
+                                editing.set(false);
+                                on_change.call(());
+                            }
+                            Err(err) => error.set(Some(err.to_string())),
+                        }
+                    },
+                    input {
+                        placeholder: "e.g. Stand mixer, Dutch oven",
+                        value: "{text}",
+                        oninput: move |evt| text.set(evt.value()),
+                    }
+                    if let Some(error) = error() {
+                        p { class: "equipment-failed", "{error}" }
+                    }
+                    div { class: "flex-row",
+                        button { r#type: "submit", "Save" }
+                        button { r#type: "button", onclick: move |_| editing.set(false), "Cancel" }
+                    }
+                }
+            } else if equipment.is_empty() {
+                p { class: "equipment-none", "No special equipment needed." }
+            } else {
+                ul {
+                    for name in &equipment {
+                        li { "{name}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The equipment the user owns, recipes needing anything unchecked can be left out of the
+/// recipe listing.
+#[component]
+pub(crate) fn KitchenEquipment() -> Element {
+    let mut equipment = use_resource(api::equipment::listing);
+
+    let equipment_read = equipment.read();
+    let equipment_list = match equipment_read.as_ref() {
+        Some(Ok(equipment_list)) => equipment_list.as_slice(),
+        _ => &[][..],
+    };
+
+    rsx! {
+        h3 { "Equipment" }
+        if equipment_list.is_empty() {
+            p { "No recipe needs any special equipment yet." }
+        }
+        ul { id: "kitchen-equipment",
+            for item in equipment_list {
+                li { key: "{item.id}",
+                    label { class: "checkbox-container",
+                        input {
+                            r#type: "checkbox",
+                            checked: item.owned,
+                            onchange: {
+                                let equipment_id = item.id;
+                                move |evt: FormEvent| async move {
+                                    if let Err(err) = api::equipment::set_owned(equipment_id, evt.checked()).await {
+                                        tracing::warn!("failed to mark equipment: {err}");
+                                    }
+                                    equipment.restart();
+                                }
+                            },
+                        }
+                        span { class: "checkmark" }
+                        span { class: "equipment-name", "{item.name}" }
+                    }
+                }
+            }
+        }
+    }
+}
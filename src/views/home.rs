@@ -1,10 +1,65 @@
 use dioxus::prelude::*;
+use food::{api, models};
+
+use crate::layouts::UserContext;
+use crate::router::Route;
+use crate::views::recipe_slug;
 
 #[component]
 pub(crate) fn Home() -> Element {
+    let user_ctx = use_context::<UserContext>();
+
     rsx! {
         div { id: "title", class: "content",
             h1 { "Food? Food!" }
+            if user_ctx.user.read().is_some() {
+                CookedRecipes {}
+            }
+        }
+    }
+}
+
+#[component]
+fn CookedRecipes() -> Element {
+    let recently_cooked = use_resource(api::recipe::recently_cooked);
+    let not_cooked_lately = use_resource(api::recipe::not_cooked_lately);
+
+    let recently_cooked = match &*recently_cooked.read() {
+        Some(Ok(recipes)) => recipes.clone(),
+        _ => Vec::new(),
+    };
+    let not_cooked_lately = match &*not_cooked_lately.read() {
+        Some(Ok(recipes)) => recipes.clone(),
+        _ => Vec::new(),
+    };
+
+    rsx! {
+        if !recently_cooked.is_empty() {
+            h3 { "Recently cooked" }
+            CookedRecipeList { recipes: recently_cooked }
+        }
+        if !not_cooked_lately.is_empty() {
+            h3 { "Haven't cooked in a while" }
+            CookedRecipeList { recipes: not_cooked_lately }
+        }
+    }
+}
+
+#[component]
+fn CookedRecipeList(recipes: Vec<models::CookedRecipe>) -> Element {
+    rsx! {
+        ul { class: "cooked-recipes",
+            for recipe in recipes {
+                li { key: "{recipe.id}",
+                    Link {
+                        to: Route::Recipe {
+                            recipe: recipe_slug(recipe.id, &recipe.title),
+                        },
+                        "{recipe.title}"
+                    }
+                    span { class: "last-cooked", " {recipe.last_cooked}" }
+                }
+            }
         }
     }
 }
@@ -0,0 +1,120 @@
+use dioxus::prelude::*;
+use food::{api, models};
+
+/// The households of the logged in user, which they can share recipes and collections with.
+#[component]
+pub(super) fn HouseholdSettings() -> Element {
+    let mut households = use_resource(api::household::listing);
+    let mut name = use_signal(String::new);
+    let mut invite_code = use_signal(String::new);
+    let mut fail_message = use_signal(|| None::<String>);
+
+    let households_read = households.read();
+    let household_list = match households_read.as_ref() {
+        Some(Ok(household_list)) => household_list.as_slice(),
+        _ => &[][..],
+    };
+
+    rsx! {
+        div { class: "households",
+            h3 { "Households" }
+            p { "Recipes shared with a household are visible to all of its members." }
+            if household_list.is_empty() {
+                p { "You aren't in a household." }
+            }
+            ul {
+                for household in household_list {
+                    li { key: "{household.id}",
+                        HouseholdSummary { household: household.clone() }
+                        button {
+                            onclick: {
+                                let id = household.id;
+                                move |_| async move {
+                                    match api::household::leave(id).await {
+                                        Ok(()) => fail_message.set(None),
+                                        Err(err) => fail_message.set(Some(household_error_message(err))),
+                                    }
+                                    households.restart();
+                                }
+                            },
+                            "Leave"
+                        }
+                    }
+                }
+            }
+            form {
+                onsubmit: move |evt: FormEvent| async move {
+                    evt.prevent_default();
+                    match api::household::create(name()).await {
+                        Ok(_) => {
+                            name.set(String::new());
+                            fail_message.set(None);
+                            households.restart();
+                        }
+                        Err(err) => fail_message.set(Some(household_error_message(err))),
+                    }
+                },
+                input {
+                    name: "name",
+                    placeholder: "Household name",
+                    required: true,
+                    value: "{name}",
+                    oninput: move |evt| name.set(evt.value()),
+                }
+                button { r#type: "submit", "Create household" }
+            }
+            form {
+                onsubmit: move |evt: FormEvent| async move {
+                    evt.prevent_default();
+                    match api::household::join(invite_code()).await {
+                        Ok(_) => {
+                            invite_code.set(String::new());
+                            fail_message.set(None);
+                            households.restart();
+                        }
+                        Err(err) => fail_message.set(Some(household_error_message(err))),
+                    }
+                },
+                input {
+                    name: "invite_code",
+                    placeholder: "Invite code",
+                    required: true,
+                    value: "{invite_code}",
+                    oninput: move |evt| invite_code.set(evt.value()),
+                }
+                button { r#type: "submit", "Join household" }
+            }
+            if let Some(message) = fail_message() {
+                p { class: "households-failed", "{message}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn HouseholdSummary(household: models::Household) -> Element {
+    let members = household.members.join(", ");
+
+    rsx! {
+        span { class: "household",
+            strong { "{household.name}" }
+            " ({members}), invite others with the code "
+            code { "{household.invite_code}" }
+            " "
+        }
+    }
+}
+
+fn household_error_message(err: api::household::HouseholdError) -> String {
+    use api::household::HouseholdError;
+    match err {
+        HouseholdError::Unauthorized => "Log in first",
+        HouseholdError::EmptyName => "Give the household a name",
+        HouseholdError::InvalidInviteCode => "No household has this invite code",
+        HouseholdError::NotMember => "You aren't in this household anymore",
+        HouseholdError::Forbidden | HouseholdError::Internal | HouseholdError::ServerFnError(_) => {
+            "Unexpected error"
+        }
+    }
+    .to_string()
+}
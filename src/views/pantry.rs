@@ -0,0 +1,188 @@
+use dioxus::prelude::*;
+use food::api;
+
+use crate::layouts::UserContext;
+use crate::router::Route;
+use crate::views::equipment::KitchenEquipment;
+use crate::views::recipe_slug;
+
+#[component]
+pub(crate) fn Pantry() -> Element {
+    let user_ctx = use_context::<UserContext>();
+
+    rsx! {
+        div { id: "pantry", class: "content",
+            h1 { "Pantry" }
+            if user_ctx.user.read().is_some() {
+                PantryItems {}
+                KitchenEquipment {}
+            } else {
+                p { "Log in to keep track of your pantry." }
+            }
+        }
+    }
+}
+
+#[component]
+fn PantryItems() -> Element {
+    let mut pantry = use_resource(api::pantry::listing);
+    let mut matches = use_resource(api::pantry::what_can_i_cook);
+
+    let pantry_read = pantry.read();
+    let items = match pantry_read.as_ref() {
+        Some(Ok(items)) => items.as_slice(),
+        _ => &[][..],
+    };
+    let matches_read = matches.read();
+    let matches_list = match matches_read.as_ref() {
+        Some(Ok(matches_list)) => matches_list.as_slice(),
+        _ => &[][..],
+    };
+
+    rsx! {
+        PantryForm {
+            on_change: move |()| {
+                pantry.restart();
+                matches.restart();
+            },
+        }
+        if items.is_empty() {
+            p { "Your pantry is empty." }
+        }
+        ul { id: "pantry-items",
+            for item in items {
+                li {
+                    key: "{item.id}",
+                    class: if item.is_expired { "pantry-item expired" } else { "pantry-item" },
+                    if let (Some(quantity), Some(unit)) = (item.quantity, item.unit.as_ref()) {
+                        "{quantity} {unit} "
+                    }
+                    "{item.ingredient}"
+                    if let Some(expires_on) = item.expires_on {
+                        span { class: "expires-on", " until {expires_on}" }
+                    }
+                    button {
+                        title: "Remove",
+                        onclick: {
+                            let item_id = item.id;
+                            move |_| async move {
+                                if let Err(err) = api::pantry::remove_item(item_id).await {
+                                    tracing::warn!("failed to remove pantry item: {err}");
+                                }
+                                pantry.restart();
+                                matches.restart();
+                            }
+                        },
+                        "✕"
+                    }
+                }
+            }
+        }
+        h3 { "What can I cook?" }
+        if matches_list.is_empty() {
+            p { "Nothing yet, stock up the pantry." }
+        }
+        ul { id: "pantry-matches",
+            for pantry_match in matches_list {
+                li { key: "{pantry_match.recipe_id}",
+                    Link {
+                        to: Route::Recipe {
+                            recipe: recipe_slug(pantry_match.recipe_id, &pantry_match.title),
+                        },
+                        "{pantry_match.title}"
+                    }
+                    span { class: "pantry-coverage",
+                        " {pantry_match.available}/{pantry_match.total}"
+                    }
+                    if !pantry_match.missing.is_empty() {
+                        p { class: "pantry-missing",
+                            "Missing: "
+                            {pantry_match.missing.join(", ")}
+                        }
+                    }
+                    if !pantry_match.substitutions.is_empty() {
+                        p { class: "pantry-substitutions",
+                            "Substitute: "
+                            {
+                                pantry_match
+                                    .substitutions
+                                    .iter()
+                                    .map(|(ingredient, replacement)| format!("{replacement} for {ingredient}"))
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PantryForm(on_change: EventHandler) -> Element {
+    let mut ingredient = use_signal(String::new);
+    let mut quantity = use_signal(String::new);
+    let mut unit = use_signal(String::new);
+    let mut expires_on = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    rsx! {
+        form {
+            id: "pantry-form",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                let quantity_value = match quantity().trim() {
+                    "" => None,
+                    text => {
+                        let Ok(value) = text.parse::<f64>() else {
+                            error.set(Some("The quantity must be a number".to_string()));
+                            return;
+                        };
+                        Some((value, unit()))
+                    }
+                };
+                let expires = expires_on().parse().ok();
+                match api::pantry::set_item(ingredient(), quantity_value, expires).await {
+                    Ok(_) => {
+                        ingredient.set(String::new());
+                        quantity.set(String::new());
+                        unit.set(String::new());
+                        expires_on.set(String::new());
+                        error.set(None);
+                        on_change.call(());
+                    }
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            },
+            input {
+                placeholder: "Ingredient",
+                value: "{ingredient}",
+                required: true,
+                oninput: move |evt| ingredient.set(evt.value()),
+            }
+            div { class: "flex-row",
+                input {
+                    placeholder: "Quantity",
+                    value: "{quantity}",
+                    oninput: move |evt| quantity.set(evt.value()),
+                }
+                input {
+                    placeholder: "Unit",
+                    value: "{unit}",
+                    oninput: move |evt| unit.set(evt.value()),
+                }
+                input {
+                    r#type: "date",
+                    title: "Expires on",
+                    value: "{expires_on}",
+                    oninput: move |evt| expires_on.set(evt.value()),
+                }
+            }
+            if let Some(error) = error() {
+                p { class: "pantry-failed", "{error}" }
+            }
+            button { r#type: "submit", "Add to pantry" }
+        }
+    }
+}
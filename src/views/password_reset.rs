@@ -0,0 +1,105 @@
+use dioxus::prelude::*;
+use food::api;
+
+use super::register::password_requirements;
+use crate::router::Route;
+
+#[component]
+pub(crate) fn ForgotPassword() -> Element {
+    let mut email = use_signal(String::new);
+    let mut sent = use_signal(|| false);
+    let mut failed = use_signal(|| false);
+
+    rsx! {
+        div { class: "content",
+            if sent() {
+                h1 { "Check your e-mail" }
+                p {
+                    "If there is an account for {email}, we sent it a link to choose a new password."
+                }
+                Link { to: Route::UserPage, "Log in" }
+            } else {
+                form {
+                    onsubmit: move |evt: FormEvent| async move {
+                        evt.prevent_default();
+                        match api::user::forgot_password(email()).await {
+                            Ok(()) => sent.set(true),
+                            Err(_) => failed.set(true),
+                        }
+                    },
+                    h1 { "Forgot your password?" }
+                    p { "We'll mail you a link to choose a new one." }
+                    input {
+                        name: "email",
+                        placeholder: "E-mail",
+                        r#type: "email",
+                        required: true,
+                        autofocus: true,
+                        title: "",
+                        value: "{email}",
+                        oninput: move |evt| email.set(evt.value()),
+                    }
+                    if failed() {
+                        p { class: "login-failed", "Unexpected error" }
+                    }
+                    button { width: "100%", r#type: "submit", "Send link" }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub(crate) fn ResetPassword(token: String) -> Element {
+    let mut new_password = use_signal(String::new);
+    let mut fail_message = use_signal(|| None::<String>);
+    let mut reset = use_signal(|| false);
+
+    rsx! {
+        div { class: "content",
+            if reset() {
+                h1 { "Password changed" }
+                p { "You were logged out everywhere, log in with your new password." }
+                Link { to: Route::UserPage, "Log in" }
+            } else {
+                form {
+                    onsubmit: move |evt: FormEvent| {
+                        let token = token.clone();
+                        async move {
+                            evt.prevent_default();
+                            use api::user::ResetPasswordError;
+                            match api::user::reset_password(token, new_password()).await {
+                                Ok(()) => reset.set(true),
+                                Err(ResetPasswordError::InvalidToken) => {
+                                    fail_message
+                                        .set(Some("This link is invalid or expired, ask for a new one".to_string()));
+                                }
+                                Err(ResetPasswordError::PasswordRequirements(requirements)) => {
+                                    fail_message.set(Some(password_requirements(&requirements)));
+                                }
+                                Err(ResetPasswordError::Internal | ResetPasswordError::ServerFnError(_)) => {
+                                    fail_message.set(Some("Unexpected error".to_string()));
+                                }
+                            }
+                        }
+                    },
+                    h1 { "Choose a new password" }
+                    input {
+                        name: "new_password",
+                        placeholder: "New password",
+                        r#type: "password",
+                        required: true,
+                        autofocus: true,
+                        value: "{new_password}",
+                        oninput: move |evt| new_password.set(evt.value()),
+                    }
+                    if let Some(message) = fail_message() {
+                        p { class: "login-failed", "{message}" }
+                    }
+                    button { width: "100%", r#type: "submit", "Change password" }
+                }
+                Link { to: Route::ForgotPassword, "Send a new link" }
+            }
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use chrono::{Datelike as _, Days, NaiveDate};
+use dioxus::prelude::*;
+use food::{api, models};
+
+use crate::layouts::UserContext;
+use crate::router::Route;
+use crate::views::recipe_slug;
+
+const SLOTS: [(models::MealSlot, &str); 3] = [
+    (models::MealSlot::Breakfast, "Breakfast"),
+    (models::MealSlot::Lunch, "Lunch"),
+    (models::MealSlot::Dinner, "Dinner"),
+];
+
+#[component]
+pub(crate) fn Plan() -> Element {
+    let user_ctx = use_context::<UserContext>();
+
+    rsx! {
+        div { id: "plan", class: "content",
+            h1 { "Meal plan" }
+            if user_ctx.user.read().is_some() {
+                Week {}
+            } else {
+                p { "Log in to plan your meals." }
+            }
+        }
+    }
+}
+
+#[component]
+fn Week() -> Element {
+    let nav = use_navigator();
+    let mut week_start = use_signal(|| monday(chrono::Local::now().date_naive()));
+    let mut dragged = use_signal(|| None::<i64>);
+    let mut error = use_signal(|| None::<String>);
+
+    let mut entries = use_resource(move || {
+        let from = week_start();
+        api::meal_plan::plan(from, from + Days::new(6))
+    });
+
+    let entries_read = entries.read();
+    let entries_list = match entries_read.as_ref() {
+        Some(Ok(entries_list)) => entries_list.as_slice(),
+        _ => &[][..],
+    };
+    let days: Vec<NaiveDate> = week_start().iter_days().take(7).collect();
+
+    rsx! {
+        div { class: "flex-row",
+            button { onclick: move |_| week_start.set(week_start() - Days::new(7)), "‹" }
+            span { class: "plan-week", " Week of {week_start} " }
+            button { onclick: move |_| week_start.set(week_start() + Days::new(7)), "›" }
+            button {
+                class: "plan-shopping-list",
+                onclick: move |_| async move {
+                    let from = week_start();
+                    let name = format!("Week of {from}");
+                    match api::shopping_list::create_from_plan(name, from, from + Days::new(6)).await {
+                        Ok(id) => {
+                            nav.push(Route::ShoppingList { list: id });
+                        }
+                        Err(err) => error.set(Some(err.to_string())),
+                    }
+                },
+                "Shopping list"
+            }
+        }
+        if let Some(error) = error() {
+            p { class: "plan-failed", "{error}" }
+        }
+        table { id: "plan-calendar",
+            thead {
+                tr {
+                    th {}
+                    for day in days.iter() {
+                        th { key: "{day}", {day.format("%a %-d %b").to_string()} }
+                    }
+                }
+            }
+            tbody {
+                for (slot, slot_label) in SLOTS {
+                    tr { key: "{slot_label}",
+                        th { "{slot_label}" }
+                        for day in days.iter().copied() {
+                            td {
+                                key: "{day}",
+                                class: "plan-cell",
+                                ondragover: move |evt| evt.prevent_default(),
+                                ondrop: move |evt| async move {
+                                    evt.prevent_default();
+                                    let Some(entry_id) = dragged.take() else {
+                                        return;
+                                    };
+                                    match api::meal_plan::move_entry(entry_id, day, slot).await {
+                                        Ok(()) => error.set(None),
+                                        Err(err) => error.set(Some(err.to_string())),
+                                    }
+                                    entries.restart();
+                                },
+                                for entry in entries_list.iter().filter(|entry| entry.date == day && entry.slot == slot) {
+                                    PlannedMeal {
+                                        key: "{entry.id}",
+                                        entry: entry.clone(),
+                                        on_drag: move |entry_id| dragged.set(Some(entry_id)),
+                                        on_change: move |()| entries.restart(),
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        AddPlannedMeal {
+            default_date: week_start(),
+            on_add: move |()| entries.restart(),
+        }
+    }
+}
+
+#[component]
+fn PlannedMeal(
+    entry: models::MealPlanEntry,
+    on_drag: EventHandler<i64>,
+    on_change: EventHandler,
+) -> Element {
+    let entry_id = entry.id;
+
+    rsx! {
+        div {
+            class: "planned-meal",
+            draggable: "true",
+            ondragstart: move |_| on_drag.call(entry_id),
+            Link {
+                to: Route::Recipe {
+                    recipe: recipe_slug(entry.recipe_id, &entry.recipe_title),
+                },
+                "{entry.recipe_title}"
+            }
+            div { class: "flex-row",
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{entry.servings}",
+                    title: "Servings",
+                    onchange: move |evt| async move {
+                        let Ok(servings) = evt.value().parse() else {
+                            return;
+                        };
+                        if let Err(err) = api::meal_plan::set_servings(entry_id, servings).await {
+                            tracing::warn!("failed to change servings: {err}");
+                        }
+                        on_change.call(());
+                    },
+                }
+                button {
+                    title: "Remove",
+                    onclick: move |_| async move {
+                        if let Err(err) = api::meal_plan::remove_entry(entry_id).await {
+                            tracing::warn!("failed to remove planned meal: {err}");
+                        }
+                        on_change.call(());
+                    },
+                    "✕"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn AddPlannedMeal(default_date: NaiveDate, on_add: EventHandler) -> Element {
+    let recipes = use_resource(|| api::recipe::listing(models::ListingQuery::default()));
+    let mut recipe_id = use_signal(|| None::<i64>);
+    let mut date = use_signal(|| default_date.to_string());
+    let mut slot = use_signal(|| models::MealSlot::Dinner);
+    let mut servings = use_signal(|| 4_u32);
+    let mut error = use_signal(|| None::<String>);
+
+    let recipes_read = recipes.read();
+    let recipes_list = match recipes_read.as_ref() {
+        Some(Ok(recipes_list)) => recipes_list.as_slice(),
+        _ => &[][..],
+    };
+    let first_id = recipes_list.first().map(|recipe| recipe.id);
+
+    rsx! {
+        form {
+            id: "plan-form",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                let Some(recipe_id) = recipe_id().or(first_id) else {
+                    error.set(Some("Pick a recipe".to_string()));
+                    return;
+                };
+                let Ok(date) = date().parse::<NaiveDate>() else {
+                    error.set(Some("Pick a date".to_string()));
+                    return;
+                };
+                match api::meal_plan::add_entry(recipe_id, date, slot(), servings()).await {
+                    Ok(_) => {
+                        error.set(None);
+                        on_add.call(());
+                    }
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            },
+            h4 { "Plan a meal" }
+            select { onchange: move |evt| recipe_id.set(evt.value().parse().ok()),
+                for recipe in recipes_list {
+                    option { key: "{recipe.id}", value: "{recipe.id}", "{recipe.title}" }
+                }
+            }
+            div { class: "flex-row",
+                input {
+                    r#type: "date",
+                    required: true,
+                    value: "{date}",
+                    oninput: move |evt| date.set(evt.value()),
+                }
+                select {
+                    onchange: move |evt| {
+                        if let Some((value, _)) = SLOTS
+                            .into_iter()
+                            .find(|(_, label)| *label == evt.value())
+                        {
+                            slot.set(value);
+                        }
+                    },
+                    for (value, label) in SLOTS {
+                        option { value: label, selected: value == slot(), "{label}" }
+                    }
+                }
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{servings}",
+                    title: "Servings",
+                    oninput: move |evt| {
+                        if let Ok(value) = evt.value().parse() {
+                            servings.set(value);
+                        }
+                    },
+                }
+            }
+            if let Some(error) = error() {
+                p { class: "plan-failed", "{error}" }
+            }
+            button { r#type: "submit", "Add to plan" }
+        }
+    }
+}
+
+fn monday(date: NaiveDate) -> NaiveDate {
+    date - Days::new(u64::from(date.weekday().num_days_from_monday()))
+}
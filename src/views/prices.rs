@@ -0,0 +1,125 @@
+use chrono::Local;
+use dioxus::prelude::*;
+use food::api;
+
+use crate::layouts::UserContext;
+
+#[component]
+pub(crate) fn Prices() -> Element {
+    let user_ctx = use_context::<UserContext>();
+
+    rsx! {
+        div { id: "prices", class: "content",
+            h1 { "Prices" }
+            if user_ctx.user.read().is_some() {
+                PriceList {}
+            } else {
+                p { "Log in to keep track of ingredient prices." }
+            }
+        }
+    }
+}
+
+#[component]
+fn PriceList() -> Element {
+    let mut prices = use_resource(api::price::current);
+
+    let prices_read = prices.read();
+    let price_list = match prices_read.as_ref() {
+        Some(Ok(price_list)) => price_list.as_slice(),
+        _ => &[][..],
+    };
+
+    rsx! {
+        PriceForm { on_change: move |()| prices.restart() }
+        if price_list.is_empty() {
+            p { "No prices yet." }
+        }
+        table { id: "price-list",
+            for price in price_list {
+                tr { key: "{price.id}",
+                    td { "{price.ingredient}" }
+                    td { "{price.store}" }
+                    td { class: "price", "{price.price:.2} / {price.unit}" }
+                    td { class: "effective-from", "since {price.effective_from}" }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PriceForm(on_change: EventHandler) -> Element {
+    let mut ingredient = use_signal(String::new);
+    let mut store = use_signal(String::new);
+    let mut price = use_signal(String::new);
+    let mut unit = use_signal(String::new);
+    let mut effective_from = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    rsx! {
+        form {
+            id: "price-form",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                let Ok(price_value) = price().trim().parse::<f64>() else {
+                    error.set(Some("The price must be a number".to_string()));
+                    return;
+                };
+                // Prices apply from today unless a date is given.
+                let from = effective_from()
+                    .parse()
+                    .unwrap_or_else(|_| Local::now().date_naive());
+                match api::price::set(ingredient(), store(), price_value, unit(), from).await {
+                    Ok(_) => {
+                        ingredient.set(String::new());
+                        price.set(String::new());
+                        unit.set(String::new());
+                        effective_from.set(String::new());
+                        error.set(None);
+                        on_change.call(());
+                    }
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            },
+            div { class: "flex-row",
+                input {
+                    placeholder: "Ingredient",
+                    value: "{ingredient}",
+                    required: true,
+                    oninput: move |evt| ingredient.set(evt.value()),
+                }
+                input {
+                    placeholder: "Store",
+                    value: "{store}",
+                    required: true,
+                    oninput: move |evt| store.set(evt.value()),
+                }
+            }
+            div { class: "flex-row",
+                input {
+                    placeholder: "Price",
+                    value: "{price}",
+                    required: true,
+                    oninput: move |evt| price.set(evt.value()),
+                }
+                input {
+                    placeholder: "per unit, e.g. kg",
+                    value: "{unit}",
+                    required: true,
+                    oninput: move |evt| unit.set(evt.value()),
+                }
+                input {
+                    r#type: "date",
+                    title: "Effective from",
+                    value: "{effective_from}",
+                    oninput: move |evt| effective_from.set(evt.value()),
+                }
+            }
+            if let Some(error) = error() {
+                p { class: "price-failed", "{error}" }
+            }
+            button { r#type: "submit", "Set price" }
+        }
+    }
+}
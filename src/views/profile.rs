@@ -0,0 +1,192 @@
+use dioxus::prelude::*;
+use food::api;
+
+use super::register::password_requirements;
+use crate::layouts::UserContext;
+use crate::router::Route;
+
+const PASSWORD_CHANGED: &str = "Your password was changed and your other sessions were logged out";
+
+/// Changing the username, email address and password of the logged in user.
+#[component]
+pub(super) fn ProfileSettings() -> Element {
+    let mut profile = use_resource(api::user::profile);
+    let mut username = use_signal(String::new);
+    let mut new_email = use_signal(String::new);
+    let mut current_password = use_signal(String::new);
+    let mut new_password = use_signal(String::new);
+    let mut message = use_signal(|| None::<String>);
+    let mut fail_message = use_signal(|| None::<String>);
+
+    let profile_read = profile.read();
+    let Some(Ok(current)) = profile_read.as_ref() else {
+        return rsx! {};
+    };
+    let email = current.email.clone();
+    let pending_email = current.pending_email.clone();
+
+    rsx! {
+        div { class: "profile",
+            h3 { "Profile" }
+            form {
+                onsubmit: move |evt: FormEvent| async move {
+                    evt.prevent_default();
+                    match api::user::change_username(username()).await {
+                        Ok(user) => {
+                            username.set(String::new());
+                            fail_message.set(None);
+                            message.set(Some("Your username was changed".to_string()));
+                            consume_context::<UserContext>().user.set(Some(user));
+                            profile.restart();
+                        }
+                        Err(err) => fail_message.set(Some(profile_error_message(err))),
+                    }
+                },
+                input {
+                    name: "username",
+                    placeholder: "{current.username}",
+                    required: true,
+                    value: "{username}",
+                    oninput: move |evt| username.set(evt.value()),
+                }
+                button { r#type: "submit", "Change username" }
+            }
+            p { "Your e-mail address is {current.email}." }
+            if let Some(pending_email) = pending_email {
+                p { "Open the link mailed to {pending_email} to make it your e-mail address." }
+            }
+            form {
+                onsubmit: move |evt: FormEvent| async move {
+                    evt.prevent_default();
+                    match api::user::change_email(new_email()).await {
+                        Ok(()) => {
+                            new_email.set(String::new());
+                            fail_message.set(None);
+                            message.set(None);
+                            profile.restart();
+                        }
+                        Err(err) => fail_message.set(Some(profile_error_message(err))),
+                    }
+                },
+                input {
+                    name: "email",
+                    r#type: "email",
+                    placeholder: "New e-mail address",
+                    required: true,
+                    value: "{new_email}",
+                    oninput: move |evt| new_email.set(evt.value()),
+                }
+                button { r#type: "submit", "Change e-mail address" }
+            }
+            form {
+                onsubmit: move |evt: FormEvent| {
+                    let email = email.clone();
+                    async move {
+                        evt.prevent_default();
+                        match api::user::change_password(email, current_password(), new_password())
+                            .await
+                        {
+                            Ok(()) => {
+                                current_password.set(String::new());
+                                new_password.set(String::new());
+                                fail_message.set(None);
+                                message.set(Some(PASSWORD_CHANGED.to_string()));
+                            }
+                            Err(err) => fail_message.set(Some(change_password_error_message(err))),
+                        }
+                    }
+                },
+                input {
+                    name: "current_password",
+                    r#type: "password",
+                    placeholder: "Current password",
+                    autocomplete: "current-password",
+                    required: true,
+                    value: "{current_password}",
+                    oninput: move |evt| current_password.set(evt.value()),
+                }
+                input {
+                    name: "new_password",
+                    r#type: "password",
+                    placeholder: "New password",
+                    autocomplete: "new-password",
+                    required: true,
+                    value: "{new_password}",
+                    oninput: move |evt| new_password.set(evt.value()),
+                }
+                button { r#type: "submit", "Change password" }
+            }
+            if let Some(message) = message() {
+                p { "{message}" }
+            }
+            if let Some(message) = fail_message() {
+                p { class: "profile-failed", "{message}" }
+            }
+        }
+    }
+}
+
+#[component]
+pub(crate) fn ConfirmEmail(token: String) -> Element {
+    let confirmed = use_resource(move || api::user::confirm_email_change(token.clone()));
+    let confirmed_read = confirmed.read();
+
+    rsx! {
+        div { class: "content",
+            match confirmed_read.as_ref() {
+                None => rsx! {
+                    p { "Changing your e-mail address..." }
+                },
+                Some(Ok(())) => rsx! {
+                    h1 { "E-mail address changed" }
+                    p { "Log in with your new e-mail address from now on." }
+                    Link { to: Route::UserPage, "Go to your profile" }
+                },
+                Some(Err(api::user::ProfileError::InvalidToken)) => rsx! {
+                    p { class: "profile-failed",
+                        "This link is invalid or expired, change your e-mail address again to get a new one."
+                    }
+                },
+                Some(Err(api::user::ProfileError::AlreadyExists)) => rsx! {
+                    p { class: "profile-failed",
+                        "Someone else uses this e-mail address by now, choose another one."
+                    }
+                },
+                Some(Err(_)) => rsx! {
+                    p { class: "profile-failed", "Unexpected error" }
+                },
+            }
+        }
+    }
+}
+
+fn profile_error_message(err: api::user::ProfileError) -> String {
+    use api::user::ProfileError;
+    match err {
+        ProfileError::Unauthorized => "Log in first",
+        ProfileError::Forbidden => "Log in with your password to do this",
+        ProfileError::EmptyUsername => "Enter a username",
+        ProfileError::InvalidEmail => "Enter a valid e-mail address",
+        ProfileError::SameEmail => "This is your e-mail address already",
+        ProfileError::AlreadyExists => "Someone else uses this e-mail address",
+        ProfileError::InvalidToken => "This link is invalid or expired",
+        ProfileError::TooManyRequests => "Wait a few minutes before changing it again",
+        ProfileError::Internal | ProfileError::ServerFnError(_) => "Unexpected error",
+    }
+    .to_string()
+}
+
+fn change_password_error_message(err: api::user::ChangePasswordError) -> String {
+    use api::user::ChangePasswordError;
+    match err {
+        ChangePasswordError::Unauthorized => "Log in first".to_string(),
+        ChangePasswordError::Forbidden => "You can't change this password".to_string(),
+        ChangePasswordError::PasswordRequirements(requirements) => {
+            password_requirements(&requirements)
+        }
+        ChangePasswordError::WrongCurrentPassword => "Wrong current password".to_string(),
+        ChangePasswordError::Internal | ChangePasswordError::ServerFnError(_) => {
+            "Unexpected error".to_string()
+        }
+    }
+}
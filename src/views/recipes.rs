@@ -1,4 +1,8 @@
+use crate::layouts::UserContext;
 use crate::router::Route;
+use crate::views::collections::AddToCollection;
+use crate::views::equipment::RecipeEquipment;
+use crate::views::revisions::{History, RecipeEditor};
 use food::{api, models};
 
 use dioxus::prelude::*;
@@ -15,7 +19,18 @@ pub(crate) fn Recipes() -> Element {
 
 #[component]
 pub(crate) fn RecipeList() -> Element {
-    let response = use_server_future(api::recipe::listing)?;
+    let user_ctx = use_context::<UserContext>();
+    let mut favorites_only = use_signal(|| false);
+    let mut owned_equipment_only = use_signal(|| true);
+    let mut sort = use_signal(models::ListingSort::default);
+
+    let response = use_server_future(move || {
+        api::recipe::listing(models::ListingQuery {
+            favorites_only: favorites_only(),
+            owned_equipment_only: owned_equipment_only(),
+            sort: sort(),
+        })
+    })?;
     let response_read = response.read();
     // SAFETY: If the future was still pending, it would have early returned
     // with 'suspended' on the `?` above.
@@ -23,35 +38,92 @@ pub(crate) fn RecipeList() -> Element {
         return HttpError::internal_server_error("failed to list recipes")?;
     };
 
+    let favorites_class = if favorites_only() {
+        "filter active"
+    } else {
+        "filter"
+    };
+
     rsx! {
+        div { id: "recipe-filters", class: "flex-row",
+            if user_ctx.user.read().is_some() {
+                button {
+                    class: favorites_class,
+                    onclick: move |_| favorites_only.toggle(),
+                    "Favorites"
+                }
+                button {
+                    class: if owned_equipment_only() { "filter active" } else { "filter" },
+                    title: "Hide recipes that need equipment you don't own",
+                    onclick: move |_| owned_equipment_only.toggle(),
+                    "My equipment"
+                }
+            }
+            select {
+                id: "recipe-sort",
+                onchange: move |evt| {
+                    sort.set(
+                        match evt.value().as_str() {
+                            "rating" => models::ListingSort::Rating,
+                            _ => models::ListingSort::Title,
+                        },
+                    );
+                },
+                option { value: "title", "By title" }
+                option { value: "rating", "By rating" }
+            }
+        }
         ul { id: "recipe-listings",
             for listing in &recipe_listings {
                 li { key: "{listing.id}", class: "recipe-listing",
                     Link {
                         to: Route::Recipe {
-                            recipe: recipe_listing_to_slug(listing),
+                            recipe: recipe_slug(listing.id, &listing.title),
                         },
                         "{listing.title}"
                     }
+                    if let Some(average_rating) = listing.average_rating {
+                        span { class: "rating", " ★ {average_rating:.1} ({listing.rating_count})" }
+                    }
                 }
             }
         }
     }
 }
 
-fn recipe_listing_to_slug(listing: &models::RecipeListing) -> String {
+pub(crate) fn recipe_slug(id: i64, title: &str) -> String {
     use heck::ToKebabCase as _;
-    format!("{}-{}", listing.id, listing.title.to_kebab_case())
+    format!("{id}-{}", title.to_kebab_case())
+}
+
+/// A link from one recipe to another that loads the page from scratch, the recipe page and its
+/// sections keep what they fetched when only the recipe in the route changes.
+fn recipe_href(id: i64, title: &str) -> String {
+    Route::Recipe {
+        recipe: recipe_slug(id, title),
+    }
+    .to_string()
 }
 
 #[component]
 pub(crate) fn Recipe(recipe: String) -> Element {
+    let user_ctx = use_context::<UserContext>();
     let (id, _title) = recipe.split_once('-').or_not_found("recipe not found")?;
     let id = id
         .parse::<i64>()
         .or_bad_request(format!("bad recipe id `{id}`"))?;
 
-    let response = use_server_future(move || api::recipe::by_id(id))?;
+    let nav = use_navigator();
+    let mut editing = use_signal(|| false);
+    let mut response = use_server_future(move || api::recipe::by_id(id))?;
+    // An edit or revert can change the title, and with it the slug.
+    let reload = move |title: String| {
+        editing.set(false);
+        nav.replace(Route::Recipe {
+            recipe: recipe_slug(id, &title),
+        });
+        response.restart();
+    };
     let response_read = response.read();
     // SAFETY: If the future was still pending, it would have early returned
     // with 'suspended' on the `?` above.
@@ -59,32 +131,84 @@ pub(crate) fn Recipe(recipe: String) -> Element {
         return HttpError::not_found("no such recipe")?;
     };
 
-    let expected_slug = recipe_listing_to_slug(&models::RecipeListing {
-        id,
-        title: r.title.clone(),
-    });
-    if expected_slug != recipe {
+    if recipe_slug(id, &r.title) != recipe {
         return HttpError::not_found("no such recipe")?;
     }
 
+    if editing() {
+        let content = models::RecipeContent {
+            title: r.title.clone(),
+            description: r.description.clone(),
+            servings: r.servings,
+            ingredients: r.ingredients.clone(),
+            instructions: r.instructions.clone(),
+        };
+        return rsx! {
+            div { class: "content",
+                RecipeEditor {
+                    recipe_id: id,
+                    content,
+                    on_save: reload,
+                    on_cancel: move |()| editing.set(false),
+                }
+            }
+        };
+    }
+
     rsx! {
         div { class: "content",
             div { id: "recipe",
-                h2 { "{r.title}" }
+                div { class: "flex-row",
+                    h2 { "{r.title}" }
+                    FavoriteToggle { recipe_id: id }
+                    if r.is_own {
+                        button { class: "edit-recipe", onclick: move |_| editing.set(true), "Edit" }
+                    }
+                    if user_ctx.user.read().is_some() {
+                        ForkButton { recipe_id: id }
+                    }
+                }
+                if let Some(forked_from) = r.forked_from {
+                    VariationOf { recipe_id: forked_from }
+                }
                 div { id: "meal-type",
                     p { "{r.meal_type.to_uppercase()}" }
                 }
+                match r.visibility {
+                    models::Visibility::Private => rsx! {
+                        p { class: "visibility", "Only visible to you" }
+                    },
+                    models::Visibility::Household => rsx! {
+                        p { class: "visibility", "Shared with your household" }
+                    },
+                    models::Visibility::Public => rsx! {},
+                }
+                if r.is_own {
+                    RecipeSharing {
+                        recipe_id: id,
+                        visibility: r.visibility,
+                        household_id: r.household_id,
+                        on_change: move |()| response.restart(),
+                    }
+                }
+                RecipeOwner { recipe_id: id, on_change: move |()| response.restart() }
                 p { "{r.description}" }
 
                 div {
                     h4 { "Ingredients" }
-                    ul { id: "ingredients",
-                        for ingredient in &r.ingredients {
-                            li { "{ingredient.quantity} {ingredient.unit} {ingredient.name}" }
-                        }
-                    }
+                    p { class: "servings", "Serves {r.servings}" }
+                    Ingredients { recipe_id: id, ingredients: r.ingredients.clone() }
                 }
 
+                RecipeEquipment {
+                    recipe_id: id,
+                    equipment: r.equipment.clone(),
+                    is_own: r.is_own,
+                    on_change: move |()| response.restart(),
+                }
+                Cost { recipe_id: id }
+                Nutrition { recipe_id: id }
+
                 div {
                     h4 { "Instructions" }
                     ol {
@@ -95,6 +219,14 @@ pub(crate) fn Recipe(recipe: String) -> Element {
                 }
             }
 
+            if user_ctx.user.read().is_some() {
+                AddToCollection { recipe_id: id }
+            }
+            Reviews { recipe_id: id }
+            CookingLog { recipe_id: id }
+            Variations { recipe_id: id }
+            History { recipe_id: id, is_own: r.is_own, on_revert: reload }
+
             div { id: "footer",
                 if let Some(source_url) = r.source_url.as_ref() {
                     a { href: "{source_url}", "{r.source_name}" }
@@ -106,3 +238,553 @@ pub(crate) fn Recipe(recipe: String) -> Element {
         }
     }
 }
+
+/// The ingredients of a recipe, with what can be used instead of them.
+#[component]
+fn Ingredients(recipe_id: i64, ingredients: Vec<models::Ingredient>) -> Element {
+    let substitutions = use_resource(move || api::substitution::for_recipe(recipe_id));
+
+    let substitutions_read = substitutions.read();
+    let substitution_list = match substitutions_read.as_ref() {
+        Some(Ok(substitution_list)) => substitution_list.as_slice(),
+        _ => &[][..],
+    };
+
+    rsx! {
+        ul { id: "ingredients",
+            for ingredient in &ingredients {
+                if let Some(sub_recipe_id) = ingredient.sub_recipe_id {
+                    li {
+                        "{ingredient.quantity} {ingredient.unit} "
+                        a { href: recipe_href(sub_recipe_id, &ingredient.name), "{ingredient.name}" }
+                    }
+                } else {
+                    li {
+                        "{ingredient.quantity} {ingredient.unit} {ingredient.name}"
+                        for substitution in substitution_list
+                            .iter()
+                            .filter(|substitution| substitution.ingredient.eq_ignore_ascii_case(&ingredient.name))
+                        {
+                            p { key: "{substitution.id}", class: "substitution",
+                                "or {replacement_quantity(ingredient.quantity, substitution.ratio)} {ingredient.unit} {substitution.replacement}"
+                                if !substitution.note.is_empty() {
+                                    " ({substitution.note})"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How much of a replacement to use, rounded for display.
+fn replacement_quantity(quantity: f64, ratio: f64) -> f64 {
+    (quantity * ratio * 100.0).round() / 100.0
+}
+
+#[component]
+fn FavoriteToggle(recipe_id: i64) -> Element {
+    let user_ctx = use_context::<UserContext>();
+    let mut is_favorite = use_resource(move || api::recipe::is_favorite(recipe_id));
+
+    if user_ctx.user.read().is_none() {
+        return rsx! {};
+    }
+
+    let favorite = matches!(*is_favorite.read(), Some(Ok(true)));
+    let (label, title) = if favorite {
+        ("♥", "Remove from favorites")
+    } else {
+        ("♡", "Add to favorites")
+    };
+
+    rsx! {
+        button {
+            class: "favorite-toggle",
+            title,
+            onclick: move |_| async move {
+                let result = if favorite {
+                    api::recipe::remove_favorite(recipe_id).await
+                } else {
+                    api::recipe::add_favorite(recipe_id).await
+                };
+                if let Err(err) = result {
+                    tracing::warn!("failed to toggle favorite: {err}");
+                }
+                is_favorite.restart();
+            },
+            "{label}"
+        }
+    }
+}
+
+/// Who the owner shares the recipe with: nobody, one of their households or everyone.
+#[component]
+fn RecipeSharing(
+    recipe_id: i64,
+    visibility: models::Visibility,
+    household_id: Option<i64>,
+    on_change: EventHandler,
+) -> Element {
+    let households = use_resource(api::household::listing);
+    let mut fail_message = use_signal(|| None::<String>);
+
+    let households_read = households.read();
+    let household_list = match households_read.as_ref() {
+        Some(Ok(household_list)) => household_list.as_slice(),
+        _ => &[][..],
+    };
+    let current = match (visibility, household_id) {
+        (models::Visibility::Household, Some(household_id)) => format!("household-{household_id}"),
+        (models::Visibility::Public, _) => "public".to_string(),
+        _ => "private".to_string(),
+    };
+
+    rsx! {
+        div { class: "recipe-sharing",
+            select {
+                name: "visibility",
+                onchange: move |evt| async move {
+                    let value = evt.value();
+                    let (visibility, household_id) = match value.strip_prefix("household-") {
+                        Some(household_id) => (models::Visibility::Household, household_id.parse().ok()),
+                        None if value == "public" => (models::Visibility::Public, None),
+                        None => (models::Visibility::Private, None),
+                    };
+                    match api::recipe::set_visibility(recipe_id, visibility, household_id).await {
+                        Ok(()) => {
+                            fail_message.set(None);
+                            on_change.call(());
+                        }
+                        Err(err) => fail_message.set(Some(err.to_string())),
+                    }
+                },
+                option { value: "private", selected: current == "private", "Only visible to you" }
+                for household in household_list {
+                    option {
+                        key: "{household.id}",
+                        value: "household-{household.id}",
+                        selected: current == format!("household-{}", household.id),
+                        "Shared with {household.name}"
+                    }
+                }
+                option { value: "public", selected: current == "public", "Visible to everyone" }
+            }
+            if let Some(message) = fail_message() {
+                p { class: "recipe-sharing-failed", "{message}" }
+            }
+        }
+    }
+}
+
+/// Lets user managers hand a recipe to someone, for recipes from before recipes had owners.
+#[component]
+fn RecipeOwner(recipe_id: i64, on_change: EventHandler) -> Element {
+    let own_permissions = use_resource(api::user::own_permissions);
+    let mut email = use_signal(String::new);
+    let mut fail_message = use_signal(|| None::<String>);
+
+    let may_manage_users = matches!(
+        own_permissions.read().as_ref(),
+        Some(Ok(permissions)) if permissions.contains(&models::Permission::ManageUsers)
+    );
+    if !may_manage_users {
+        return rsx! {};
+    }
+
+    rsx! {
+        form {
+            class: "recipe-owner",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                match api::recipe::set_owner(recipe_id, email()).await {
+                    Ok(()) => {
+                        email.set(String::new());
+                        fail_message.set(None);
+                        on_change.call(());
+                    }
+                    Err(err) => fail_message.set(Some(err.to_string())),
+                }
+            },
+            input {
+                name: "owner_email",
+                r#type: "email",
+                placeholder: "E-mail address of the new owner",
+                required: true,
+                value: "{email}",
+                oninput: move |evt| email.set(evt.value()),
+            }
+            button { r#type: "submit", "Change owner" }
+            if let Some(message) = fail_message() {
+                p { class: "recipe-sharing-failed", "{message}" }
+            }
+        }
+    }
+}
+
+/// Copy the recipe into a variation of the current user and open it.
+#[component]
+fn ForkButton(recipe_id: i64) -> Element {
+    let nav = use_navigator();
+
+    rsx! {
+        button {
+            class: "fork-recipe",
+            title: "Make your own variation of this recipe",
+            onclick: move |_| async move {
+                let fork = match api::recipe::fork(recipe_id).await {
+                    Ok(fork) => fork,
+                    Err(err) => {
+                        tracing::warn!("failed to fork recipe: {err}");
+                        return;
+                    }
+                };
+                match api::recipe::by_id(fork).await {
+                    Ok(recipe) => {
+                        nav.push(NavigationTarget::<Route>::External(recipe_href(fork, &recipe.title)));
+                    }
+                    Err(err) => tracing::warn!("failed to open forked recipe: {err}"),
+                }
+            },
+            "Fork"
+        }
+    }
+}
+
+/// Links to the recipe this recipe is a variation of, if the viewer can see it.
+#[component]
+fn VariationOf(recipe_id: i64) -> Element {
+    let original = use_resource(move || api::recipe::by_id(recipe_id));
+
+    let original_read = original.read();
+    let Some(Ok(o)) = original_read.as_ref() else {
+        return rsx! {};
+    };
+
+    rsx! {
+        p { class: "variation-of",
+            "Variation of "
+            a { href: recipe_href(recipe_id, &o.title), "{o.title}" }
+        }
+    }
+}
+
+#[component]
+fn Variations(recipe_id: i64) -> Element {
+    let variations = use_resource(move || api::recipe::variations(recipe_id));
+
+    let variations_read = variations.read();
+    let variation_list = match variations_read.as_ref() {
+        Some(Ok(variation_list)) => variation_list.as_slice(),
+        _ => &[][..],
+    };
+    if variation_list.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { id: "variations",
+            h4 { "Variations" }
+            ul {
+                for variation in variation_list {
+                    li { key: "{variation.id}",
+                        a { href: recipe_href(variation.id, &variation.title), "{variation.title}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn Cost(recipe_id: i64) -> Element {
+    let cost = use_resource(move || api::recipe::cost(recipe_id));
+
+    let cost_read = cost.read();
+    let Some(Ok(c)) = cost_read.as_ref() else {
+        return rsx! {};
+    };
+    // Without any prices there's nothing worth showing.
+    if c.total <= 0.0 {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { id: "cost",
+            p { class: "cost-per-serving", "Cost per serving: {c.per_serving:.2}" }
+            if !c.unpriced.is_empty() {
+                p { class: "cost-unpriced",
+                    "Not included: "
+                    {c.unpriced.join(", ")}
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn Nutrition(recipe_id: i64) -> Element {
+    let nutrition = use_resource(move || api::recipe::nutrition(recipe_id));
+
+    let nutrition_read = nutrition.read();
+    let Some(Ok(n)) = nutrition_read.as_ref() else {
+        return rsx! {};
+    };
+    let per_serving = n.per_serving;
+
+    rsx! {
+        div { id: "nutrition",
+            h4 { "Nutrition per serving" }
+            table {
+                tr {
+                    td { "Energy" }
+                    td { "{per_serving.kcal:.0} kcal" }
+                }
+                tr {
+                    td { "Protein" }
+                    td { "{per_serving.protein:.1} g" }
+                }
+                tr {
+                    td { "Fat" }
+                    td { "{per_serving.fat:.1} g" }
+                }
+                tr {
+                    td { "Carbohydrates" }
+                    td { "{per_serving.carbohydrates:.1} g" }
+                }
+            }
+            if !n.unresolved.is_empty() {
+                p { class: "nutrition-unresolved",
+                    "Not included: "
+                    {n.unresolved.join(", ")}
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn Reviews(recipe_id: i64) -> Element {
+    let user_ctx = use_context::<UserContext>();
+    let mut reviews = use_resource(move || api::recipe::reviews(recipe_id));
+
+    let reviews_read = reviews.read();
+    let reviews_list = match reviews_read.as_ref() {
+        Some(Ok(reviews_list)) => reviews_list.as_slice(),
+        _ => &[][..],
+    };
+    let own = reviews_list.iter().find(|review| review.is_own).cloned();
+    let form_key = own.as_ref().map_or(0, |review| review.id);
+
+    rsx! {
+        div { id: "reviews",
+            h4 { "Reviews" }
+            if user_ctx.user.read().is_some() {
+                ReviewForm {
+                    key: "{form_key}",
+                    recipe_id,
+                    own,
+                    on_change: move |()| reviews.restart(),
+                }
+            }
+            if reviews_list.is_empty() {
+                p { "No reviews yet." }
+            }
+            for review in reviews_list {
+                div { key: "{review.id}", class: "review",
+                    div { class: "flex-row",
+                        span { class: "rating", {stars(review.rating)} }
+                        span { class: "review-author", "{review.username}, {review.creation_date}" }
+                    }
+                    if let Some(text) = review.text.as_ref() {
+                        p { "{text}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ReviewForm(recipe_id: i64, own: Option<models::Review>, on_change: EventHandler) -> Element {
+    let mut rating = use_signal(|| own.as_ref().map_or(5, |review| review.rating));
+    let mut text = use_signal(|| {
+        own.as_ref()
+            .and_then(|review| review.text.clone())
+            .unwrap_or_default()
+    });
+    let mut error = use_signal(|| None::<String>);
+    let has_review = own.is_some();
+
+    rsx! {
+        form {
+            id: "review-form",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                let text = text();
+                let text = (!text.trim().is_empty()).then_some(text);
+                match api::recipe::set_review(recipe_id, rating(), text).await {
+                    Ok(()) => {
+                        error.set(None);
+                        on_change.call(());
+                    }
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            },
+            select {
+                value: "{rating}",
+                onchange: move |evt| {
+                    if let Ok(value) = evt.value().parse() {
+                        rating.set(value);
+                    }
+                },
+                for value in (1..=5).rev() {
+                    option { value: "{value}", {stars(value)} }
+                }
+            }
+            textarea {
+                value: "{text}",
+                placeholder: "How did it turn out?",
+                oninput: move |evt| text.set(evt.value()),
+            }
+            if let Some(error) = error() {
+                p { class: "review-failed", "{error}" }
+            }
+            div { class: "flex-row",
+                button { r#type: "submit",
+                    if has_review {
+                        "Update review"
+                    } else {
+                        "Add review"
+                    }
+                }
+                if has_review {
+                    button {
+                        r#type: "button",
+                        onclick: move |_| async move {
+                            match api::recipe::delete_review(recipe_id).await {
+                                Ok(()) => on_change.call(()),
+                                Err(err) => error.set(Some(err.to_string())),
+                            }
+                        },
+                        "Delete review"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn CookingLog(recipe_id: i64) -> Element {
+    let user_ctx = use_context::<UserContext>();
+    let mut cooking_log = use_resource(move || api::recipe::cooking_log(recipe_id));
+
+    let cooking_log_read = cooking_log.read();
+    let entries = match cooking_log_read.as_ref() {
+        Some(Ok(entries)) => entries.as_slice(),
+        _ => &[][..],
+    };
+
+    rsx! {
+        div { id: "cooking-log",
+            h4 { "Cooking log" }
+            if user_ctx.user.read().is_some() {
+                CookingLogForm { recipe_id, on_change: move |()| cooking_log.restart() }
+            }
+            if entries.is_empty() {
+                p { "Nobody has made this yet." }
+            }
+            ul {
+                for entry in entries {
+                    li { key: "{entry.id}", class: "cooking-log-entry",
+                        span { class: "cooked-on", "{entry.cooked_on}" }
+                        " {entry.username} made {entry.servings} servings"
+                        if let Some(notes) = entry.notes.as_ref() {
+                            p { "{notes}" }
+                        }
+                        if entry.is_own {
+                            button {
+                                onclick: {
+                                    let entry_id = entry.id;
+                                    move |_| async move {
+                                        if let Err(err) = api::recipe::delete_cooking_log_entry(entry_id).await {
+                                            tracing::warn!("failed to delete cooking log entry: {err}");
+                                        }
+                                        cooking_log.restart();
+                                    }
+                                },
+                                "Remove"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn CookingLogForm(recipe_id: i64, on_change: EventHandler) -> Element {
+    let mut cooked_on = use_signal(String::new);
+    let mut servings = use_signal(|| 4_u32);
+    let mut notes = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    rsx! {
+        form {
+            id: "cooking-log-form",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                let Ok(date) = cooked_on().parse::<chrono::NaiveDate>() else {
+                    error.set(Some("Pick a date".to_string()));
+                    return;
+                };
+                let notes = notes();
+                let notes = (!notes.trim().is_empty()).then_some(notes);
+                match api::recipe::log_cooking(recipe_id, date, servings(), notes).await {
+                    Ok(()) => {
+                        error.set(None);
+                        on_change.call(());
+                    }
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            },
+            div { class: "flex-row",
+                input {
+                    r#type: "date",
+                    required: true,
+                    value: "{cooked_on}",
+                    oninput: move |evt| cooked_on.set(evt.value()),
+                }
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{servings}",
+                    title: "Servings",
+                    oninput: move |evt| {
+                        if let Ok(value) = evt.value().parse() {
+                            servings.set(value);
+                        }
+                    },
+                }
+            }
+            textarea {
+                value: "{notes}",
+                placeholder: "What did you change?",
+                oninput: move |evt| notes.set(evt.value()),
+            }
+            if let Some(error) = error() {
+                p { class: "cooking-log-failed", "{error}" }
+            }
+            button { r#type: "submit", "I made this" }
+        }
+    }
+}
+
+fn stars(rating: u8) -> String {
+    let rating = usize::from(rating.min(5));
+    format!("{}{}", "★".repeat(rating), "☆".repeat(5 - rating))
+}
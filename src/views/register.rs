@@ -0,0 +1,117 @@
+use dioxus::fullstack::Form;
+use dioxus::prelude::*;
+use food::api;
+
+use crate::router::Route;
+
+#[component]
+pub(crate) fn Register() -> Element {
+    let mut fail_state = use_signal(|| rsx! {});
+    let mut registered = use_signal(|| None::<String>);
+
+    rsx! {
+        div { class: "content",
+            if let Some(email) = registered() {
+                h1 { "Check your e-mail" }
+                p { "We sent a link to {email}, open it to verify your address and then log in." }
+                Link { to: Route::UserPage, "Log in" }
+            } else {
+                form {
+                    onsubmit: move |evt: FormEvent| async move {
+                        evt.prevent_default();
+                        let register_form: api::user::RegisterForm = evt.parsed_values().unwrap();
+                        let email = register_form.email.clone();
+                        match api::user::register(Form(register_form)).await {
+                            Ok(()) => registered.set(Some(email)),
+                            Err(register_error) => {
+                                use api::user::RegisterError;
+                                let message = match register_error {
+                                    RegisterError::PasswordRequirement(requirements) => {
+                                        password_requirements(&requirements)
+                                    }
+                                    RegisterError::Internal | RegisterError::ServerFnError(_) => {
+                                        "Unexpected error".to_string()
+                                    }
+                                };
+                                fail_state.set(rsx! {
+                                    p { class: "login-failed", "{message}" }
+                                });
+                            }
+                        }
+                    },
+                    h1 { "Create an account" }
+                    input {
+                        name: "username",
+                        placeholder: "Name",
+                        required: true,
+                        autofocus: true,
+                    }
+                    input {
+                        name: "email",
+                        placeholder: "E-mail",
+                        r#type: "email",
+                        required: true,
+                        title: "",
+                    }
+                    input {
+                        name: "password",
+                        placeholder: "Password",
+                        r#type: "password",
+                        required: true,
+                    }
+                    div { {fail_state} }
+                    button { width: "100%", r#type: "submit", "Create account" }
+                }
+                Link { to: Route::UserPage, "I already have an account" }
+            }
+        }
+    }
+}
+
+#[component]
+pub(crate) fn VerifyEmail(token: String) -> Element {
+    let verified = use_resource(move || api::user::verify_email(token.clone()));
+    let verified_read = verified.read();
+
+    rsx! {
+        div { class: "content",
+            match verified_read.as_ref() {
+                None => rsx! {
+                    p { "Verifying your e-mail address..." }
+                },
+                Some(Ok(())) => rsx! {
+                    h1 { "E-mail address verified" }
+                    Link { to: Route::UserPage, "Log in" }
+                },
+                Some(Err(api::user::VerifyEmailError::InvalidToken)) => rsx! {
+                    p { class: "login-failed",
+                        "This link is invalid or expired, log in to get a new one."
+                    }
+                },
+                Some(Err(_)) => rsx! {
+                    p { class: "login-failed", "Unexpected error" }
+                },
+            }
+        }
+    }
+}
+
+pub(super) fn password_requirements(requirements: &[api::user::PasswordRequirement]) -> String {
+    use api::user::PasswordRequirement;
+    let requirements = requirements
+        .iter()
+        .map(|requirement| match requirement {
+            PasswordRequirement::PasswordTooShort { min_length } => {
+                format!("at least {min_length} characters")
+            }
+            PasswordRequirement::PasswordTooLong { max_length } => {
+                format!("at most {max_length} characters")
+            }
+            PasswordRequirement::NoLowerCase => "a lowercase letter".to_string(),
+            PasswordRequirement::NoUppercase => "an uppercase letter".to_string(),
+            PasswordRequirement::NoDigit => "a digit".to_string(),
+            PasswordRequirement::NoSpecial => "a special character".to_string(),
+        })
+        .collect::<Vec<_>>();
+    format!("The password needs {}", requirements.join(", "))
+}
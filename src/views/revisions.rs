@@ -0,0 +1,325 @@
+use dioxus::prelude::*;
+use food::{api, models};
+
+/// Edit the content of a recipe. Ingredients are entered one per line as "quantity unit name",
+/// or as "quantity @title" to use servings of another recipe, instructions one step per line.
+#[component]
+pub(crate) fn RecipeEditor(
+    recipe_id: i64,
+    content: models::RecipeContent,
+    on_save: EventHandler<String>,
+    on_cancel: EventHandler,
+) -> Element {
+    let mut title = use_signal(|| content.title.clone());
+    let mut description = use_signal(|| content.description.clone());
+    let mut servings = use_signal(|| content.servings.to_string());
+    let mut ingredients = use_signal(|| {
+        content
+            .ingredients
+            .iter()
+            .map(|ingredient| {
+                if ingredient.sub_recipe_id.is_some() {
+                    format!("{} @{}", ingredient.quantity, ingredient.name)
+                } else {
+                    format!(
+                        "{} {} {}",
+                        ingredient.quantity, ingredient.unit, ingredient.name
+                    )
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    let mut instructions = use_signal(|| content.instructions.join("\n"));
+    let mut error = use_signal(|| None::<String>);
+    let recipes = use_resource(|| api::recipe::listing(models::ListingQuery::default()));
+    // The sub-recipes the recipe already has stay resolvable even if the listing fails.
+    let current_sub_recipes: Vec<(String, i64)> = content
+        .ingredients
+        .iter()
+        .filter_map(|ingredient| Some((ingredient.name.clone(), ingredient.sub_recipe_id?)))
+        .collect();
+
+    rsx! {
+        form {
+            id: "recipe-editor",
+            onsubmit: move |evt: FormEvent| {
+                let current_sub_recipes = current_sub_recipes.clone();
+                async move {
+                    evt.prevent_default();
+                    let Ok(servings_value) = servings().trim().parse::<u32>() else {
+                        error.set(Some("Servings must be a whole number".to_string()));
+                        return;
+                    };
+                    let sub_recipe_id = |title: &str| {
+                        let listed = match recipes.read().as_ref() {
+                            Some(Ok(listings)) => listings
+                                .iter()
+                                .find(|listing| listing.title.eq_ignore_ascii_case(title))
+                                .map(|listing| listing.id),
+                            _ => None,
+                        };
+                        listed.or_else(|| {
+                            current_sub_recipes
+                                .iter()
+                                .find(|(name, _)| name.eq_ignore_ascii_case(title))
+                                .map(|(_, id)| *id)
+                        })
+                    };
+                    let ingredient_list = match parse_ingredients(&ingredients(), sub_recipe_id) {
+                        Ok(ingredient_list) => ingredient_list,
+                        Err(line) => {
+                            error.set(Some(format!("Can't read the ingredient `{line}`")));
+                            return;
+                        }
+                    };
+                    let content = models::RecipeContent {
+                        title: title(),
+                        description: description(),
+                        servings: servings_value,
+                        ingredients: ingredient_list,
+                        instructions: instructions().lines().map(str::to_string).collect(),
+                    };
+                    match api::revision::update(recipe_id, content).await {
+                        Ok(_) => {
+                            error.set(None);
+                            on_save.call(title().trim().to_string());
+                        }
+                        Err(err) => error.set(Some(err.to_string())),
+                    }
+                }
+            },
+            input {
+                placeholder: "Title",
+                value: "{title}",
+                required: true,
+                oninput: move |evt| title.set(evt.value()),
+            }
+            textarea {
+                value: "{description}",
+                placeholder: "Description",
+                oninput: move |evt| description.set(evt.value()),
+            }
+            input {
+                r#type: "number",
+                min: "1",
+                title: "Servings",
+                value: "{servings}",
+                oninput: move |evt| servings.set(evt.value()),
+            }
+            textarea {
+                class: "recipe-editor-ingredients",
+                value: "{ingredients}",
+                placeholder: "One ingredient per line, e.g. 3 dl milk or 1 @Pizza dough",
+                oninput: move |evt| ingredients.set(evt.value()),
+            }
+            textarea {
+                class: "recipe-editor-instructions",
+                value: "{instructions}",
+                placeholder: "One step per line",
+                oninput: move |evt| instructions.set(evt.value()),
+            }
+            if let Some(error) = error() {
+                p { class: "recipe-editor-failed", "{error}" }
+            }
+            div { class: "flex-row",
+                button { r#type: "submit", "Save" }
+                button { r#type: "button", onclick: move |_| on_cancel.call(()), "Cancel" }
+            }
+        }
+    }
+}
+
+/// Parse lines of "quantity unit name" or "quantity @title", returning the first line that
+/// doesn't parse as error. `sub_recipe_id` looks up recipes by title.
+fn parse_ingredients(
+    text: &str,
+    sub_recipe_id: impl Fn(&str) -> Option<i64>,
+) -> Result<Vec<models::Ingredient>, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            if let Some((quantity, title)) = line.split_once('@') {
+                let title = title.trim();
+                return match (quantity.trim().parse(), sub_recipe_id(title)) {
+                    (Ok(quantity), Some(id)) => Ok(models::Ingredient {
+                        quantity,
+                        unit: "serving".to_string(),
+                        name: title.to_string(),
+                        sub_recipe_id: Some(id),
+                    }),
+                    _ => Err(line.to_string()),
+                };
+            }
+            let mut parts = line.splitn(3, char::is_whitespace);
+            let quantity = parts.next().and_then(|quantity| quantity.parse().ok());
+            let unit = parts.next();
+            let name = parts.next().map(str::trim);
+            match (quantity, unit, name) {
+                (Some(quantity), Some(unit), Some(name)) if !name.is_empty() => {
+                    Ok(models::Ingredient {
+                        quantity,
+                        unit: unit.to_string(),
+                        name: name.to_string(),
+                        sub_recipe_id: None,
+                    })
+                }
+                _ => Err(line.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// The revisions of a recipe, each can be compared to the one before it. The owner can revert
+/// to older revisions, `on_revert` gets the title the recipe has after the revert.
+#[component]
+pub(crate) fn History(recipe_id: i64, is_own: bool, on_revert: EventHandler<String>) -> Element {
+    let mut revisions = use_resource(move || api::revision::listing(recipe_id));
+    let mut selected = use_signal(|| None::<u32>);
+    let mut error = use_signal(|| None::<String>);
+
+    let revisions_read = revisions.read();
+    let revision_list = match revisions_read.as_ref() {
+        Some(Ok(revision_list)) => revision_list.as_slice(),
+        _ => &[][..],
+    };
+    if revision_list.is_empty() {
+        return rsx! {};
+    }
+    let latest = revision_list[0].number;
+
+    rsx! {
+        div { id: "history",
+            h4 { "History" }
+            ul {
+                for revision in revision_list {
+                    li { key: "{revision.number}", class: "revision",
+                        button {
+                            class: "revision-link",
+                            disabled: revision.number == 1,
+                            onclick: {
+                                let number = revision.number;
+                                move |_| {
+                                    let next = (selected() != Some(number)).then_some(number);
+                                    selected.set(next);
+                                }
+                            },
+                            "#{revision.number}"
+                        }
+                        span { class: "revision-author",
+                            " "
+                            {revision.author.clone().unwrap_or_else(|| "someone".to_string())}
+                            ", "
+                            {revision.created_at.format("%Y-%m-%d %H:%M").to_string()}
+                        }
+                        if is_own && revision.number != latest {
+                            button {
+                                onclick: {
+                                    let number = revision.number;
+                                    move |_| async move {
+                                        match revert(recipe_id, number).await {
+                                            Ok(title) => {
+                                                error.set(None);
+                                                selected.set(None);
+                                                revisions.restart();
+                                                on_revert.call(title);
+                                            }
+                                            Err(err) => error.set(Some(err.to_string())),
+                                        }
+                                    }
+                                },
+                                "Revert"
+                            }
+                        }
+                        if selected() == Some(revision.number) {
+                            RevisionDiff { recipe_id, number: revision.number }
+                        }
+                    }
+                }
+            }
+            if let Some(error) = error() {
+                p { class: "revision-failed", "{error}" }
+            }
+        }
+    }
+}
+
+/// Revert to a revision, returning the title the recipe has afterwards.
+async fn revert(recipe_id: i64, number: u32) -> Result<String, api::revision::RevisionError> {
+    api::revision::revert(recipe_id, number).await?;
+    let revision = api::revision::by_number(recipe_id, number).await?;
+    Ok(revision.content.title)
+}
+
+/// What changed in a revision compared to the revision before it.
+#[component]
+fn RevisionDiff(recipe_id: i64, number: u32) -> Element {
+    let diff = use_resource(move || api::revision::diff(recipe_id, number - 1, number));
+
+    let diff_read = diff.read();
+    let Some(Ok(d)) = diff_read.as_ref() else {
+        return rsx! {};
+    };
+
+    rsx! {
+        div { class: "revision-diff",
+            if d.fields.is_empty() && d.ingredients.is_empty() && d.instructions.is_empty() {
+                p { "No changes." }
+            }
+            for change in &d.fields {
+                p { class: "changed",
+                    {field_name(change.field)}
+                    ": "
+                    del { "{change.old}" }
+                    " → "
+                    ins { "{change.new}" }
+                }
+            }
+            for change in &d.ingredients {
+                match change {
+                    models::IngredientChange::Added(ingredient) => rsx! {
+                        p { class: "added", "+ {ingredient.quantity} {ingredient.unit} {ingredient.name}" }
+                    },
+                    models::IngredientChange::Removed(ingredient) => rsx! {
+                        p { class: "removed", "− {ingredient.quantity} {ingredient.unit} {ingredient.name}" }
+                    },
+                    models::IngredientChange::Changed { old, new } => rsx! {
+                        p { class: "changed",
+                            "{new.name}: "
+                            del { "{old.quantity} {old.unit}" }
+                            " → "
+                            ins { "{new.quantity} {new.unit}" }
+                        }
+                    },
+                }
+            }
+            for change in &d.instructions {
+                match change {
+                    models::InstructionChange::Added { step, text } => rsx! {
+                        p { class: "added", "+ Step {step}: {text}" }
+                    },
+                    models::InstructionChange::Removed { step, text } => rsx! {
+                        p { class: "removed", "− Step {step}: {text}" }
+                    },
+                    models::InstructionChange::Edited { step, old, new } => rsx! {
+                        p { class: "changed",
+                            "Step {step}: "
+                            del { "{old}" }
+                            " → "
+                            ins { "{new}" }
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+fn field_name(field: models::RecipeField) -> &'static str {
+    match field {
+        models::RecipeField::Title => "Title",
+        models::RecipeField::Description => "Description",
+        models::RecipeField::Servings => "Servings",
+    }
+}
@@ -0,0 +1,87 @@
+use dioxus::prelude::*;
+use food::{api, models};
+
+/// Where the logged in user is logged in, with a way to log out elsewhere.
+#[component]
+pub(super) fn SessionSettings() -> Element {
+    let mut sessions = use_resource(api::user::sessions);
+    let mut fail_message = use_signal(|| None::<String>);
+
+    let sessions_read = sessions.read();
+    let session_list = match sessions_read.as_ref() {
+        Some(Ok(session_list)) => session_list.as_slice(),
+        _ => &[][..],
+    };
+    let has_others = session_list.iter().any(|session| !session.is_current);
+
+    rsx! {
+        div { class: "sessions",
+            h3 { "Sessions" }
+            ul {
+                for session in session_list {
+                    li { key: "{session.id}",
+                        SessionSummary { session: session.clone() }
+                        if !session.is_current {
+                            button {
+                                onclick: {
+                                    let id = session.id;
+                                    move |_| async move {
+                                        match api::user::log_out_session(id).await {
+                                            Ok(()) => fail_message.set(None),
+                                            Err(err) => fail_message.set(Some(session_error_message(err))),
+                                        }
+                                        sessions.restart();
+                                    }
+                                },
+                                "Log out"
+                            }
+                        }
+                    }
+                }
+            }
+            if has_others {
+                button {
+                    onclick: move |_| async move {
+                        match api::user::log_out_other_sessions().await {
+                            Ok(()) => fail_message.set(None),
+                            Err(err) => fail_message.set(Some(session_error_message(err))),
+                        }
+                        sessions.restart();
+                    },
+                    "Log out all other sessions"
+                }
+            }
+            if let Some(message) = fail_message() {
+                p { class: "sessions-failed", "{message}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn SessionSummary(session: models::Session) -> Element {
+    let client = session
+        .user_agent
+        .unwrap_or_else(|| "Unknown client".to_string());
+    let ip = session.ip.map_or(String::new(), |ip| format!(" from {ip}"));
+
+    rsx! {
+        span { class: "session",
+            if session.is_current {
+                strong { "This session: " }
+            }
+            "{client}{ip}, logged in {session.created_at}, last active {session.last_seen_at} "
+        }
+    }
+}
+
+fn session_error_message(err: api::user::SessionError) -> String {
+    use api::user::SessionError;
+    match err {
+        SessionError::Unauthorized => "Log in first",
+        SessionError::Forbidden => "Log in with your password to do this",
+        SessionError::NotFound => "The session is logged out already",
+        SessionError::Internal | SessionError::ServerFnError(_) => "Unexpected error",
+    }
+    .to_string()
+}
@@ -0,0 +1,250 @@
+use chrono::{Days, NaiveDate};
+use dioxus::prelude::*;
+use food::{api, models};
+
+use crate::layouts::UserContext;
+use crate::router::Route;
+
+#[component]
+pub(crate) fn ShoppingLists() -> Element {
+    let user_ctx = use_context::<UserContext>();
+
+    rsx! {
+        div { id: "shopping-lists", class: "content",
+            h1 { "Shopping lists" }
+            if user_ctx.user.read().is_some() {
+                Outlet::<Route> {}
+            } else {
+                p { "Log in to make shopping lists." }
+            }
+        }
+    }
+}
+
+#[component]
+pub(crate) fn ShoppingListList() -> Element {
+    let response = use_server_future(api::shopping_list::listing)?;
+    let response_read = response.read();
+    // SAFETY: If the future was still pending, it would have early returned
+    // with 'suspended' on the `?` above.
+    let Ok(lists) = response_read.as_ref().unwrap() else {
+        return HttpError::internal_server_error("failed to list shopping lists")?;
+    };
+
+    rsx! {
+        ul { id: "shopping-list-listings",
+            for list in lists {
+                li { key: "{list.id}",
+                    Link {
+                        to: Route::ShoppingList {
+                            list: list.id,
+                        },
+                        "{list.name}"
+                    }
+                    span { class: "shopping-list-progress",
+                        " {list.checked_count}/{list.item_count}, {list.creation_date}"
+                    }
+                }
+            }
+        }
+        NewShoppingList {}
+    }
+}
+
+/// Creates a shopping list from either a range of the meal plan or a selection of recipes.
+#[component]
+fn NewShoppingList() -> Element {
+    let nav = use_navigator();
+    let recipes = use_resource(|| api::recipe::listing(models::ListingQuery::default()));
+    let today = chrono::Local::now().date_naive();
+    let mut name = use_signal(String::new);
+    let mut from_plan = use_signal(|| true);
+    let mut from = use_signal(|| today.to_string());
+    let mut to = use_signal(|| (today + Days::new(6)).to_string());
+    // The servings to shop for per recipe, recipes without servings are left out.
+    let mut servings = use_signal(Vec::<models::RecipeSelection>::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let recipes_read = recipes.read();
+    let recipes_list = match recipes_read.as_ref() {
+        Some(Ok(recipes_list)) => recipes_list.as_slice(),
+        _ => &[][..],
+    };
+
+    rsx! {
+        form {
+            id: "new-shopping-list",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                let result = if from_plan() {
+                    let dates = (from().parse::<NaiveDate>(), to().parse::<NaiveDate>());
+                    let (Ok(from), Ok(to)) = dates else {
+                        error.set(Some("Pick the dates to shop for".to_string()));
+                        return;
+                    };
+                    api::shopping_list::create_from_plan(name(), from, to).await
+                } else {
+                    let recipes: Vec<_> = servings()
+                        .into_iter()
+                        .filter(|selection| selection.servings > 0)
+                        .collect();
+                    if recipes.is_empty() {
+                        error.set(Some("Pick at least one recipe".to_string()));
+                        return;
+                    }
+                    api::shopping_list::create_from_recipes(name(), recipes).await
+                };
+                match result {
+                    Ok(id) => {
+                        nav.push(Route::ShoppingList { list: id });
+                    }
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            },
+            h4 { "New shopping list" }
+            input {
+                placeholder: "Name",
+                value: "{name}",
+                required: true,
+                oninput: move |evt| name.set(evt.value()),
+            }
+            div { class: "flex-row",
+                button {
+                    r#type: "button",
+                    class: if from_plan() { "filter active" } else { "filter" },
+                    onclick: move |_| from_plan.set(true),
+                    "From meal plan"
+                }
+                button {
+                    r#type: "button",
+                    class: if from_plan() { "filter" } else { "filter active" },
+                    onclick: move |_| from_plan.set(false),
+                    "From recipes"
+                }
+            }
+            if from_plan() {
+                div { class: "flex-row",
+                    input {
+                        r#type: "date",
+                        value: "{from}",
+                        oninput: move |evt| from.set(evt.value()),
+                    }
+                    input {
+                        r#type: "date",
+                        value: "{to}",
+                        oninput: move |evt| to.set(evt.value()),
+                    }
+                }
+            } else {
+                table { id: "shopping-list-recipes",
+                    for recipe in recipes_list {
+                        tr { key: "{recipe.id}",
+                            td { "{recipe.title}" }
+                            td {
+                                input {
+                                    r#type: "number",
+                                    min: "0",
+                                    placeholder: "0",
+                                    title: "Servings",
+                                    oninput: {
+                                        let recipe_id = recipe.id;
+                                        move |evt: FormEvent| {
+                                            let Ok(value) = evt.value().parse() else {
+                                                return;
+                                            };
+                                            let mut servings = servings.write();
+                                            servings.retain(|selection| selection.recipe_id != recipe_id);
+                                            servings
+                                                .push(models::RecipeSelection {
+                                                    recipe_id,
+                                                    servings: value,
+                                                });
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(error) = error() {
+                p { class: "shopping-list-failed", "{error}" }
+            }
+            button { r#type: "submit", "Create" }
+        }
+    }
+}
+
+#[component]
+pub(crate) fn ShoppingList(list: i64) -> Element {
+    let list_id = list;
+    let nav = use_navigator();
+    let mut response = use_server_future(move || api::shopping_list::by_id(list_id))?;
+    let mut cost = use_resource(move || api::shopping_list::cost(list_id));
+    let response_read = response.read();
+    // SAFETY: If the future was still pending, it would have early returned
+    // with 'suspended' on the `?` above.
+    let Ok(l) = response_read.as_ref().unwrap() else {
+        return HttpError::not_found("no such shopping list")?;
+    };
+
+    rsx! {
+        div { id: "shopping-list",
+            h2 { "{l.name}" }
+            p { class: "shopping-list-progress", "{l.creation_date}" }
+            if l.items.is_empty() {
+                p { "Nothing to buy." }
+            }
+            ul {
+                for item in &l.items {
+                    li {
+                        key: "{item.id}",
+                        class: if item.checked { "shopping-list-item checked" } else { "shopping-list-item" },
+                        label { class: "checkbox-container",
+                            input {
+                                r#type: "checkbox",
+                                checked: item.checked,
+                                onchange: {
+                                    let item_id = item.id;
+                                    move |evt: FormEvent| async move {
+                                        if let Err(err) = api::shopping_list::set_checked(item_id, evt.checked()).await {
+                                            tracing::warn!("failed to check off item: {err}");
+                                        }
+                                        response.restart();
+                                        cost.restart();
+                                    }
+                                },
+                            }
+                            span { class: "checkmark" }
+                            span { class: "shopping-list-item-text",
+                                "{item.quantity} {item.unit} {item.name}"
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(Ok(estimate)) = cost.read().as_ref() {
+                p { class: "shopping-list-cost",
+                    "Estimated cost: {estimate.total:.2}"
+                }
+                if !estimate.unpriced.is_empty() {
+                    p { class: "cost-unpriced",
+                        "Not included: "
+                        {estimate.unpriced.join(", ")}
+                    }
+                }
+            }
+            button {
+                onclick: move |_| async move {
+                    match api::shopping_list::delete(list_id).await {
+                        Ok(()) => {
+                            nav.push(Route::ShoppingListList);
+                        }
+                        Err(err) => tracing::warn!("failed to delete shopping list: {err}"),
+                    }
+                },
+                "Delete list"
+            }
+        }
+    }
+}
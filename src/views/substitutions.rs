@@ -0,0 +1,127 @@
+use dioxus::prelude::*;
+use food::api;
+
+use crate::layouts::UserContext;
+
+#[component]
+pub(crate) fn Substitutions() -> Element {
+    let user_ctx = use_context::<UserContext>();
+
+    rsx! {
+        div { id: "substitutions", class: "content",
+            h1 { "Substitutions" }
+            if user_ctx.user.read().is_some() {
+                SubstitutionList {}
+            } else {
+                p { "Log in to see what to use when you're out of something." }
+            }
+        }
+    }
+}
+
+#[component]
+fn SubstitutionList() -> Element {
+    let mut substitutions = use_resource(api::substitution::listing);
+
+    let substitutions_read = substitutions.read();
+    let substitution_list = match substitutions_read.as_ref() {
+        Some(Ok(substitution_list)) => substitution_list.as_slice(),
+        _ => &[][..],
+    };
+
+    rsx! {
+        SubstitutionForm { on_change: move |()| substitutions.restart() }
+        if substitution_list.is_empty() {
+            p { "No substitutions yet." }
+        }
+        table { id: "substitution-list",
+            for substitution in substitution_list {
+                tr { key: "{substitution.id}",
+                    td { "{substitution.ingredient}" }
+                    td { "{substitution.ratio} × {substitution.replacement}" }
+                    td { class: "substitution-note", "{substitution.note}" }
+                    td {
+                        button {
+                            title: "Remove",
+                            onclick: {
+                                let substitution_id = substitution.id;
+                                move |_| async move {
+                                    if let Err(err) = api::substitution::remove(substitution_id).await {
+                                        tracing::warn!("failed to remove substitution: {err}");
+                                    }
+                                    substitutions.restart();
+                                }
+                            },
+                            "✕"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Add a substitution, or change the ratio and note of an existing one.
+#[component]
+fn SubstitutionForm(on_change: EventHandler) -> Element {
+    let mut ingredient = use_signal(String::new);
+    let mut replacement = use_signal(String::new);
+    let mut ratio = use_signal(|| "1".to_string());
+    let mut note = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    rsx! {
+        form {
+            id: "substitution-form",
+            onsubmit: move |evt: FormEvent| async move {
+                evt.prevent_default();
+                let Ok(ratio_value) = ratio().trim().parse::<f64>() else {
+                    error.set(Some("The ratio must be a number".to_string()));
+                    return;
+                };
+                match api::substitution::set(ingredient(), replacement(), ratio_value, note()).await {
+                    Ok(_) => {
+                        ingredient.set(String::new());
+                        replacement.set(String::new());
+                        ratio.set("1".to_string());
+                        note.set(String::new());
+                        error.set(None);
+                        on_change.call(());
+                    }
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            },
+            div { class: "flex-row",
+                input {
+                    placeholder: "Ingredient",
+                    value: "{ingredient}",
+                    required: true,
+                    oninput: move |evt| ingredient.set(evt.value()),
+                }
+                input {
+                    placeholder: "Replacement",
+                    value: "{replacement}",
+                    required: true,
+                    oninput: move |evt| replacement.set(evt.value()),
+                }
+            }
+            div { class: "flex-row",
+                input {
+                    title: "How much of the replacement to use for one of the ingredient",
+                    value: "{ratio}",
+                    required: true,
+                    oninput: move |evt| ratio.set(evt.value()),
+                }
+                input {
+                    placeholder: "Note",
+                    value: "{note}",
+                    oninput: move |evt| note.set(evt.value()),
+                }
+            }
+            if let Some(error) = error() {
+                p { class: "substitution-failed", "{error}" }
+            }
+            button { r#type: "submit", "Set substitution" }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use dioxus::prelude::*;
+use food::{api, models};
+
+/// Enabling and disabling two-factor authentication for the logged in user.
+#[component]
+pub(super) fn TwoFactorSettings() -> Element {
+    let mut enabled = use_resource(api::user::two_factor_enabled);
+    let mut enrolment = use_signal(|| None::<models::TotpEnrolment>);
+    let mut recovery_codes = use_signal(Vec::<String>::new);
+    let mut code = use_signal(String::new);
+    let mut fail_message = use_signal(|| None::<String>);
+
+    let enabled_read = enabled.read();
+    let is_enabled = matches!(enabled_read.as_ref(), Some(Ok(true)));
+
+    rsx! {
+        div { class: "two-factor",
+            h3 { "Two-factor authentication" }
+            if !recovery_codes.read().is_empty() {
+                p {
+                    "Two-factor authentication is on. Keep these recovery codes somewhere safe, each of them logs you in once without your authenticator app:"
+                }
+                ul { class: "recovery-codes",
+                    for recovery_code in recovery_codes() {
+                        li { key: "{recovery_code}", "{recovery_code}" }
+                    }
+                }
+                button { onclick: move |_| recovery_codes.set(Vec::new()), "Done" }
+            } else if is_enabled {
+                p { "Two-factor authentication is on. Enter a code to turn it off." }
+                form {
+                    onsubmit: move |evt: FormEvent| async move {
+                        evt.prevent_default();
+                        match api::user::disable_two_factor(code()).await {
+                            Ok(()) => {
+                                code.set(String::new());
+                                fail_message.set(None);
+                                enabled.restart();
+                            }
+                            Err(err) => fail_message.set(Some(two_factor_error_message(err))),
+                        }
+                    },
+                    CodeInput { code }
+                    button { r#type: "submit", "Turn off" }
+                }
+            } else if let Some(current) = enrolment() {
+                p {
+                    "Add this account to your authenticator app with the link or the key, then enter the code it shows."
+                }
+                a { href: "{current.otpauth_uri}", "Add to authenticator app" }
+                p { class: "totp-secret", "{current.secret}" }
+                form {
+                    onsubmit: move |evt: FormEvent| async move {
+                        evt.prevent_default();
+                        match api::user::confirm_two_factor(code()).await {
+                            Ok(codes) => {
+                                code.set(String::new());
+                                fail_message.set(None);
+                                enrolment.set(None);
+                                recovery_codes.set(codes);
+                                enabled.restart();
+                            }
+                            Err(err) => fail_message.set(Some(two_factor_error_message(err))),
+                        }
+                    },
+                    CodeInput { code }
+                    button { r#type: "submit", "Turn on" }
+                }
+            } else {
+                p { "Protect your account with codes from an authenticator app." }
+                button {
+                    onclick: move |_| async move {
+                        match api::user::begin_two_factor().await {
+                            Ok(new_enrolment) => {
+                                fail_message.set(None);
+                                enrolment.set(Some(new_enrolment));
+                            }
+                            Err(err) => fail_message.set(Some(two_factor_error_message(err))),
+                        }
+                    },
+                    "Set up"
+                }
+            }
+            if let Some(message) = fail_message() {
+                p { class: "two-factor-failed", "{message}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn CodeInput(mut code: Signal<String>) -> Element {
+    rsx! {
+        input {
+            name: "code",
+            placeholder: "Code",
+            autocomplete: "one-time-code",
+            required: true,
+            value: "{code}",
+            oninput: move |evt| code.set(evt.value()),
+        }
+    }
+}
+
+fn two_factor_error_message(err: api::user::TwoFactorError) -> String {
+    use api::user::TwoFactorError;
+    match err {
+        TwoFactorError::Unauthorized => "Log in first",
+        TwoFactorError::Forbidden => "Log in with your password to do this",
+        TwoFactorError::NotEnrolled => "Set up two-factor authentication first",
+        TwoFactorError::AlreadyEnabled => "Two-factor authentication is on already",
+        TwoFactorError::InvalidCode => "Wrong code",
+        TwoFactorError::Internal | TwoFactorError::ServerFnError(_) => "Unexpected error",
+    }
+    .to_string()
+}
@@ -1,7 +1,12 @@
 use dioxus::fullstack::Form;
 use dioxus::prelude::*;
-use food::api;
+use food::{api, models};
 
+use super::api_tokens::ApiTokenSettings;
+use super::households::HouseholdSettings;
+use super::profile::ProfileSettings;
+use super::sessions::SessionSettings;
+use super::two_factor::TwoFactorSettings;
 use crate::layouts::UserContext;
 use crate::router::Route;
 
@@ -18,7 +23,11 @@ pub(crate) fn User() -> Element {
         div { class: "content",
             if let Some(user) = user() {
                 h2 { "Hello, {user.username}" }
-                p { "We're working on the user page here, come back soon! 😊" }
+                ProfileSettings {}
+                TwoFactorSettings {}
+                HouseholdSettings {}
+                SessionSettings {}
+                ApiTokenSettings {}
                 button {
                     onclick: move |_| async move {
                         logout.call().await;
@@ -37,6 +46,18 @@ pub(crate) fn User() -> Element {
 #[component]
 fn Login() -> Element {
     let mut fail_state = use_signal(|| rsx! {});
+    let mut second_factor = use_signal(|| false);
+
+    if second_factor() {
+        return rsx! {
+            SecondFactor {
+                on_start_over: move |()| {
+                    fail_state.set(rsx! {});
+                    second_factor.set(false);
+                },
+            }
+        };
+    }
 
     rsx! {
         div {
@@ -44,19 +65,43 @@ fn Login() -> Element {
                 onsubmit: move |evt: FormEvent| async move {
                     evt.prevent_default();
                     let login_form: api::user::LoginForm = evt.parsed_values().unwrap();
+                    let email = login_form.email.clone();
                     match api::user::login(Form(login_form)).await {
-                        Ok(user) => {
+                        Ok(models::LoginStep::LoggedIn(user)) => {
                             consume_context::<UserContext>().user.set(Some(user));
                         }
-                        Err(login_error) => {
-                            use api::user::LoginError;
+                        Ok(models::LoginStep::SecondFactorRequired) => second_factor.set(true),
+                        Err(api::user::LoginError::EmailNotVerified) => {
                             fail_state.set(rsx! {
                                 a { class: "login-failed",
-                                    match login_error {
-                                        LoginError::InvalidCredentials => "Login failed",
-                                        LoginError::Internal | LoginError::ServerFnError(_) => "Unexpected error",
-                                    }
+                                    "Verify your e-mail address with the link we sent you first"
                                 }
+                                button {
+                                    r#type: "button",
+                                    width: "100%",
+                                    onclick: move |_| {
+                                        let email = email.clone();
+                                        async move {
+                                            let sent = api::user::resend_verification(email).await;
+                                            fail_state.set(rsx! {
+                                                a { class: "login-failed",
+                                                    if sent.is_ok() {
+                                                        "We sent you a new link"
+                                                    } else {
+                                                        "Unexpected error"
+                                                    }
+                                                }
+                                            });
+                                        }
+                                    },
+                                    "Send a new link"
+                                }
+                            });
+                        }
+                        Err(login_error) => {
+                            let message = login_error_message(login_error);
+                            fail_state.set(rsx! {
+                                a { class: "login-failed", "{message}" }
                             });
                         }
                     }
@@ -96,6 +141,76 @@ fn Login() -> Element {
                 }
                 button { width: "100%", r#type: "submit", "Log in" }
             }
+            div { class: "flex-row account-links",
+                Link { to: Route::Register, "Create an account" }
+                Link { to: Route::ForgotPassword, "Forgot your password?" }
+            }
+        }
+    }
+}
+
+#[component]
+fn SecondFactor(on_start_over: EventHandler) -> Element {
+    let mut code = use_signal(String::new);
+    let mut fail_message = use_signal(|| None::<String>);
+
+    rsx! {
+        div {
+            form {
+                onsubmit: move |evt: FormEvent| async move {
+                    evt.prevent_default();
+                    match api::user::login_second_factor(code()).await {
+                        Ok(user) => {
+                            consume_context::<UserContext>().user.set(Some(user));
+                        }
+                        Err(login_error) => {
+                            fail_message.set(Some(login_error_message(login_error)));
+                        }
+                    }
+                },
+                h1 { "Enter your code" }
+                p { "Enter the code from your authenticator app, or one of your recovery codes." }
+                input {
+                    name: "code",
+                    placeholder: "Code",
+                    autocomplete: "one-time-code",
+                    required: true,
+                    autofocus: true,
+                    value: "{code}",
+                    oninput: move |evt| code.set(evt.value()),
+                }
+                if let Some(message) = fail_message() {
+                    a { class: "login-failed", "{message}" }
+                }
+                button { width: "100%", r#type: "submit", "Log in" }
+            }
+            button { width: "100%", onclick: move |_| on_start_over.call(()), "Start over" }
         }
     }
 }
+
+fn login_error_message(login_error: api::user::LoginError) -> String {
+    use api::user::LoginError;
+    match login_error {
+        LoginError::InvalidCredentials => "Login failed".to_string(),
+        LoginError::EmailNotVerified => "Verify your e-mail address first".to_string(),
+        LoginError::InvalidSecondFactor => "Wrong code".to_string(),
+        LoginError::NoPendingLogin => "That took too long, start over".to_string(),
+        LoginError::TooManyAttempts { retry_after } => {
+            format!(
+                "Too many failed logins, try again in {}",
+                wait_time(retry_after)
+            )
+        }
+        LoginError::Internal | LoginError::ServerFnError(_) => "Unexpected error".to_string(),
+    }
+}
+
+/// Rounded up to whole minutes once it's longer than a minute.
+fn wait_time(seconds: u64) -> String {
+    match seconds {
+        0..=1 => "a second".to_string(),
+        2..=60 => format!("{seconds} seconds"),
+        _ => format!("{} minutes", seconds.div_ceil(60)),
+    }
+}